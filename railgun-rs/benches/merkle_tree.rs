@@ -1,9 +1,8 @@
 use criterion::{Criterion, criterion_group, criterion_main};
-use railgun_rs::railgun::merkle_tree::MerkleTree;
+use railgun_rs::railgun::merkle_tree::{MerkleTree, TOTAL_LEAVES};
 use ruint::aliases::U256;
 
-const TREE_DEPTH: usize = 14;
-const FULL_TREE_SIZE: usize = 1 << TREE_DEPTH;
+const FULL_TREE_SIZE: usize = TOTAL_LEAVES;
 
 fn bench_full_tree_fill(c: &mut Criterion) {
     c.bench_function("full_tree_fill", |b| {
@@ -29,5 +28,24 @@ fn bench_single_leaf_edit(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_full_tree_fill, bench_single_leaf_edit);
+fn bench_generate_proof(c: &mut Criterion) {
+    let mut tree = MerkleTree::new(0);
+    let leaves: Vec<U256> = (1..=FULL_TREE_SIZE as u64).map(U256::from).collect();
+    tree.insert_leaves_raw(&leaves, 0);
+    tree.rebuild();
+
+    c.bench_function("generate_proof", |b| {
+        b.iter(|| {
+            tree.generate_proof(U256::from(FULL_TREE_SIZE as u64 / 2))
+                .unwrap()
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_full_tree_fill,
+    bench_single_leaf_edit,
+    bench_generate_proof
+);
 criterion_main!(benches);
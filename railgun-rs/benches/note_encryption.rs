@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use alloy::primitives::Address;
+use criterion::{Criterion, criterion_group, criterion_main};
+use railgun_rs::{
+    caip::AssetId,
+    crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+    railgun::{
+        note::{encrypt::encrypt_note, utxo::UtxoNote},
+        signer::{PrivateKeySigner, Signer},
+    },
+};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+fn bench_note_encryption(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+    let sender_viewing_key = ViewingKey::from_bytes([2u8; 32]);
+    let signer: Arc<dyn Signer> = PrivateKeySigner::new_evm(
+        SpendingKey::from_bytes([3u8; 32]),
+        ViewingKey::from_bytes([4u8; 32]),
+        1,
+    );
+    let receiver = signer.address();
+    let asset = AssetId::Erc20(Address::repeat_byte(1));
+
+    c.bench_function("encrypt_note", |b| {
+        b.iter(|| {
+            encrypt_note(
+                &receiver,
+                &[5u8; 16],
+                100,
+                &asset,
+                "memo",
+                sender_viewing_key,
+                false,
+                false,
+                &mut rng,
+            )
+            .unwrap()
+        });
+    });
+
+    let encrypted = encrypt_note(
+        &receiver,
+        &[5u8; 16],
+        100,
+        &asset,
+        "memo",
+        sender_viewing_key,
+        false,
+        false,
+        &mut rng,
+    )
+    .unwrap();
+
+    c.bench_function("decrypt_note", |b| {
+        b.iter(|| UtxoNote::decrypt(signer.clone(), 0, 0, &encrypted).unwrap());
+    });
+}
+
+criterion_group!(benches, bench_note_encryption);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use railgun_rs::crypto::poseidon::poseidon_hash;
+use ruint::aliases::U256;
+
+fn bench_poseidon_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("poseidon_hash");
+    group.throughput(Throughput::Elements(1));
+
+    //? Merkle nodes hash 2 inputs, note commitments 3, and POI inputs up to 13.
+    for arity in [2, 3, 13] {
+        let inputs: Vec<U256> = (1..=arity as u64).map(U256::from).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(arity), &inputs, |b, inputs| {
+            b.iter(|| poseidon_hash(inputs).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_poseidon_hash);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+//! Benchmarks proving a 3-operation bundle serially versus concurrently.
+//!
+//! Requires the circuit artifacts in `./artifacts`, so it only runs with the
+//! `artifacts` feature. Reads the committed transact inputs fixture.
+use criterion::{Criterion, criterion_group, criterion_main};
+use futures::{StreamExt, stream};
+use railgun_rs::circuit::{
+    inputs::TransactCircuitInputs, native::Groth16Prover, prover::TransactProver,
+};
+
+const BUNDLE_SIZE: usize = 3;
+
+fn bench_prove_bundle(c: &mut Criterion) {
+    let prover = Groth16Prover::new_native("./artifacts");
+    let json = std::fs::read_to_string("./tests/fixtures/transact_inputs.json")
+        .expect("transact inputs fixture must exist");
+    let inputs = TransactCircuitInputs::from_json(&json).unwrap();
+    let bundle = vec![inputs; BUNDLE_SIZE];
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("prove_bundle");
+    group.sample_size(10);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for inputs in &bundle {
+                    prover.prove_transact(inputs).await.unwrap();
+                }
+            })
+        });
+    });
+
+    group.bench_function("concurrent", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                stream::iter(&bundle)
+                    .map(|inputs| prover.prove_transact(inputs))
+                    .buffered(BUNDLE_SIZE)
+                    .for_each(|proof| async move {
+                        proof.unwrap();
+                    })
+                    .await
+            })
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_prove_bundle);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+//! Benchmarks witness calculation and proving for a single transact circuit.
+//!
+//! Requires the circuit artifacts in `./artifacts`, and reads transact inputs
+//! from the committed `tests/fixtures/transact_inputs.json` fixture.
+//! Run with `cargo bench --bench prover --features artifacts`.
+use criterion::{Criterion, criterion_group, criterion_main};
+use futures::executor::block_on;
+use railgun_rs::circuit::{
+    inputs::TransactCircuitInputs, native::Groth16Prover, prover::TransactProver,
+};
+
+fn bench_prover(c: &mut Criterion) {
+    let prover = Groth16Prover::new_native("./artifacts");
+    let json = std::fs::read_to_string("./tests/fixtures/transact_inputs.json")
+        .expect("transact inputs fixture must exist");
+    let inputs = TransactCircuitInputs::from_json(&json).unwrap();
+
+    let mut group = c.benchmark_group("prover");
+    group.sample_size(10);
+
+    group.bench_function("calculate_witness", |b| {
+        b.iter(|| block_on(prover.calculate_witness(&inputs)).unwrap());
+    });
+
+    let witness = block_on(prover.calculate_witness(&inputs)).unwrap();
+    group.bench_function("prove_witness", |b| {
+        b.iter(|| block_on(prover.prove_witness(&witness)).unwrap());
+    });
+
+    group.bench_function("prove_transact", |b| {
+        b.iter(|| block_on(prover.prove_transact(&inputs)).unwrap());
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_prover);
+criterion_main!(benches);
@@ -14,6 +14,8 @@ use crate::crypto::railgun_zero::SNARK_PRIME;
 pub enum TokenDataError {
     #[error("Invalid token data hash length")]
     InvalidHashLength,
+    #[error("Unknown token type: {0}")]
+    UnknownTokenType(u8),
 }
 
 impl TokenData {
@@ -35,7 +37,7 @@ impl TokenData {
         let token_type = match token_type {
             1 => TokenType::ERC721,
             2 => TokenType::ERC1155,
-            _ => unreachable!(),
+            other => return Err(TokenDataError::UnknownTokenType(other)),
         };
         let token_address = Address::from_slice(&hash[44..64]);
         let token_sub_id = U256::from_be_bytes::<32>(hash[64..96].try_into().unwrap());
@@ -47,6 +49,16 @@ impl TokenData {
         })
     }
 
+    /// Returns the token hash for raw token data bytes without interpreting
+    /// the token type. Used to identify tokens the library does not model.
+    pub fn raw_hash(hash: &[u8]) -> U256 {
+        if hash.len() <= 32 {
+            return U256::from_be_slice(hash);
+        }
+
+        hash_to_scalar(hash)
+    }
+
     pub fn hash(&self) -> ruint::aliases::U256 {
         if self.tokenType == TokenType::ERC20 {
             let mut bytes = [0u8; 32];
@@ -95,16 +107,23 @@ impl BoundParams {
         }
     }
 
+    /// Canonical bound params hash, used as a public input to the transact
+    /// circuit. Matches `hashBoundParams` in the JS SDK: the keccak256 of the
+    /// abi-encoded struct, reduced into the SNARK scalar field.
     pub fn hash(&self) -> U256 {
         let encoded = self.abi_encode();
         hash_to_scalar(&encoded)
     }
+
+    /// Returns true if `hash` matches the canonical hash of these bound params.
+    pub fn verify_hash(&self, hash: U256) -> bool {
+        self.hash() == hash
+    }
 }
 
 fn hash_to_scalar(data: &[u8]) -> U256 {
     let hash = keccak256_cached(data);
-    let hash_bigint = U256::from_be_bytes::<32>(hash.as_slice().try_into().unwrap());
-    hash_bigint % SNARK_PRIME
+    U256::from_be_bytes(hash.0) % SNARK_PRIME
 }
 
 sol! {
@@ -171,6 +190,14 @@ sol! {
         bytes memo; // Added to note ciphertext for decryption
     }
 
+    // Emitted in `CommitmentBatch` events by the pre-V2 smart wallet
+    #[derive(Debug, Serialize, Deserialize)]
+    struct LegacyCommitmentCiphertext {
+        uint256[4] ciphertext; // Ciphertext order: IV & tag (16 bytes each), recipient MPK, random & amount (16 bytes each), token
+        uint256[2] ephemeralKeys; // Sender and receiver ephemeral keys
+        uint256[] memo;
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     struct ShieldCiphertext {
         bytes32[3] encryptedBundle; // IV shared (16 bytes), tag (16 bytes), random (16 bytes), IV sender (16 bytes), receiver viewing public key (32 bytes)
@@ -251,6 +278,8 @@ mod tests {
     use ruint::uint;
     use tracing_test::traced_test;
 
+    use alloy_sol_types::SolValue;
+
     use crate::abis::railgun::{BoundParams, CommitmentCiphertext, UnshieldType};
 
     #[test]
@@ -283,4 +312,94 @@ mod tests {
 
         assert_eq!(hash, expected);
     }
+
+    #[test]
+    #[traced_test]
+    fn test_hash_bound_params_unshield() {
+        let bound_params = BoundParams::new(
+            2,
+            1_000_000_000,
+            UnshieldType::NORMAL,
+            137,
+            address!("0x4025ee6512dbbda97049bcf5aa5d38c54af6be8a"),
+            &[9u8; 32],
+            vec![
+                CommitmentCiphertext {
+                    ciphertext: [
+                        FixedBytes::from_slice(&[0x11; 32]),
+                        FixedBytes::from_slice(&[0x12; 32]),
+                        FixedBytes::from_slice(&[0x13; 32]),
+                        FixedBytes::from_slice(&[0x14; 32]),
+                    ],
+                    blindedSenderViewingKey: FixedBytes::from_slice(&[0x21; 32]),
+                    blindedReceiverViewingKey: FixedBytes::from_slice(&[0x31; 32]),
+                    annotationData: Bytes::from(&[0x41u8; 50]),
+                    memo: Bytes::new(),
+                },
+                CommitmentCiphertext {
+                    ciphertext: [
+                        FixedBytes::from_slice(&[0x15; 32]),
+                        FixedBytes::from_slice(&[0x16; 32]),
+                        FixedBytes::from_slice(&[0x17; 32]),
+                        FixedBytes::from_slice(&[0x18; 32]),
+                    ],
+                    blindedSenderViewingKey: FixedBytes::from_slice(&[0x22; 32]),
+                    blindedReceiverViewingKey: FixedBytes::from_slice(&[0x32; 32]),
+                    annotationData: Bytes::from(&[0x42u8; 50]),
+                    memo: Bytes::from(&[0x52u8; 13]),
+                },
+            ],
+        );
+
+        //? Regression vector pinning the current encoding and hash. It hasn't
+        //? been checked against the railgun engine's `hashBoundParams`.
+        let expected_encoded = concat!(
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "000000000000000000000000000000000000000000000000000000003b9aca00",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000089",
+            "0000000000000000000000004025ee6512dbbda97049bcf5aa5d38c54af6be8a",
+            "0909090909090909090909090909090909090909090909090909090909090909",
+            "00000000000000000000000000000000000000000000000000000000000000e0",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "0000000000000000000000000000000000000000000000000000000000000040",
+            "00000000000000000000000000000000000000000000000000000000000001c0",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "1212121212121212121212121212121212121212121212121212121212121212",
+            "1313131313131313131313131313131313131313131313131313131313131313",
+            "1414141414141414141414141414141414141414141414141414141414141414",
+            "2121212121212121212121212121212121212121212121212121212121212121",
+            "3131313131313131313131313131313131313131313131313131313131313131",
+            "0000000000000000000000000000000000000000000000000000000000000100",
+            "0000000000000000000000000000000000000000000000000000000000000160",
+            "0000000000000000000000000000000000000000000000000000000000000032",
+            "4141414141414141414141414141414141414141414141414141414141414141",
+            "4141414141414141414141414141414141410000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "1515151515151515151515151515151515151515151515151515151515151515",
+            "1616161616161616161616161616161616161616161616161616161616161616",
+            "1717171717171717171717171717171717171717171717171717171717171717",
+            "1818181818181818181818181818181818181818181818181818181818181818",
+            "2222222222222222222222222222222222222222222222222222222222222222",
+            "3232323232323232323232323232323232323232323232323232323232323232",
+            "0000000000000000000000000000000000000000000000000000000000000100",
+            "0000000000000000000000000000000000000000000000000000000000000160",
+            "0000000000000000000000000000000000000000000000000000000000000032",
+            "4242424242424242424242424242424242424242424242424242424242424242",
+            "4242424242424242424242424242424242420000000000000000000000000000",
+            "000000000000000000000000000000000000000000000000000000000000000d",
+            "5252525252525252525252525200000000000000000000000000000000000000",
+        );
+        assert_eq!(hex::encode(bound_params.abi_encode()), expected_encoded);
+
+        let hash = bound_params.hash();
+        let expected = uint!(
+            6724028387013346492885564752099425903356670715129987190082987736712407298954_U256
+        );
+
+        assert_eq!(hash, expected);
+        assert!(bound_params.verify_hash(expected));
+        assert!(!bound_params.verify_hash(expected + uint!(1_U256)));
+    }
 }
@@ -1,6 +1,6 @@
 use std::{fmt::Display, str::FromStr};
 
-use alloy::primitives::{Address, U256, Uint};
+use alloy::primitives::{Address, ChainId, U256, Uint};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -14,6 +14,10 @@ pub enum AssetId {
     Erc20(Address),
     Erc721(Address, U256),
     Erc1155(Address, U256),
+    /// A token whose type is not modeled by this library, identified by its
+    /// raw token hash. Notes holding unknown assets are still tracked so their
+    /// value is visible, but they cannot be shielded or unshielded.
+    Unknown(U256),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -22,8 +26,121 @@ pub enum AccountId {
     Railgun(RailgunAddress),
 }
 
+#[derive(Debug, Error)]
+pub enum AccountIdError {
+    #[error("Expected a railgun account, got eip155 address {0}")]
+    NotRailgun(Address),
+    #[error("Expected an eip155 account, got railgun address {0}")]
+    NotEip155(RailgunAddress),
+}
+
+#[derive(Debug, Error)]
+pub enum AccountIdParseError {
+    #[error("Invalid account: expected '0zk...', '0x...', or 'eip155:<chainId>:0x...', got {0}")]
+    InvalidFormat(String),
+    #[error("Invalid chain ID: {0}")]
+    InvalidChainId(String),
+    #[error("Account is for chain {actual}, expected chain {expected}")]
+    ChainMismatch { expected: ChainId, actual: ChainId },
+}
+
+impl AccountId {
+    /// Parses an account, rejecting CAIP-10 accounts for a chain other than
+    /// `chain_id`. See `AccountId::from_str` for the supported formats.
+    pub fn parse_for_chain(s: &str, chain_id: ChainId) -> Result<Self, AccountIdParseError> {
+        let (chain, account) = parse_account(s)?;
+        match chain {
+            Some(actual) if actual != chain_id => Err(AccountIdParseError::ChainMismatch {
+                expected: chain_id,
+                actual,
+            }),
+            _ => Ok(account),
+        }
+    }
+}
+
+impl FromStr for AccountId {
+    type Err = AccountIdParseError;
+
+    /// Parse an AccountId from a string.
+    ///
+    /// Supported formats:
+    /// - `0zk...` - Railgun address
+    /// - `0x...` - EVM address
+    /// - `eip155:1:0x...` - CAIP-10 EVM address. The chain ID isn't checked,
+    ///   use `parse_for_chain` to check it.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_account(s).map(|(_, account)| account)
+    }
+}
+
+/// Parses an account, returning the CAIP-10 chain ID if one was given.
+fn parse_account(s: &str) -> Result<(Option<ChainId>, AccountId), AccountIdParseError> {
+    let invalid = || AccountIdParseError::InvalidFormat(s.to_string());
+
+    if s.starts_with("0zk") {
+        let address: RailgunAddress = s.parse().map_err(|_| invalid())?;
+        return Ok((None, AccountId::Railgun(address)));
+    }
+
+    let (chain, address) = match s.split_once(':') {
+        Some((namespace, rest)) => {
+            if !namespace.eq_ignore_ascii_case("eip155") {
+                return Err(invalid());
+            }
+            let (chain, address) = rest.split_once(':').ok_or_else(invalid)?;
+            let chain: ChainId = chain
+                .parse()
+                .map_err(|_| AccountIdParseError::InvalidChainId(chain.to_string()))?;
+            (Some(chain), address)
+        }
+        None => (None, s),
+    };
+
+    let address: Address = address.parse().map_err(|_| invalid())?;
+    Ok((chain, AccountId::Eip155(address)))
+}
+
+impl From<RailgunAddress> for AccountId {
+    fn from(address: RailgunAddress) -> Self {
+        AccountId::Railgun(address)
+    }
+}
+
+impl From<Address> for AccountId {
+    fn from(address: Address) -> Self {
+        AccountId::Eip155(address)
+    }
+}
+
+impl TryFrom<AccountId> for RailgunAddress {
+    type Error = AccountIdError;
+
+    fn try_from(account: AccountId) -> Result<Self, Self::Error> {
+        match account {
+            AccountId::Railgun(address) => Ok(address),
+            AccountId::Eip155(address) => Err(AccountIdError::NotRailgun(address)),
+        }
+    }
+}
+
+impl TryFrom<AccountId> for Address {
+    type Error = AccountIdError;
+
+    fn try_from(account: AccountId) -> Result<Self, Self::Error> {
+        match account {
+            AccountId::Eip155(address) => Ok(address),
+            AccountId::Railgun(address) => Err(AccountIdError::NotEip155(address)),
+        }
+    }
+}
+
 impl AssetId {
     pub fn hash(&self) -> U256 {
+        if let AssetId::Unknown(hash) = self {
+            return *hash;
+        }
+
         let token_data: TokenData = (*self).into();
         token_data.hash()
     }
@@ -35,6 +152,7 @@ impl Display for AssetId {
             AssetId::Erc20(address) => write!(f, "erc20:{:?}", address),
             AssetId::Erc721(address, sub_id) => write!(f, "erc721:{:?}/{}", address, sub_id),
             AssetId::Erc1155(address, sub_id) => write!(f, "erc1155:{:?}/{}", address, sub_id),
+            AssetId::Unknown(hash) => write!(f, "unknown:{:#066x}", hash),
         }
     }
 }
@@ -57,6 +175,13 @@ impl From<AssetId> for TokenData {
                 tokenAddress: address,
                 tokenSubID: sub_id,
             },
+            //? Unknown assets have no recoverable token data. Produce an invalid
+            //? token type so that any attempt to use it on-chain is rejected.
+            AssetId::Unknown(hash) => TokenData {
+                tokenType: TokenType::__Invalid,
+                tokenAddress: Address::ZERO,
+                tokenSubID: hash,
+            },
         }
     }
 }
@@ -67,7 +192,7 @@ impl From<TokenData> for AssetId {
             TokenType::ERC20 => AssetId::Erc20(token_data.tokenAddress),
             TokenType::ERC721 => AssetId::Erc721(token_data.tokenAddress, token_data.tokenSubID),
             TokenType::ERC1155 => AssetId::Erc1155(token_data.tokenAddress, token_data.tokenSubID),
-            _ => unreachable!(),
+            _ => AssetId::Unknown(token_data.hash()),
         }
     }
 }
@@ -93,6 +218,7 @@ impl FromStr for AssetId {
     /// - `erc20:0x...` - ERC20 token
     /// - `erc721:0x.../123` - ERC721 token with sub ID
     /// - `erc1155:0x.../456` - ERC1155 token with sub ID
+    /// - `unknown:0x...` - Unknown token type with its raw token hash
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (asset_type, rest) = s.split_once(':').ok_or(AssetIdParseError::InvalidFormat)?;
 
@@ -127,6 +253,12 @@ impl FromStr for AssetId {
                     .map_err(|_| AssetIdParseError::InvalidSubId(sub_id_str.to_string()))?;
                 Ok(AssetId::Erc1155(address, sub_id))
             }
+            "unknown" => {
+                let hash: U256 = rest
+                    .parse()
+                    .map_err(|_| AssetIdParseError::InvalidSubId(rest.to_string()))?;
+                Ok(AssetId::Unknown(hash))
+            }
             _ => Err(AssetIdParseError::UnknownType(asset_type.to_string())),
         }
     }
@@ -134,6 +266,8 @@ impl FromStr for AssetId {
 
 #[cfg(test)]
 mod tests {
+    use alloy::primitives::address;
+
     use super::*;
 
     #[test]
@@ -161,4 +295,65 @@ mod tests {
         let hash = erc1155.hash();
         insta::assert_debug_snapshot!(hash);
     }
+
+    #[test]
+    fn test_unknown_token_type() {
+        let token_data = TokenData {
+            tokenType: TokenType::__Invalid,
+            tokenAddress: Address::from_slice(&[4u8; 20]),
+            tokenSubID: U256::from(789),
+        };
+        let hash = token_data.hash();
+
+        let asset: AssetId = token_data.into();
+        assert_eq!(asset, AssetId::Unknown(hash));
+        assert_eq!(asset.hash(), hash);
+
+        let parsed: AssetId = asset.to_string().parse().unwrap();
+        assert_eq!(parsed, asset);
+    }
+
+    #[test]
+    fn test_account_id_conversions() {
+        let railgun: RailgunAddress = "0zk1qyjftlcuuxwjj574e5979wzt5veel9wmnh8peq6slvd668pz9ggzerv7j6fe3z53latpxdq2zqzs7l780x9gu7hfsgn93m27fwx3k6pk8fsrtgrp45ywuctqpkg".parse().unwrap();
+        let eoa = address!("0x1234567890123456789012345678901234567890");
+
+        let account: AccountId = railgun.into();
+        assert_eq!(account, AccountId::Railgun(railgun));
+        assert_eq!(RailgunAddress::try_from(account).unwrap(), railgun);
+        assert!(Address::try_from(account).is_err());
+
+        let account: AccountId = eoa.into();
+        assert_eq!(account, AccountId::Eip155(eoa));
+        assert_eq!(Address::try_from(account).unwrap(), eoa);
+        assert!(RailgunAddress::try_from(account).is_err());
+    }
+
+    #[test]
+    fn test_parse_account_id() {
+        let railgun = "0zk1qyjftlcuuxwjj574e5979wzt5veel9wmnh8peq6slvd668pz9ggzerv7j6fe3z53latpxdq2zqzs7l780x9gu7hfsgn93m27fwx3k6pk8fsrtgrp45ywuctqpkg";
+        let eoa = address!("0x1234567890123456789012345678901234567890");
+
+        let account: AccountId = railgun.parse().unwrap();
+        assert_eq!(account, AccountId::Railgun(railgun.parse().unwrap()));
+
+        let account: AccountId = "0x1234567890123456789012345678901234567890"
+            .parse()
+            .unwrap();
+        assert_eq!(account, AccountId::Eip155(eoa));
+
+        let caip10 = "eip155:1:0x1234567890123456789012345678901234567890";
+        let account = AccountId::parse_for_chain(caip10, 1).unwrap();
+        assert_eq!(account, AccountId::Eip155(eoa));
+        assert!(matches!(
+            AccountId::parse_for_chain(caip10, 11155111),
+            Err(AccountIdParseError::ChainMismatch {
+                expected: 11155111,
+                actual: 1
+            })
+        ));
+
+        assert!("cosmos:hub:abc".parse::<AccountId>().is_err());
+        assert!("0zk1invalid".parse::<AccountId>().is_err());
+    }
 }
@@ -1,4 +1,5 @@
 use alloy::primitives::{Address, ChainId, address};
+use thiserror::Error;
 
 /// Eip155 Chain Configurations
 #[derive(Copy, Clone, Debug)]
@@ -25,8 +26,58 @@ pub struct ChainConfig {
 
     /// Optional POI endpoint for this chain, if available
     pub poi_endpoint: Option<&'static str>,
+    /// Default directory holding the circuit artifacts, laid out as
+    /// `railgun/NNxNN.{zkey,wasm}` and `ppoi/NNxNN.{zkey,wasm}`
+    pub artifacts_path: &'static str,
+
+    /// Railgun shield fee, in basis points. Deducted from the shielded amount.
+    ///
+    /// Sourced from the smart wallet's `shieldFee`
+    pub shield_fee_bps: u16,
+    /// Railgun unshield fee, in basis points. Deducted from the unshielded amount.
+    ///
+    /// Sourced from the smart wallet's `unshieldFee`
+    pub unshield_fee_bps: u16,
 }
 
+impl ChainConfig {
+    /// Splits a shielded amount into the `(net, fee)` amounts. The net amount
+    /// is the value of the resulting shielded note.
+    pub const fn shield_fee(&self, amount: u128) -> Result<(u128, u128), FeeError> {
+        deduct_fee(amount, self.shield_fee_bps)
+    }
+
+    /// Splits an unshielded amount into the `(net, fee)` amounts. The net
+    /// amount is what the receiver gets.
+    pub const fn unshield_fee(&self, amount: u128) -> Result<(u128, u128), FeeError> {
+        deduct_fee(amount, self.unshield_fee_bps)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FeeError {
+    #[error("Fee of {fee_bps} bps on {amount} overflows")]
+    Overflow { amount: u128, fee_bps: u16 },
+    #[error("Fee of {fee_bps} bps exceeds the amount {amount}")]
+    ExceedsAmount { amount: u128, fee_bps: u16 },
+}
+
+/// Matches `getFee` in the railgun contracts with `isInclusive = true`.
+const fn deduct_fee(amount: u128, fee_bps: u16) -> Result<(u128, u128), FeeError> {
+    let fee = match amount.checked_mul(fee_bps as u128) {
+        Some(scaled) => scaled / 10_000,
+        None => return Err(FeeError::Overflow { amount, fee_bps }),
+    };
+    match amount.checked_sub(fee) {
+        Some(net) => Ok((net, fee)),
+        None => Err(FeeError::ExceedsAmount { amount, fee_bps }),
+    }
+}
+
+/// Artifact directory used by the built-in chain configs, relative to the
+/// working directory.
+pub const DEFAULT_ARTIFACTS_PATH: &str = "./artifacts";
+
 pub const CHAIN_CONFIGS: &[ChainConfig] = &[MAINNET_CONFIG, SEPOLIA_CONFIG];
 
 pub const MAINNET_CONFIG: ChainConfig = ChainConfig {
@@ -38,6 +89,9 @@ pub const MAINNET_CONFIG: ChainConfig = ChainConfig {
         "https://rail-squid.squids.live/squid-railgun-ethereum-v2/v/v1/graphql",
     ),
     poi_endpoint: Some("https://ppoi-agg.horsewithsixlegs.xyz/"),
+    artifacts_path: DEFAULT_ARTIFACTS_PATH,
+    shield_fee_bps: 25,
+    unshield_fee_bps: 25,
 };
 
 pub const SEPOLIA_CONFIG: ChainConfig = ChainConfig {
@@ -49,6 +103,9 @@ pub const SEPOLIA_CONFIG: ChainConfig = ChainConfig {
         "https://rail-squid.squids.live/squid-railgun-eth-sepolia-v2/v/v1/graphql",
     ),
     poi_endpoint: Some("https://ppoi-agg.horsewithsixlegs.xyz/"),
+    artifacts_path: DEFAULT_ARTIFACTS_PATH,
+    shield_fee_bps: 25,
+    unshield_fee_bps: 25,
 };
 
 pub const fn get_chain_config(chain_id: ChainId) -> Option<ChainConfig> {
@@ -61,3 +118,38 @@ pub const fn get_chain_config(chain_id: ChainId) -> Option<ChainConfig> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fees() {
+        assert_eq!(MAINNET_CONFIG.shield_fee(1_000_000), Ok((997_500, 2_500)));
+        assert_eq!(MAINNET_CONFIG.unshield_fee(1_000_000), Ok((997_500, 2_500)));
+        assert_eq!(MAINNET_CONFIG.shield_fee(0), Ok((0, 0)));
+    }
+
+    #[test]
+    fn test_fee_overflow() {
+        assert_eq!(
+            MAINNET_CONFIG.shield_fee(u128::MAX),
+            Err(FeeError::Overflow {
+                amount: u128::MAX,
+                fee_bps: 25
+            })
+        );
+
+        let config = ChainConfig {
+            unshield_fee_bps: 10_001,
+            ..MAINNET_CONFIG
+        };
+        assert_eq!(
+            config.unshield_fee(10_000),
+            Err(FeeError::ExceedsAmount {
+                amount: 10_000,
+                fee_bps: 10_001
+            })
+        );
+    }
+}
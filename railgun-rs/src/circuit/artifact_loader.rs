@@ -1,11 +1,175 @@
 use ark_bn254::{Bn254, Fr};
 use ark_groth16::ProvingKey;
 use ark_relations::r1cs::ConstraintMatrices;
+use thiserror::Error;
 
 use crate::circuit::witness::CircuitType;
 
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    #[error("Failed to load artifact: {0}")]
+    Load(String),
+    #[error("Artifact for {circuit:?} has {actual} public inputs, expected {expected}")]
+    CircuitMismatch {
+        circuit: CircuitType,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Artifact for {circuit:?} has {expected} wires, but the witness has {actual}")]
+    WitnessMismatch {
+        circuit: CircuitType,
+        expected: usize,
+        actual: usize,
+    },
+}
+
 #[async_trait::async_trait]
 pub trait ArtifactLoader {
-    async fn load_proving_key(&self, circuit: CircuitType) -> Result<ProvingKey<Bn254>, String>;
-    async fn load_matrices(&self, circuit: CircuitType) -> Result<ConstraintMatrices<Fr>, String>;
+    async fn load_proving_key(
+        &self,
+        circuit: CircuitType,
+    ) -> Result<ProvingKey<Bn254>, ArtifactError>;
+    async fn load_matrices(
+        &self,
+        circuit: CircuitType,
+    ) -> Result<ConstraintMatrices<Fr>, ArtifactError>;
+
+    /// Whether the proving key for `circuit` is available to this loader.
+    fn has_circuit(&self, _circuit: CircuitType) -> bool {
+        true
+    }
+
+    /// Where this loader expects the proving key for `circuit` to be, if it
+    /// loads from a known location.
+    fn artifact_path(&self, _circuit: CircuitType) -> Option<String> {
+        None
+    }
+}
+
+/// Checks that a loaded proving key and its constraint matrices are for
+/// `circuit`, catching artifacts for a different circuit size before they
+/// produce proofs that only fail on-chain.
+pub fn validate_artifacts(
+    circuit: CircuitType,
+    pk: &ProvingKey<Bn254>,
+    matrices: &ConstraintMatrices<Fr>,
+) -> Result<(), ArtifactError> {
+    let expected = circuit.public_inputs();
+
+    //? Instance variables include the constant `1` wire.
+    for actual in [
+        matrices.num_instance_variables.saturating_sub(1),
+        pk.vk.gamma_abc_g1.len().saturating_sub(1),
+    ] {
+        if actual != expected {
+            return Err(ArtifactError::CircuitMismatch {
+                circuit,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a witness has one value per wire of the constraint matrices
+/// it's proved against.
+///
+/// Circuits with the same number of public inputs (e.g. 1x2 and 2x1) pass
+/// `validate_artifacts`, but their wire counts differ, so this catches a zkey
+/// for the wrong circuit once the witness for the requested one is known.
+pub fn validate_witness(
+    circuit: CircuitType,
+    matrices: &ConstraintMatrices<Fr>,
+    witness_len: usize,
+) -> Result<(), ArtifactError> {
+    let expected = matrices.num_instance_variables + matrices.num_witness_variables;
+    if witness_len != expected {
+        return Err(ArtifactError::WitnessMismatch {
+            circuit,
+            expected,
+            actual: witness_len,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::G1Affine;
+
+    use super::*;
+
+    fn artifacts(
+        public_inputs: usize,
+        wires: usize,
+    ) -> (ProvingKey<Bn254>, ConstraintMatrices<Fr>) {
+        let mut pk = ProvingKey::<Bn254>::default();
+        pk.vk.gamma_abc_g1 = vec![G1Affine::default(); public_inputs + 1];
+
+        let matrices = ConstraintMatrices {
+            num_instance_variables: public_inputs + 1,
+            num_witness_variables: wires - (public_inputs + 1),
+            num_constraints: 0,
+            a_num_non_zero: 0,
+            b_num_non_zero: 0,
+            c_num_non_zero: 0,
+            a: vec![],
+            b: vec![],
+            c: vec![],
+        };
+        (pk, matrices)
+    }
+
+    #[test]
+    fn test_validate_artifacts() {
+        let circuit = CircuitType::Transact {
+            nullifiers: 1,
+            commitments: 2,
+        };
+
+        let (pk, matrices) = artifacts(5, 10190);
+        assert!(validate_artifacts(circuit, &pk, &matrices).is_ok());
+
+        //? Artifacts for the 2x2 circuit
+        let (pk, matrices) = artifacts(6, 14855);
+        let err = validate_artifacts(circuit, &pk, &matrices).unwrap_err();
+        assert!(matches!(
+            err,
+            ArtifactError::CircuitMismatch {
+                expected: 5,
+                actual: 6,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_validate_swapped_circuit() {
+        let circuit = CircuitType::Transact {
+            nullifiers: 1,
+            commitments: 2,
+        };
+        //? The 1x2 circuit has 10190 wires, per its zkey header
+        let witness_len = 10190;
+
+        let (pk, matrices) = artifacts(5, witness_len);
+        assert!(validate_artifacts(circuit, &pk, &matrices).is_ok());
+        assert!(validate_witness(circuit, &matrices, witness_len).is_ok());
+
+        //? A 2x1 zkey has the same public inputs, but a wire count of its own
+        let (pk, matrices) = artifacts(5, 14440);
+        assert!(validate_artifacts(circuit, &pk, &matrices).is_ok());
+        let err = validate_witness(circuit, &matrices, witness_len).unwrap_err();
+        assert!(matches!(
+            err,
+            ArtifactError::WitnessMismatch {
+                expected: 14440,
+                actual: 10190,
+                ..
+            }
+        ));
+    }
 }
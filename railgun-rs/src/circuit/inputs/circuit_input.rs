@@ -113,7 +113,8 @@ where
     }
 }
 
-/// A macro to generate the `as_flat_map` method for circuit inputs.
+/// A macro to generate the `as_flat_map` and `dump_signals` methods for circuit
+/// inputs.
 #[macro_export]
 macro_rules! circuit_inputs {
     ($($field:ident => $key:literal),* $(,)?) => {
@@ -124,5 +125,17 @@ macro_rules! circuit_inputs {
             $(m.insert($key.into(), self.$field.clone().into_signal_vec());)*
             m
         }
+
+        /// Dumps every circuit signal as a JSON object of circom signal name to
+        /// decimal string values, matching the input format of the JS SDK and
+        /// snarkjs. Useful for diffing against known-good inputs.
+        pub fn dump_signals(&self) -> String {
+            let signals: std::collections::BTreeMap<String, Vec<String>> = self
+                .as_flat_map()
+                .into_iter()
+                .map(|(k, v)| (k, v.iter().map(|x| x.to_string()).collect()))
+                .collect();
+            serde_json::to_string_pretty(&signals).unwrap()
+        }
     };
 }
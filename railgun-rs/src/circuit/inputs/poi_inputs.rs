@@ -86,7 +86,7 @@ pub enum PoiCircuitInputsError {
 
 /// Determines the circuit size based on the number of nullifiers and commitments.
 /// Returns 3 for the "mini" circuit, 13 for the "full" circuit.
-fn circuit_size(nullifiers_len: usize, commitments_len: usize) -> usize {
+pub(crate) fn circuit_size(nullifiers_len: usize, commitments_len: usize) -> usize {
     if nullifiers_len <= 3 && commitments_len <= 3 {
         3
     } else {
@@ -224,10 +224,8 @@ impl PoiCircuitInputs {
         in_notes: &[PoiNote<S>],
     ) -> Result<Vec<U256>, PoiCircuitInputsError> {
         info!("UTXO proofs");
-        let utxo_proofs: Vec<_> = in_notes
-            .iter()
-            .map(|note| utxo_merkle_tree.generate_proof(note.hash()))
-            .collect::<Result<_, _>>()?;
+        let leaves: Vec<_> = in_notes.iter().map(|note| note.hash()).collect();
+        let utxo_proofs = utxo_merkle_tree.generate_proofs(&leaves)?;
         Ok(in_notes
             .iter()
             .zip(utxo_proofs.iter())
@@ -343,3 +341,146 @@ impl PoiCircuitInputs {
         poi_in_merkle_proof_path_elements => "poiInMerkleProofPathElements"
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::*;
+    use crate::{
+        crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+        railgun::{merkle_tree::TOTAL_LEAVES, note::utxo::test_note},
+    };
+
+    const LIST_KEY: &str = "efc6ddb59c098a13fb2b618fdae94c1c3a807abc8fb1837c93620c9143ee9e88";
+    const BOUND_PARAMS_HASH: U256 = U256::from_limbs([5, 0, 0, 0]);
+
+    fn list_key() -> ListKey {
+        LIST_KEY.parse().unwrap()
+    }
+
+    /// Spends `test_note` into `outputs` commitments, with the txid at
+    /// `tree_index`. Included indices are inserted into a fresh txid tree.
+    fn test_inputs(outputs: usize, tree_index: UtxoTreeIndex) -> PoiCircuitInputs {
+        let note = test_note();
+        let poi_proof = MerkleProof::new_pre_inclusion(note.blinded_commitment());
+        let note = PoiNote::new(note, HashMap::from([(list_key(), poi_proof)]));
+
+        let mut utxo_tree = UtxoMerkleTree::new(note.tree_number());
+        utxo_tree.insert_leaf(note.hash(), note.leaf_index() as usize);
+
+        let commitments: Vec<U256> = (0..outputs).map(|i| U256::from(100 + i)).collect();
+        let npks: Vec<U256> = (0..outputs).map(|i| U256::from(200 + i)).collect();
+        let values: Vec<U256> = (0..outputs).map(|i| U256::from(1 + i)).collect();
+
+        let spending_pubkey = SpendingKey::from_bytes([1u8; 32]).public_key();
+        let nullifying_key = ViewingKey::from_bytes([2u8; 32]).nullifying_key();
+
+        let pre_inclusion = PoiCircuitInputs::from_inputs(
+            spending_pubkey,
+            nullifying_key,
+            &utxo_tree,
+            note.tree_number(),
+            BOUND_PARAMS_HASH,
+            std::slice::from_ref(&note),
+            &commitments,
+            &npks,
+            &values,
+            note.asset().hash(),
+            false,
+            list_key(),
+        )
+        .unwrap();
+
+        if matches!(tree_index, UtxoTreeIndex::PreInclusion) {
+            return pre_inclusion;
+        }
+
+        let leaf = TxidLeafHash::new(pre_inclusion.txid, note.tree_number(), tree_index);
+        let mut txid_tree = TxidMerkleTree::new(0);
+        txid_tree.insert_leaf(leaf, 0);
+
+        PoiCircuitInputs::from_inputs_included(
+            spending_pubkey,
+            nullifying_key,
+            &utxo_tree,
+            note.tree_number(),
+            BOUND_PARAMS_HASH,
+            std::slice::from_ref(&note),
+            &commitments,
+            &npks,
+            &values,
+            note.asset().hash(),
+            false,
+            list_key(),
+            tree_index,
+            &txid_tree,
+        )
+        .unwrap()
+    }
+
+    /// Asserts every per-note signal is padded to `size`, with the expected
+    /// padding value after the real entries.
+    fn assert_padded(inputs: &PoiCircuitInputs, size: usize, ins: usize, outs: usize) {
+        let signals = inputs.as_flat_map();
+        let zero_value = railgun_merkle_tree_zero();
+        let zero = U256::ZERO;
+
+        for (key, used, padding) in [
+            ("nullifiers", ins, zero_value),
+            ("randomsIn", ins, zero_value),
+            ("valuesIn", ins, zero),
+            ("utxoPositionsIn", ins, zero_value),
+            ("poiMerkleroots", ins, zero_value),
+            ("poiInMerkleProofIndices", ins, zero),
+            ("commitmentsOut", outs, zero_value),
+            ("npksOut", outs, zero_value),
+            ("valuesOut", outs, zero),
+        ] {
+            let signal = &signals[key];
+            assert_eq!(signal.len(), size, "{}", key);
+            assert!(signal[used..].iter().all(|v| *v == padding), "{}", key);
+        }
+
+        let paths = &signals["poiInMerkleProofPathElements"];
+        assert_eq!(paths.len(), size * TREE_DEPTH);
+        assert!(paths[ins * TREE_DEPTH..].iter().all(|v| *v == zero_value));
+
+        //? Unpadded roots are what's submitted to the POI node
+        assert_eq!(inputs.poi_merkleroots.len(), ins);
+    }
+
+    #[test]
+    fn test_circuit_size() {
+        assert_eq!(circuit_size(1, 1), 3);
+        assert_eq!(circuit_size(3, 3), 3);
+        assert_eq!(circuit_size(4, 1), 13);
+        assert_eq!(circuit_size(1, 4), 13);
+        assert_eq!(circuit_size(13, 13), 13);
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_mini_circuit_padding() {
+        let inputs = test_inputs(2, UtxoTreeIndex::PreInclusion);
+
+        assert_padded(&inputs, 3, 1, 2);
+        assert_eq!(
+            inputs.as_flat_map()["utxoBatchGlobalStartPositionOut"],
+            vec![U256::from(UtxoTreeIndex::PreInclusion.global_index())]
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_full_circuit_padding() {
+        let tree_index = UtxoTreeIndex::included(2, 10);
+        let inputs = test_inputs(4, tree_index);
+
+        assert_padded(&inputs, 13, 1, 4);
+        assert_eq!(
+            inputs.as_flat_map()["utxoBatchGlobalStartPositionOut"],
+            vec![U256::from(2 * TOTAL_LEAVES + 10)]
+        );
+    }
+}
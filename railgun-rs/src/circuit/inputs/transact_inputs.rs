@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use ruint::aliases::U256;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
@@ -12,9 +13,11 @@ use crate::{
     },
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TransactCircuitInputs {
     // Public Inputs
+    #[serde(rename = "merkleRoot")]
     pub merkleroot: MerkleRoot,
     pub bound_params_hash: U256,
     pub nullifiers: Vec<U256>,
@@ -53,10 +56,8 @@ impl TransactCircuitInputs {
         }
 
         let merkleroot = merkle_tree.root();
-        let merkle_proofs: Vec<_> = notes_in
-            .iter()
-            .map(|note| merkle_tree.generate_proof(note.hash()))
-            .collect::<Result<_, _>>()?;
+        let leaves: Vec<_> = notes_in.iter().map(|note| note.hash()).collect();
+        let merkle_proofs = merkle_tree.generate_proofs(&leaves)?;
 
         let nullifiers: Vec<U256> = notes_in
             .iter()
@@ -120,6 +121,16 @@ impl TransactCircuitInputs {
         })
     }
 
+    /// Serializes the circuit inputs to JSON, keyed by circuit signal name.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Deserializes circuit inputs from JSON produced by `to_json`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
     circuit_inputs!(
         merkleroot => "merkleRoot",
         bound_params_hash => "boundParamsHash",
@@ -137,3 +148,82 @@ impl TransactCircuitInputs {
         value_out => "valueOut"
     );
 }
+
+/// Path of the committed JSON fixture holding `test_inputs()`, relative to the
+/// crate root.
+#[cfg(test)]
+pub const TRANSACT_FIXTURE: &str = "./tests/fixtures/transact_inputs.json";
+
+/// Deterministic transact inputs spending `test_note` into a single transfer
+/// note of the same value.
+#[cfg(test)]
+pub fn test_inputs() -> TransactCircuitInputs {
+    use crate::{
+        crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+        railgun::{
+            note::{transfer::TransferNote, utxo::test_note},
+            signer::{PrivateKeySigner, Signer},
+        },
+    };
+
+    let in_note = test_note();
+    let mut tree = UtxoMerkleTree::new(in_note.tree_number());
+    tree.insert_leaf(in_note.hash(), in_note.leaf_index() as usize);
+
+    let to = PrivateKeySigner::new_evm(
+        SpendingKey::from_bytes([5u8; 32]),
+        ViewingKey::from_bytes([6u8; 32]),
+        1,
+    );
+    let out_note = TransferNote::new(
+        ViewingKey::from_bytes([3u8; 32]),
+        to.address(),
+        in_note.asset(),
+        in_note.value(),
+        [4u8; 16],
+        "test memo",
+    );
+    let notes_out: Vec<Box<dyn Note>> = vec![Box::new(out_note)];
+
+    TransactCircuitInputs::from_inputs(&tree, U256::from(5), &[in_note], &notes_out).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    #[test]
+    #[traced_test]
+    fn test_json_round_trip() {
+        let inputs = test_inputs();
+        let json = inputs.to_json().unwrap();
+        let decoded = TransactCircuitInputs::from_json(&json).unwrap();
+
+        assert_eq!(decoded.as_flat_map(), inputs.as_flat_map());
+    }
+
+    /// The committed fixture must stay in sync with `test_inputs`, since the
+    /// prover tests and benches read it instead of rebuilding the inputs.
+    #[test]
+    #[traced_test]
+    fn test_fixture_matches_test_inputs() {
+        let json = std::fs::read_to_string(TRANSACT_FIXTURE).unwrap();
+        let fixture = TransactCircuitInputs::from_json(&json).unwrap();
+
+        assert_eq!(fixture.as_flat_map(), test_inputs().as_flat_map());
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_dump_signals() {
+        let inputs = test_inputs();
+        let signals: HashMap<String, Vec<String>> =
+            serde_json::from_str(&inputs.dump_signals()).unwrap();
+
+        assert_eq!(signals.len(), inputs.as_flat_map().len());
+        assert_eq!(signals["boundParamsHash"], vec!["5".to_string()]);
+        assert_eq!(signals["pathElements"].len(), 16);
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, io::Cursor, sync::Mutex};
+
+use ark_bn254::{Bn254, Fr};
+use ark_circom::read_zkey;
+use ark_groth16::ProvingKey;
+use ark_relations::r1cs::ConstraintMatrices;
+
+use crate::circuit::{
+    artifact_loader::{ArtifactError, ArtifactLoader, validate_artifacts},
+    witness::CircuitType,
+};
+
+/// Loads proving keys compiled into the binary, e.g. via `include_bytes!`.
+///
+/// Useful for shipping the small, common circuits inside a single executable.
+/// Circuits that aren't embedded are delegated to an optional fallback loader,
+/// typically an `FsArtifactLoader` pointed at the larger artifacts.
+///
+/// ```ignore
+/// let loader = EmbeddedArtifactLoader::new()
+///     .with_zkey(
+///         CircuitType::Transact { nullifiers: 1, commitments: 2 },
+///         include_bytes!("../artifacts/railgun/01x02.zkey"),
+///     )
+///     .with_fallback(FsArtifactLoader::new("./artifacts"));
+/// ```
+#[derive(Default)]
+pub struct EmbeddedArtifactLoader {
+    zkeys: HashMap<CircuitType, &'static [u8]>,
+    fallback: Option<Box<dyn ArtifactLoader + Send + Sync>>,
+    cache: Mutex<HashMap<CircuitType, (ProvingKey<Bn254>, ConstraintMatrices<Fr>)>>,
+}
+
+impl EmbeddedArtifactLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Embeds the zkey bytes for `circuit`. The zkey is parsed and validated
+    /// lazily on first use.
+    pub fn with_zkey(mut self, circuit: CircuitType, zkey: &'static [u8]) -> Self {
+        self.zkeys.insert(circuit, zkey);
+        self
+    }
+
+    /// Loader used for circuits that aren't embedded.
+    pub fn with_fallback(mut self, fallback: impl ArtifactLoader + Send + Sync + 'static) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    /// Runs `f` on the parsed artifacts for an embedded `circuit`, parsing and
+    /// caching them on first use.
+    fn with_artifacts<T>(
+        &self,
+        circuit: CircuitType,
+        zkey: &[u8],
+        f: impl FnOnce(&ProvingKey<Bn254>, &ConstraintMatrices<Fr>) -> T,
+    ) -> Result<T, ArtifactError> {
+        let mut cache = self
+            .cache
+            .lock()
+            .map_err(|e| ArtifactError::Load(e.to_string()))?;
+
+        if let Some((pk, matrices)) = cache.get(&circuit) {
+            return Ok(f(pk, matrices));
+        }
+
+        let (pk, matrices) = read_zkey(&mut Cursor::new(zkey))
+            .map_err(|e| ArtifactError::Load(format!("Failed to read zkey: {}", e)))?;
+        validate_artifacts(circuit, &pk, &matrices)?;
+
+        let result = f(&pk, &matrices);
+        cache.insert(circuit, (pk, matrices));
+        Ok(result)
+    }
+
+    fn fallback(
+        &self,
+        circuit: CircuitType,
+    ) -> Result<&(dyn ArtifactLoader + Send + Sync), ArtifactError> {
+        match &self.fallback {
+            Some(fallback) => Ok(fallback.as_ref()),
+            None => Err(ArtifactError::Load(format!(
+                "No embedded artifact for {:?}",
+                circuit
+            ))),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactLoader for EmbeddedArtifactLoader {
+    async fn load_proving_key(
+        &self,
+        circuit: CircuitType,
+    ) -> Result<ProvingKey<Bn254>, ArtifactError> {
+        match self.zkeys.get(&circuit) {
+            Some(zkey) => self.with_artifacts(circuit, zkey, |pk, _| pk.clone()),
+            None => self.fallback(circuit)?.load_proving_key(circuit).await,
+        }
+    }
+
+    async fn load_matrices(
+        &self,
+        circuit: CircuitType,
+    ) -> Result<ConstraintMatrices<Fr>, ArtifactError> {
+        match self.zkeys.get(&circuit) {
+            Some(zkey) => self.with_artifacts(circuit, zkey, |_, matrices| matrices.clone()),
+            None => self.fallback(circuit)?.load_matrices(circuit).await,
+        }
+    }
+
+    fn has_circuit(&self, circuit: CircuitType) -> bool {
+        self.zkeys.contains_key(&circuit)
+            || self
+                .fallback
+                .as_ref()
+                .is_some_and(|fallback| fallback.has_circuit(circuit))
+    }
+
+    fn artifact_path(&self, circuit: CircuitType) -> Option<String> {
+        if self.zkeys.contains_key(&circuit) {
+            return None;
+        }
+
+        self.fallback
+            .as_ref()
+            .and_then(|fallback| fallback.artifact_path(circuit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::native::FsArtifactLoader;
+
+    const CIRCUIT: CircuitType = CircuitType::Transact {
+        nullifiers: 1,
+        commitments: 2,
+    };
+
+    #[tokio::test]
+    async fn test_embedded_fallback() {
+        let loader = EmbeddedArtifactLoader::new();
+        assert!(!loader.has_circuit(CIRCUIT));
+        assert!(loader.load_proving_key(CIRCUIT).await.is_err());
+
+        let loader = loader.with_fallback(FsArtifactLoader::new("./does-not-exist"));
+        assert!(!loader.has_circuit(CIRCUIT));
+        assert_eq!(
+            loader.artifact_path(CIRCUIT).as_deref(),
+            Some("./does-not-exist/railgun/01x02.zkey")
+        );
+
+        //? Corrupt bytes are reported when loaded, not when embedded
+        let loader = loader.with_zkey(CIRCUIT, b"");
+        assert!(loader.has_circuit(CIRCUIT));
+        assert_eq!(loader.artifact_path(CIRCUIT), None);
+        assert!(matches!(
+            loader.load_matrices(CIRCUIT).await,
+            Err(ArtifactError::Load(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_embedded_zkey() {
+        let loader = EmbeddedArtifactLoader::new().with_zkey(
+            CIRCUIT,
+            include_bytes!("../../../artifacts/railgun/01x02.zkey"),
+        );
+
+        let pk = loader.load_proving_key(CIRCUIT).await.unwrap();
+        let matrices = loader.load_matrices(CIRCUIT).await.unwrap();
+        validate_artifacts(CIRCUIT, &pk, &matrices).unwrap();
+    }
+}
@@ -1,11 +1,14 @@
-use std::{collections::HashMap, fs, sync::Mutex};
+use std::{collections::HashMap, fs, path::Path, sync::Mutex};
 
 use ark_bn254::{Bn254, Fr};
 use ark_circom::read_zkey;
 use ark_groth16::ProvingKey;
 use ark_relations::r1cs::ConstraintMatrices;
 
-use crate::circuit::{artifact_loader::ArtifactLoader, witness::CircuitType};
+use crate::circuit::{
+    artifact_loader::{ArtifactError, ArtifactLoader, validate_artifacts},
+    witness::CircuitType,
+};
 
 pub struct FsArtifactLoader {
     path: String,
@@ -42,13 +45,15 @@ impl FsArtifactLoader {
     fn load_artifacts(
         &self,
         circuit_type: CircuitType,
-    ) -> Result<(ProvingKey<Bn254>, ConstraintMatrices<Fr>), String> {
+    ) -> Result<(ProvingKey<Bn254>, ConstraintMatrices<Fr>), ArtifactError> {
         let zkey_path = self.zkey_path(circuit_type);
-        let mut zkey_file = fs::File::open(&zkey_path)
-            .map_err(|e| format!("Failed to open zkey file {}: {}", zkey_path, e))?;
+        let mut zkey_file = fs::File::open(&zkey_path).map_err(|e| {
+            ArtifactError::Load(format!("Failed to open zkey file {}: {}", zkey_path, e))
+        })?;
 
-        let (proving_key, matrices) =
-            read_zkey(&mut zkey_file).map_err(|e| format!("Failed to read zkey: {}", e))?;
+        let (proving_key, matrices) = read_zkey(&mut zkey_file)
+            .map_err(|e| ArtifactError::Load(format!("Failed to read zkey: {}", e)))?;
+        validate_artifacts(circuit_type, &proving_key, &matrices)?;
 
         Ok((proving_key, matrices))
     }
@@ -56,8 +61,14 @@ impl FsArtifactLoader {
 
 #[async_trait::async_trait]
 impl ArtifactLoader for FsArtifactLoader {
-    async fn load_proving_key(&self, circuit: CircuitType) -> Result<ProvingKey<Bn254>, String> {
-        let mut cache = self.cache.lock().map_err(|e| e.to_string())?;
+    async fn load_proving_key(
+        &self,
+        circuit: CircuitType,
+    ) -> Result<ProvingKey<Bn254>, ArtifactError> {
+        let mut cache = self
+            .cache
+            .lock()
+            .map_err(|e| ArtifactError::Load(e.to_string()))?;
 
         if let Some((pk, _)) = cache.get(&circuit) {
             return Ok(pk.clone());
@@ -69,8 +80,14 @@ impl ArtifactLoader for FsArtifactLoader {
         Ok(pk)
     }
 
-    async fn load_matrices(&self, circuit: CircuitType) -> Result<ConstraintMatrices<Fr>, String> {
-        let mut cache = self.cache.lock().map_err(|e| e.to_string())?;
+    async fn load_matrices(
+        &self,
+        circuit: CircuitType,
+    ) -> Result<ConstraintMatrices<Fr>, ArtifactError> {
+        let mut cache = self
+            .cache
+            .lock()
+            .map_err(|e| ArtifactError::Load(e.to_string()))?;
 
         if let Some((_, matrices)) = cache.get(&circuit) {
             return Ok(matrices.clone());
@@ -80,4 +97,12 @@ impl ArtifactLoader for FsArtifactLoader {
         cache.insert(circuit, (pk, matrices.clone()));
         Ok(matrices)
     }
+
+    fn has_circuit(&self, circuit: CircuitType) -> bool {
+        Path::new(&self.zkey_path(circuit)).exists()
+    }
+
+    fn artifact_path(&self, circuit: CircuitType) -> Option<String> {
+        Some(self.zkey_path(circuit))
+    }
 }
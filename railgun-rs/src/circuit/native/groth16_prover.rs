@@ -3,18 +3,19 @@ use std::collections::HashMap;
 use ark_bn254::{Bn254, Fr};
 use ark_circom::CircomReduction;
 use ark_ff::BigInt;
-use ark_groth16::{Groth16, prepare_verifying_key};
+use ark_groth16::{Groth16, ProvingKey, prepare_verifying_key};
+use ark_relations::r1cs::{ConstraintMatrices, SynthesisError};
 use ark_std::rand::random;
 use ruint::aliases::U256;
 use tracing::info;
 
 use crate::circuit::{
-    artifact_loader::ArtifactLoader,
+    artifact_loader::{ArtifactLoader, validate_witness},
     inputs::{PoiCircuitInputs, TransactCircuitInputs},
     native::{FsArtifactLoader, WasmerWitnessCalculator},
     proof::Proof,
-    prover::{PoiProver, PublicInputs, TransactProver},
-    witness::{CircuitType, WitnessCalculator},
+    prover::{PoiProver, ProverError, PublicInputs, TransactProver},
+    witness::{CircuitType, Witness, WitnessCalculator},
 };
 
 pub struct Groth16Prover<W, A> {
@@ -46,12 +47,8 @@ impl<W: WitnessCalculator + Sync, A: ArtifactLoader + Sync> TransactProver for G
         &self,
         inputs: &TransactCircuitInputs,
     ) -> Result<(Proof, PublicInputs), Box<dyn std::error::Error>> {
-        let circuit_type = CircuitType::Transact {
-            nullifiers: inputs.nullifiers.len(),
-            commitments: inputs.commitments_out.len(),
-        };
-
-        self.prove(circuit_type, inputs.as_flat_map()).await
+        let witness = self.calculate_witness(inputs).await?;
+        self.prove_witness(&witness).await
     }
 }
 
@@ -72,49 +69,185 @@ impl<W: WitnessCalculator + Sync, A: ArtifactLoader + Sync> PoiProver for Groth1
 }
 
 impl<W: WitnessCalculator + Sync, A: ArtifactLoader + Sync> Groth16Prover<W, A> {
+    /// Whether both the witness calculator module and proving key for
+    /// `circuit` are available.
+    pub fn has_circuit(&self, circuit: CircuitType) -> bool {
+        self.witness_calculator.has_circuit(circuit) && self.artifact_loader.has_circuit(circuit)
+    }
+
+    /// Calculates the witness for a transact circuit. The witness can be cached
+    /// and passed to `prove_witness` to re-prove the same inputs.
+    pub async fn calculate_witness(
+        &self,
+        inputs: &TransactCircuitInputs,
+    ) -> Result<Witness, Box<dyn std::error::Error>> {
+        let circuit_type = CircuitType::Transact {
+            nullifiers: inputs.nullifiers.len(),
+            commitments: inputs.commitments_out.len(),
+        };
+
+        self.calculate(circuit_type, inputs.as_flat_map()).await
+    }
+
     pub async fn prove(
         &self,
         circuit_type: CircuitType,
         inputs: HashMap<String, Vec<U256>>,
     ) -> Result<(Proof, PublicInputs), Box<dyn std::error::Error>> {
+        let witness = self.calculate(circuit_type, inputs).await?;
+        self.prove_witness(&witness).await
+    }
+
+    /// Proves a precomputed witness. Each call uses fresh randomness, so
+    /// proving the same witness twice yields distinct, equally valid proofs.
+    ///
+    /// Inside a tokio runtime the proof is created on the blocking pool, so
+    /// concurrent calls prove in parallel.
+    pub async fn prove_witness(
+        &self,
+        witness: &Witness,
+    ) -> Result<(Proof, PublicInputs), Box<dyn std::error::Error>> {
+        let circuit_type = witness.circuit_type;
+        if !self.artifact_loader.has_circuit(circuit_type) {
+            return Err(Box::new(ProverError::MissingArtifact {
+                circuit: circuit_type,
+                path: self.artifact_loader.artifact_path(circuit_type),
+            }));
+        }
+
         info!("Loading artifacts");
         let pk = self.artifact_loader.load_proving_key(circuit_type).await?;
         let matrices = self.artifact_loader.load_matrices(circuit_type).await?;
+        validate_witness(circuit_type, &matrices, witness.values.len())?;
 
-        info!("Calculating witness");
-        let witnesses = self
-            .witness_calculator
-            .calculate_witness(circuit_type, inputs)
-            .await?;
-        let witnesses: Vec<Fr> = witnesses
+        let witnesses: Vec<Fr> = witness
+            .values
             .iter()
             .map(|x| Fr::from(BigInt::from(*x)))
             .collect();
 
-        info!("Creating proof");
-        let proof = Groth16::<Bn254, CircomReduction>::create_proof_with_reduction_and_matrices(
-            &pk,
-            random(),
-            random(),
-            &matrices,
-            matrices.num_instance_variables,
-            matrices.num_constraints,
-            &witnesses,
-        )?;
-
-        info!("Verifying proof");
-        let public_inputs = &witnesses[1..matrices.num_instance_variables];
-        let pvk = prepare_verifying_key(&pk.vk);
-        let verified =
-            Groth16::<Bn254, CircomReduction>::verify_proof(&pvk, &proof, &public_inputs).unwrap();
-        assert!(verified, "Proof verification failed");
-
-        let public_inputs = public_inputs
-            .iter()
-            .map(|x| BigInt::from(*x).into())
-            .collect();
+        let create = move || create_proof(&pk, &matrices, &witnesses);
+        let (proof, public_inputs) = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => handle.spawn_blocking(create).await??,
+            Err(_) => create()?,
+        };
 
         info!("Proof verified successfully");
         Ok((proof.into(), public_inputs))
     }
+
+    async fn calculate(
+        &self,
+        circuit_type: CircuitType,
+        inputs: HashMap<String, Vec<U256>>,
+    ) -> Result<Witness, Box<dyn std::error::Error>> {
+        if !self.witness_calculator.has_circuit(circuit_type) {
+            return Err(Box::new(ProverError::MissingArtifact {
+                circuit: circuit_type,
+                path: self.witness_calculator.artifact_path(circuit_type),
+            }));
+        }
+
+        info!("Calculating witness");
+        let values = self
+            .witness_calculator
+            .calculate_witness(circuit_type, inputs)
+            .await?;
+
+        Ok(Witness {
+            circuit_type,
+            values,
+        })
+    }
+}
+
+/// Creates and verifies a proof for the witness, returning it with its public
+/// inputs.
+fn create_proof(
+    pk: &ProvingKey<Bn254>,
+    matrices: &ConstraintMatrices<Fr>,
+    witnesses: &[Fr],
+) -> Result<(ark_groth16::Proof<Bn254>, PublicInputs), SynthesisError> {
+    info!("Creating proof");
+    let proof = Groth16::<Bn254, CircomReduction>::create_proof_with_reduction_and_matrices(
+        pk,
+        random(),
+        random(),
+        matrices,
+        matrices.num_instance_variables,
+        matrices.num_constraints,
+        witnesses,
+    )?;
+
+    info!("Verifying proof");
+    let public_inputs = &witnesses[1..matrices.num_instance_variables];
+    let pvk = prepare_verifying_key(&pk.vk);
+    let verified =
+        Groth16::<Bn254, CircomReduction>::verify_proof(&pvk, &proof, public_inputs).unwrap();
+    assert!(verified, "Proof verification failed");
+
+    let public_inputs = public_inputs
+        .iter()
+        .map(|x| BigInt::from(*x).into())
+        .collect();
+
+    Ok((proof, public_inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::inputs::transact_inputs::{TRANSACT_FIXTURE, test_inputs};
+
+    /// Proves the transact circuit from the committed inputs fixture,
+    /// independent of the transaction builder.
+    #[tokio::test]
+    #[ignore]
+    async fn test_transact_proof_from_fixture() {
+        let json = std::fs::read_to_string(TRANSACT_FIXTURE).unwrap();
+        let inputs = TransactCircuitInputs::from_json(&json).unwrap();
+
+        let prover = Groth16Prover::new_native("./artifacts");
+        let (_, public_inputs) = prover.prove_transact(&inputs).await.unwrap();
+
+        let mut expected = vec![inputs.merkleroot.into(), inputs.bound_params_hash];
+        expected.extend_from_slice(&inputs.nullifiers);
+        expected.extend_from_slice(&inputs.commitments_out);
+        assert_eq!(public_inputs, expected);
+    }
+
+    /// Re-proving a cached witness yields fresh proofs for the same public inputs.
+    #[tokio::test]
+    #[ignore]
+    async fn test_prove_cached_witness() {
+        let prover = Groth16Prover::new_native("./artifacts");
+        let witness = prover.calculate_witness(&test_inputs()).await.unwrap();
+
+        let (proof_a, public_a) = prover.prove_witness(&witness).await.unwrap();
+        let (proof_b, public_b) = prover.prove_witness(&witness).await.unwrap();
+        assert_eq!(public_a, public_b);
+        assert_ne!(proof_a.a.x, proof_b.a.x);
+    }
+
+    #[tokio::test]
+    async fn test_missing_artifact() {
+        let prover = Groth16Prover::new_native("./does-not-exist");
+        let inputs = test_inputs();
+        let circuit = CircuitType::Transact {
+            nullifiers: inputs.nullifiers.len(),
+            commitments: inputs.commitments_out.len(),
+        };
+        assert!(!prover.has_circuit(circuit));
+
+        let err = prover.prove_transact(&inputs).await.unwrap_err();
+        let err = err.downcast_ref::<ProverError>().unwrap();
+        let ProverError::MissingArtifact {
+            circuit: missing,
+            path,
+        } = err;
+        assert_eq!(*missing, circuit);
+        let path = path.as_deref().unwrap();
+        assert!(path.starts_with("./does-not-exist/railgun/"));
+        assert!(path.ends_with(".wasm"));
+    }
 }
@@ -1,7 +1,9 @@
+mod embedded_artifact_loader;
 mod fs_artifact_loader;
 mod groth16_prover;
 mod wasmer_witness_calculator;
 
+pub use embedded_artifact_loader::EmbeddedArtifactLoader;
 pub use fs_artifact_loader::FsArtifactLoader;
 pub use groth16_prover::Groth16Prover;
 pub use wasmer_witness_calculator::WasmerWitnessCalculator;
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Mutex};
+use std::{collections::HashMap, path::Path, sync::Mutex};
 
 use num_bigint::BigInt;
 use ruint::aliases::U256;
@@ -72,7 +72,9 @@ impl WitnessCalculator for WasmerWitnessCalculator {
             });
         }
 
-        let state = guard.as_mut().unwrap();
+        let state = guard
+            .as_mut()
+            .ok_or_else(|| "Witness calculator not loaded".to_string())?;
 
         // Convert inputs from U256 to BigInt
         let inputs: HashMap<String, Vec<BigInt>> = inputs
@@ -91,4 +93,12 @@ impl WitnessCalculator for WasmerWitnessCalculator {
 
         Ok(witness)
     }
+
+    fn has_circuit(&self, circuit: CircuitType) -> bool {
+        Path::new(&self.wasm_path(circuit)).exists()
+    }
+
+    fn artifact_path(&self, circuit: CircuitType) -> Option<String> {
+        Some(self.wasm_path(circuit))
+    }
 }
@@ -32,6 +32,43 @@ pub struct G2Affine {
     pub y: [U256; 2],
 }
 
+impl Proof {
+    /// Serializes the proof into the `proof.json` format produced by snarkjs,
+    /// with g1 / g2 points in projective form and the protocol and curve tags.
+    pub fn to_snarkjs_json(&self) -> String {
+        let [a_x, a_y]: [String; 2] = self.a.clone().into();
+        let [b_x, b_y]: [[String; 2]; 2] = self.b.clone().into();
+        let [c_x, c_y]: [String; 2] = self.c.clone().into();
+        let one = || "1".to_string();
+
+        let proof = SnarkjsProof {
+            pi_a: [a_x, a_y, one()],
+            pi_b: [b_x, b_y, [one(), "0".to_string()]],
+            pi_c: [c_x, c_y, one()],
+            protocol: "groth16",
+            curve: "bn128",
+        };
+        serde_json::to_string_pretty(&proof).expect("snarkjs proof always serializes")
+    }
+}
+
+/// Proof in the snarkjs `proof.json` layout.
+#[derive(Serialize)]
+struct SnarkjsProof {
+    pi_a: [String; 3],
+    pi_b: [[String; 2]; 3],
+    pi_c: [String; 3],
+    protocol: &'static str,
+    curve: &'static str,
+}
+
+/// Serializes public inputs into the `public.json` format produced by snarkjs,
+/// an array of decimal strings.
+pub fn public_inputs_to_json(public_inputs: &[U256]) -> String {
+    let values: Vec<String> = public_inputs.iter().map(U256::to_string).collect();
+    serde_json::to_string_pretty(&values).expect("string arrays always serialize")
+}
+
 impl From<ark_groth16::Proof<Bn254>> for Proof {
     fn from(proof: ark_groth16::Proof<Bn254>) -> Self {
         Proof {
@@ -114,6 +151,20 @@ mod tests {
         insta::assert_debug_snapshot!(abi_proof);
     }
 
+    #[test]
+    fn test_proof_to_snarkjs_json() {
+        let proof = test_proof();
+
+        insta::assert_snapshot!(proof.to_snarkjs_json());
+    }
+
+    #[test]
+    fn test_public_inputs_to_json() {
+        let public_inputs = vec![uint!(1_U256), uint!(12345678901234567890_U256)];
+
+        insta::assert_snapshot!(public_inputs_to_json(&public_inputs));
+    }
+
     fn test_proof() -> Proof {
         Proof {
             a: G1Affine {
@@ -1,12 +1,23 @@
 use ruint::aliases::U256;
+use thiserror::Error;
 
 use crate::circuit::{
     inputs::{PoiCircuitInputs, TransactCircuitInputs},
     proof::Proof,
+    witness::CircuitType,
 };
 
 pub type PublicInputs = Vec<U256>;
 
+#[derive(Debug, Error)]
+pub enum ProverError {
+    #[error("Missing artifact for {circuit:?}, expected at {}", path.as_deref().unwrap_or("<unknown>"))]
+    MissingArtifact {
+        circuit: CircuitType,
+        path: Option<String>,
+    },
+}
+
 #[cfg_attr(not(feature = "wasm"), async_trait::async_trait)]
 #[cfg_attr(feature = "wasm", async_trait::async_trait(?Send))]
 pub trait TransactProver {
@@ -9,6 +9,25 @@ pub trait WitnessCalculator {
         circuit_type: CircuitType,
         inputs: HashMap<String, Vec<U256>>,
     ) -> Result<Vec<U256>, String>;
+
+    /// Whether the witness calculator module for `circuit` is available.
+    fn has_circuit(&self, _circuit: CircuitType) -> bool {
+        true
+    }
+
+    /// Where this calculator expects the module for `circuit` to be, if it
+    /// loads from a known location.
+    fn artifact_path(&self, _circuit: CircuitType) -> Option<String> {
+        None
+    }
+}
+
+/// A calculated witness for a specific circuit. Witnesses are deterministic in
+/// their inputs, so they can be cached and re-proven with fresh randomness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Witness {
+    pub circuit_type: CircuitType,
+    pub values: Vec<U256>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -22,3 +41,24 @@ pub enum CircuitType {
         commitments: usize,
     },
 }
+
+impl CircuitType {
+    /// Number of public inputs the circuit exposes.
+    ///
+    /// Transact circuits expose the merkleroot, bound params hash, nullifiers,
+    /// and commitments. POI circuits expose the txid merkleroot, the
+    /// unshield txid, and a POI merkleroot and blinded commitment per input
+    /// and output.
+    pub fn public_inputs(&self) -> usize {
+        match self {
+            CircuitType::Transact {
+                nullifiers,
+                commitments,
+            }
+            | CircuitType::Poi {
+                nullifiers,
+                commitments,
+            } => 2 + nullifiers + commitments,
+        }
+    }
+}
@@ -15,6 +15,9 @@ use crate::crypto::{
     poseidon::poseidon_hash,
 };
 
+const PAYMENT_TAG_DOMAIN: &[u8] = b"railgun-payment-tag";
+const PAYMENT_TAG_LENGTH: usize = 8;
+
 /// Private key for signing transactions (BabyJubJub curve).
 #[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct SpendingKey([u8; 32]);
@@ -40,10 +43,20 @@ pub struct ViewingPublicKey([u8; 32]);
 #[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct MasterPublicKey([u8; 32]);
 
-/// Symmetric key for AES encryption.
+/// Symmetric key for note encryption.
+///
+/// Derived as sha256 of the shared ed25519 point. Distinct by construction from
+/// `SharedSecret`, so the two can't be used in place of one another.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct SharedKey([u8; 32]);
 
+/// Symmetric key for broadcaster message encryption.
+///
+/// Derived as the raw x25519 shared secret, compatible with @noble/ed25519's
+/// `getSharedSecret`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct SharedSecret([u8; 32]);
+
 /// Key for nullifier derivation.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct NullifyingKey([u8; 32]);
@@ -166,6 +179,7 @@ impl_byte_key!(SpendingKey);
 impl_byte_key!(ViewingKey);
 impl_byte_key!(ViewingPublicKey);
 impl_byte_key!(SharedKey);
+impl_byte_key!(SharedSecret);
 impl_byte_key!(NullifyingKey);
 impl_byte_key!(BlindedKey);
 impl_byte_key!(MasterPublicKey);
@@ -266,12 +280,10 @@ impl ViewingKey {
     }
 
     /// Generate a shared secret compatible with @noble/ed25519's `ed.getSharedSecret`
-    ///
-    /// TODO: Make me my own type
     pub fn derive_shared_secret(
         &self,
         their_public: ViewingPublicKey,
-    ) -> Result<SharedKey, KeyError> {
+    ) -> Result<SharedSecret, KeyError> {
         let scalar = self.to_curve25519_scalar();
         let ed_point = CompressedEdwardsY(their_public.0)
             .decompress()
@@ -279,7 +291,7 @@ impl ViewingKey {
         let x_point = ed_point.to_montgomery();
         let shared_secret = x_point * scalar;
 
-        Ok(SharedKey(shared_secret.to_bytes()))
+        Ok(SharedSecret(shared_secret.to_bytes()))
     }
 
     pub fn encrypt_gcm<R: Rng>(
@@ -302,6 +314,29 @@ impl ViewingKey {
         decrypt_ctr(ciphertext, &self.0)
     }
 
+    /// Derives the payment tag for the given index.
+    ///
+    /// Railgun can't derive per-payment receiving addresses. An address commits
+    /// to the master public key and the viewing public key, and notes can only
+    /// be decrypted with that viewing key and spent with that master key, so a
+    /// distinct address is necessarily a distinct account. Instead, receivers
+    /// can hand out payment tags that senders include in the note memo. Tags
+    /// can't be linked to each other or to the account without the viewing key.
+    pub fn payment_tag(&self, index: u32) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(PAYMENT_TAG_DOMAIN);
+        hasher.update(self.0);
+        hasher.update(index.to_be_bytes());
+        let digest = hasher.finalize();
+        hex::encode(&digest[..PAYMENT_TAG_LENGTH])
+    }
+
+    /// Returns the index of the first payment tag in `0..max_index` found in
+    /// the memo, if any.
+    pub fn find_payment_tag(&self, memo: &str, max_index: u32) -> Option<u32> {
+        (0..max_index).find(|&i| memo.contains(&self.payment_tag(i)))
+    }
+
     fn to_curve25519_scalar(&self) -> Scalar {
         let hash = Sha512::digest(self.0);
         let mut head = [0u8; 32];
@@ -345,6 +380,20 @@ impl SharedKey {
     }
 }
 
+impl SharedSecret {
+    pub fn encrypt_gcm<R: Rng + ?Sized>(
+        &self,
+        plaintext: &[&[u8]],
+        rng: &mut R,
+    ) -> Result<Ciphertext, AesError> {
+        encrypt_gcm(plaintext, &self.0, rng)
+    }
+
+    pub fn decrypt_gcm(&self, ciphertext: &Ciphertext) -> Result<Vec<Vec<u8>>, AesError> {
+        decrypt_gcm(ciphertext, &self.0)
+    }
+}
+
 impl MasterPublicKey {
     pub fn new(spending_pubkey: SpendingPublicKey, nullifying_key: NullifyingKey) -> Self {
         MasterPublicKey::from_u256(
@@ -468,6 +517,40 @@ mod tests {
         assert_eq!(shared_key_ab.to_hex(), shared_key_ba.to_hex());
     }
 
+    #[test]
+    #[traced_test]
+    fn test_shared_key_domain_separation() {
+        let viewing_key = ViewingKey::from_bytes([2u8; 32]);
+        let their_viewing = ViewingKey::from_bytes([3u8; 32]);
+
+        let note_key = viewing_key
+            .derive_shared_key(their_viewing.public_key())
+            .unwrap();
+        let broadcaster_secret = viewing_key
+            .derive_shared_secret(their_viewing.public_key())
+            .unwrap();
+
+        assert_ne!(note_key.as_bytes(), broadcaster_secret.as_bytes());
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_payment_tag() {
+        let viewing_key = ViewingKey::from_bytes([2u8; 32]);
+        let other_key = ViewingKey::from_bytes([3u8; 32]);
+
+        let tag = viewing_key.payment_tag(7);
+        assert_eq!(tag.len(), PAYMENT_TAG_LENGTH * 2);
+        assert_eq!(tag, viewing_key.payment_tag(7));
+        assert_ne!(tag, viewing_key.payment_tag(8));
+        assert_ne!(tag, other_key.payment_tag(7));
+
+        let memo = format!("invoice {}", tag);
+        assert_eq!(viewing_key.find_payment_tag(&memo, 16), Some(7));
+        assert_eq!(viewing_key.find_payment_tag(&memo, 7), None);
+        assert_eq!(other_key.find_payment_tag(&memo, 16), None);
+    }
+
     #[test]
     #[traced_test]
     fn test_blinded_key() {
@@ -37,7 +37,6 @@ const USDC: AssetId = AssetId::Erc20(USDC_ADDRESS);
 const WETH_ADDRESS: Address = address!("0xfff9976782d46cc05630d1f6ebab18b2324d6b14");
 const WETH: AssetId = AssetId::Erc20(WETH_ADDRESS);
 
-const PPOI_URL: &str = "https://ppoi-agg.horsewithsixlegs.xyz/";
 const INDEXER_STATE: &str = "./indexer_state_11155111.bincode";
 
 #[tokio::main]
@@ -106,8 +105,8 @@ async fn main() {
     let indexer_state = bitcode::serialize(&indexer.state()).unwrap();
     std::fs::write("./indexer_state_11155111.bincode", indexer_state).unwrap();
 
-    let prover = Groth16Prover::new_native("./artifacts");
-    let poi_client = PoiClient::new(PPOI_URL, CHAIN.id).await.unwrap();
+    let prover = Groth16Prover::new_native(CHAIN.artifacts_path);
+    let poi_client = PoiClient::for_chain(CHAIN).await.unwrap();
 
     // let shield = ShieldBuilder::new(CHAIN)
     //     .shield(account1.address(), USDC, 1000)
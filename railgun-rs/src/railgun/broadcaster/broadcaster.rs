@@ -1,6 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use alloy::primitives::{Address, Bytes, ChainId, FixedBytes, TxHash};
+use futures::lock::Mutex;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
@@ -10,13 +11,16 @@ use tracing::{error, info, warn};
 use crate::{
     crypto::{
         aes::Ciphertext,
-        keys::{KeyError, SharedKey, ViewingKey, ViewingPublicKey},
+        keys::{KeyError, SharedSecret, ViewingKey, ViewingPublicKey},
     },
     railgun::{
         address::RailgunAddress,
         broadcaster::{
             content_topics::{transact_content_topic, transact_response_content_topic},
             transport::{WakuTransport, WakuTransportError},
+            types::{
+                BROADCASTER_VERSION, BroadcasterFeeMessage, BroadcasterFeeMessageData, WakuMessage,
+            },
         },
         poi::{ListKey, PreTransactionPoisPerTxidLeafPerList, TxidVersion},
         transaction::PoiProvedTransaction,
@@ -61,6 +65,11 @@ pub struct Broadcaster {
 
     timeout: web_time::Duration,
     retry_delay: web_time::Duration,
+    max_scanned_messages: usize,
+    /// Retrieved response messages not yet matched to a request, oldest first.
+    responses: Mutex<Vec<WakuMessage>>,
+    /// Tx hashes of matched responses, by `BroadcastRequest::id`.
+    matched: Mutex<HashMap<ViewingPublicKey, TxHash>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -97,6 +106,24 @@ pub enum BroadcastError {
     Transport(#[from] WakuTransportError),
 }
 
+#[derive(Debug, Error)]
+pub enum FeeParseError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid hex data: {0}")]
+    Hex(#[from] hex::FromHexError),
+    #[error("Invalid broadcaster version: got {got}, expected {expected}")]
+    IncompatibleVersion { got: String, expected: String },
+    #[error("Invalid railgun address: {0}")]
+    InvalidRailgunAddress(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid fee: {0}")]
+    InvalidFee(String),
+    #[error("No fee offered for token {0}")]
+    UnsupportedToken(Address),
+}
+
 #[serde_as]
 #[derive(Debug, Clone, Serialize)]
 struct BroadcastParamsRaw {
@@ -127,13 +154,34 @@ struct BroadcastParamsRaw {
     pre_transaction_pois_per_txid_leaf_per_list: PreTransactionPoisPerTxidLeafPerList,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// A prepared broadcast request.
+///
+/// The request is encrypted once with a request-specific key, so submitting
+/// the same request multiple times sends identical bytes, and responses to any
+/// submission can be matched back to it. Keep the request around after a
+/// timeout to retry or check its status instead of preparing a new one.
+/// Requests serialize with their shared secret, so they can be persisted to
+/// check status or resubmit after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastRequest {
+    message: BroadcastMessage,
+    shared_secret: SharedSecret,
+}
+
+impl BroadcastRequest {
+    /// Stable identifier for this request, unique per prepared request.
+    pub fn id(&self) -> ViewingPublicKey {
+        self.message.params.pubkey
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BroadcastMessage {
     pub method: String,
     pub params: BroadcastMessageParams,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BroadcastMessageParams {
     pub pubkey: ViewingPublicKey,
     #[serde(rename = "encryptedData")]
@@ -158,6 +206,29 @@ type EncryptedData = (FixedBytes<32>, Bytes);
 const MIN_BROADCASTER_VERSION: &str = "8.0.0";
 const MAX_BROADCASTER_VERSION: &str = "8.999.0";
 
+/// Default number of most-recent response messages checked per poll.
+const DEFAULT_MAX_SCANNED_MESSAGES: usize = 100;
+
+impl Fee {
+    /// Parses the fee offered for `token` from a broadcaster's fee message.
+    ///
+    /// Each message lists fees for several tokens. Fails with
+    /// `FeeParseError::UnsupportedToken` if none is for `token`.
+    pub fn from_broadcast_message(msg: &WakuMessage, token: Address) -> Result<Fee, FeeParseError> {
+        Self::all_from_broadcast_message(msg)?
+            .into_iter()
+            .find(|fee| fee.token == token)
+            .ok_or(FeeParseError::UnsupportedToken(token))
+    }
+
+    /// Parses every token fee offered in a broadcaster's fee message.
+    pub fn all_from_broadcast_message(msg: &WakuMessage) -> Result<Vec<Fee>, FeeParseError> {
+        let fee_data = decode_fee_message(&msg.payload)?;
+        let (_, fees) = parse_fee_data(&fee_data)?;
+        Ok(fees)
+    }
+}
+
 impl Broadcaster {
     pub fn new(
         transport: Arc<dyn WakuTransport>,
@@ -174,14 +245,37 @@ impl Broadcaster {
             fee,
             timeout: web_time::Duration::from_secs(120),
             retry_delay: web_time::Duration::from_secs(5),
+            max_scanned_messages: DEFAULT_MAX_SCANNED_MESSAGES,
+            responses: Mutex::new(Vec::new()),
+            matched: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Sets how many of the most recent response messages are kept and
+    /// checked per poll. Responses to other users share the topic, so on a
+    /// busy topic ours may not be the latest message.
+    pub fn with_max_scanned_messages(mut self, max_scanned_messages: usize) -> Self {
+        self.max_scanned_messages = max_scanned_messages;
+        self
+    }
+
+    /// Prepares and submits a transaction to the broadcaster.
     pub async fn broadcast<R: Rng>(
         &self,
         transaction: &PoiProvedTransaction,
         rng: &mut R,
     ) -> Result<TxHash, BroadcastError> {
+        let request = self.prepare(transaction, rng)?;
+        self.submit(&request).await
+    }
+
+    /// Prepares an encrypted broadcast request for a transaction without
+    /// sending it.
+    pub fn prepare<R: Rng>(
+        &self,
+        transaction: &PoiProvedTransaction,
+        rng: &mut R,
+    ) -> Result<BroadcastRequest, BroadcastError> {
         let fees_id = match &transaction.fee {
             Some(fee) => fee.fees_id.clone(),
             None => return Err(BroadcastError::MissingFee),
@@ -225,20 +319,24 @@ impl Broadcaster {
             },
         };
 
-        self.send(shared_secret, message).await
+        Ok(BroadcastRequest {
+            message,
+            shared_secret,
+        })
     }
 
-    /// Send the message via the waku transport
-    async fn send(
-        &self,
-        shared_secret: SharedKey,
-        message: BroadcastMessage,
-    ) -> Result<TxHash, BroadcastError> {
+    /// Submits a prepared request via the waku transport and waits for the
+    /// broadcaster's response.
+    ///
+    /// Resubmitting a request after a timeout is safe: the payload is
+    /// identical, and the transaction's nullifiers can only be spent once.
+    /// Prefer calling `check_status` first to avoid a redundant submission.
+    pub async fn submit(&self, request: &BroadcastRequest) -> Result<TxHash, BroadcastError> {
         info!(
             "Broadcasting message: {}",
-            serde_json::to_string_pretty(&message)?
+            serde_json::to_string_pretty(&request.message)?
         );
-        let payload = serde_json::to_vec(&message)?;
+        let payload = serde_json::to_vec(&request.message)?;
         let req_topic = &transact_content_topic(self.chain_id);
         let resp_topic = &transact_response_content_topic(self.chain_id);
 
@@ -256,25 +354,80 @@ impl Broadcaster {
                 sleep(self.retry_delay - elapsed).await;
             }
 
-            // Retrieve historical messages to check if we got a response.
-            let historical_messages = self.transport.retrieve_historical(resp_topic).await?;
-            info!(
-                "Retrieved {} historical messages from topic {}",
-                historical_messages.len(),
-                resp_topic
-            );
-            let Some(message) = historical_messages.first() else {
-                continue;
-            };
-
-            //? If the message doesn't match our request (e.g. decryption fails), continue
-            //? If it matches but indicates an error, return that error.
-            //? If it matches and is ok, return the tx hash.
-            if let Some(tx_hash) = decode_response(&shared_secret, &message.payload)? {
+            if let Some(tx_hash) = self.check_status(request).await? {
                 return Ok(tx_hash);
             }
         }
     }
+
+    /// Checks whether the broadcaster has responded to a previously submitted
+    /// request, returning the tx hash if it was broadcast.
+    ///
+    /// Only the matching response is consumed. Responses to other requests are
+    /// kept for their own checks, up to the `max_scanned_messages` most recent.
+    /// Matched tx hashes are remembered by `BroadcastRequest::id`, so checking
+    /// the same request again returns the same tx hash.
+    ///
+    /// Matches are only remembered by this `Broadcaster`. The transport returns
+    /// each message once, so a request restored in another process can't see
+    /// responses retrieved before it was persisted. Check whether the
+    /// transaction's nullifiers were spent on-chain instead.
+    pub async fn check_status(
+        &self,
+        request: &BroadcastRequest,
+    ) -> Result<Option<TxHash>, BroadcastError> {
+        if let Some(tx_hash) = self.matched.lock().await.get(&request.id()) {
+            return Ok(Some(*tx_hash));
+        }
+
+        let resp_topic = &transact_response_content_topic(self.chain_id);
+        let historical_messages = self.transport.retrieve_historical(resp_topic).await?;
+        info!(
+            "Retrieved {} historical messages from topic {}",
+            historical_messages.len(),
+            resp_topic
+        );
+
+        let mut responses = self.responses.lock().await;
+        responses.extend(historical_messages);
+        let tx_hash = scan_responses(
+            &request.shared_secret,
+            &mut responses,
+            self.max_scanned_messages,
+        )?;
+
+        if let Some(tx_hash) = tx_hash {
+            self.matched.lock().await.insert(request.id(), tx_hash);
+        }
+        Ok(tx_hash)
+    }
+}
+
+/// Trial-decodes `messages` newest first, removing only the one that matches
+/// the request. Messages older than the `max` most recent are dropped.
+fn scan_responses(
+    shared_secret: &SharedSecret,
+    messages: &mut Vec<WakuMessage>,
+    max: usize,
+) -> Result<Option<TxHash>, BroadcastError> {
+    let excess = messages.len().saturating_sub(max);
+    messages.drain(..excess);
+
+    //? If the message doesn't match our request (e.g. decryption fails), keep
+    //? it for other requests and continue.
+    //? If it matches but indicates an error, return that error.
+    //? If it matches and is ok, return the tx hash.
+    for i in (0..messages.len()).rev() {
+        match decode_response(shared_secret, &messages[i].payload) {
+            Ok(None) => continue,
+            result => {
+                messages.remove(i);
+                return result;
+            }
+        }
+    }
+
+    Ok(None)
 }
 
 fn new_pre_transaction_pois(
@@ -302,7 +455,7 @@ fn encrypt_transaction<R: Rng>(
     params: BroadcastParamsRaw,
     broadcaster_viewing_key: ViewingPublicKey,
     rng: &mut R,
-) -> Result<(EncryptedData, ViewingPublicKey, SharedKey), BroadcastError> {
+) -> Result<(EncryptedData, ViewingPublicKey, SharedSecret), BroadcastError> {
     info!(
         "Encrypting transaction for broadcast with params: {}",
         serde_json::to_string_pretty(&params)?
@@ -328,11 +481,72 @@ fn encrypt_transaction<R: Rng>(
     Ok(((iv_tag.into(), data.into()), random_pubkey, shared_secret))
 }
 
+/// Decode a fee message payload from the Waku network, rejecting messages from
+/// incompatible broadcaster versions.
+pub(crate) fn decode_fee_message(
+    payload: &[u8],
+) -> Result<BroadcasterFeeMessageData, FeeParseError> {
+    let msg: BroadcasterFeeMessage = serde_json::from_slice(payload)?;
+    let data_bytes = hex::decode(msg.data.trim_start_matches("0x"))?;
+    let fee_data: BroadcasterFeeMessageData = serde_json::from_slice(&data_bytes)?;
+
+    let major_version = fee_data
+        .version
+        .split('.')
+        .next()
+        .unwrap_or(&fee_data.version);
+    if major_version != BROADCASTER_VERSION {
+        return Err(FeeParseError::IncompatibleVersion {
+            got: fee_data.version.clone(),
+            expected: BROADCASTER_VERSION.to_string(),
+        });
+    }
+
+    Ok(fee_data)
+}
+
+/// Parses the broadcaster's address and its per-token fees from decoded fee
+/// message data.
+pub(crate) fn parse_fee_data(
+    fee_data: &BroadcasterFeeMessageData,
+) -> Result<(RailgunAddress, Vec<Fee>), FeeParseError> {
+    let recipient: RailgunAddress = fee_data.railgun_address.parse().map_err(|e| {
+        FeeParseError::InvalidRailgunAddress(format!("{} ({})", fee_data.railgun_address, e))
+    })?;
+    let relay_adapt: Address = fee_data
+        .relay_adapt
+        .parse()
+        .map_err(|e| FeeParseError::InvalidAddress(format!("{} ({})", fee_data.relay_adapt, e)))?;
+
+    let mut fees = Vec::with_capacity(fee_data.fees.len());
+    for (token, fee_hex) in &fee_data.fees {
+        let token: Address = token
+            .parse()
+            .map_err(|e| FeeParseError::InvalidAddress(format!("{} ({})", token, e)))?;
+        let per_unit_gas = u128::from_str_radix(fee_hex.trim_start_matches("0x"), 16)
+            .map_err(|e| FeeParseError::InvalidFee(format!("{} ({})", fee_hex, e)))?;
+
+        fees.push(Fee {
+            token,
+            per_unit_gas,
+            recipient,
+            expiration: fee_data.fee_expiration,
+            fees_id: fee_data.fees_id.clone(),
+            available_wallets: fee_data.available_wallets,
+            relay_adapt,
+            reliability: (fee_data.reliability * 100.0) as u32,
+            list_keys: fee_data.required_poi_list_keys.clone(),
+        });
+    }
+
+    Ok((recipient, fees))
+}
+
 /// Decode a broadcaster response message. If the message is not a valid response,
 /// returns Ok(None). If the message is a valid response but indicates an error,
 /// returns Err.
 fn decode_response(
-    shared_secret: &SharedKey,
+    shared_secret: &SharedSecret,
     payload: &[u8],
 ) -> Result<Option<TxHash>, BroadcastError> {
     info!("Decoding response with payload: {:?}", payload);
@@ -438,6 +652,33 @@ mod test {
         insta::assert_debug_snapshot!(encrypted);
     }
 
+    #[test]
+    fn test_broadcast_request_serde() {
+        let broadcaster_viewing = ViewingKey::from_bytes([5u8; 32]);
+        let params = test_params(broadcaster_viewing.public_key());
+
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let (encrypted_data, pubkey, shared_secret) =
+            encrypt_transaction(params, broadcaster_viewing.public_key(), &mut rng).unwrap();
+        let request = BroadcastRequest {
+            message: BroadcastMessage {
+                method: "transact".to_string(),
+                params: BroadcastMessageParams {
+                    pubkey,
+                    encrypted_data,
+                },
+            },
+            shared_secret,
+        };
+
+        let json = serde_json::to_string(&request).unwrap();
+        let decoded: BroadcastRequest = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.id(), request.id());
+        assert_eq!(decoded.shared_secret, request.shared_secret);
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+    }
+
     #[test]
     fn test_shared_secret() {
         let broadcaster_private = ViewingKey::from_hex(
@@ -469,6 +710,221 @@ mod test {
         );
     }
 
+    const BROADCASTER_ADDRESS: &str = "0zk1qyjftlcuuxwjj574e5979wzt5veel9wmnh8peq6slvd668pz9ggzerv7j6fe3z53latpxdq2zqzs7l780x9gu7hfsgn93m27fwx3k6pk8fsrtgrp45ywuctqpkg";
+    const LIST_KEY: &str = "efc6ddb59c098a13fb2b618fdae94c1c3a807abc8fb1837c93620c9143ee9e88";
+
+    fn fee_message(version: &str) -> WakuMessage {
+        let data = serde_json::json!({
+            "fees": { "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238": "0x2386f26fc10000" },
+            "feeExpiration": 1_700_000_000u64,
+            "feesID": "fees-id",
+            "railgunAddress": BROADCASTER_ADDRESS,
+            "identifier": null,
+            "availableWallets": 2,
+            "version": version,
+            "relayAdapt": "0x4025ee6512DBbda97049Bcf5AA5D38C54aF6bE8a",
+            "requiredPOIListKeys": [LIST_KEY],
+            "reliability": 0.95,
+        });
+        let payload = serde_json::json!({
+            "data": format!("0x{}", hex::encode(serde_json::to_vec(&data).unwrap())),
+            "signature": "0x",
+        });
+
+        WakuMessage {
+            payload: serde_json::to_vec(&payload).unwrap(),
+            content_topic: "test".to_string(),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_fee_from_broadcast_message() {
+        let token = address!("0x1c7d4b196cb0c7b01d743fbc6116a902379c7238");
+        let fee = Fee::from_broadcast_message(&fee_message("8.1.0"), token).unwrap();
+
+        assert_eq!(fee.token, token);
+        assert_eq!(fee.per_unit_gas, 10_000_000_000_000_000);
+        assert_eq!(fee.recipient, BROADCASTER_ADDRESS.parse().unwrap());
+        assert_eq!(fee.expiration, 1_700_000_000);
+        assert_eq!(fee.fees_id, "fees-id");
+        assert_eq!(fee.available_wallets, 2);
+        assert_eq!(
+            fee.relay_adapt,
+            address!("0x4025ee6512dbbda97049bcf5aa5d38c54af6be8a")
+        );
+        assert_eq!(fee.reliability, 95);
+        assert_eq!(fee.list_keys, vec![LIST_KEY.parse().unwrap()]);
+
+        assert!(matches!(
+            Fee::from_broadcast_message(&fee_message("8.1.0"), Address::ZERO),
+            Err(FeeParseError::UnsupportedToken(_))
+        ));
+        assert!(matches!(
+            Fee::from_broadcast_message(&fee_message("7.9.0"), token),
+            Err(FeeParseError::IncompatibleVersion { .. })
+        ));
+    }
+
+    /// Encrypts a broadcaster response carrying `tx_hash` to `shared_secret`.
+    fn response_message(shared_secret: &SharedSecret, tx_hash: TxHash) -> WakuMessage {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let plaintext = serde_json::to_vec(&serde_json::json!({ "txHash": tx_hash })).unwrap();
+        let ciphertext = shared_secret.encrypt_gcm(&[&plaintext], &mut rng).unwrap();
+        let iv_tag: FixedBytes<32> =
+            FixedBytes::from_slice(&[ciphertext.iv, ciphertext.tag].concat());
+        let data: Bytes = ciphertext.data.concat().into();
+
+        WakuMessage {
+            payload: serde_json::to_vec(&serde_json::json!({ "result": (iv_tag, data) })).unwrap(),
+            content_topic: "test".to_string(),
+            timestamp: None,
+        }
+    }
+
+    fn other_message() -> WakuMessage {
+        WakuMessage {
+            payload: b"other".to_vec(),
+            content_topic: "test".to_string(),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_scan_responses() {
+        let shared_secret = SharedSecret::from_hex(
+            "7417f43de2c532f78f9f4faaa1626edea79f75c8c0cec5d1444ff34ab8e7836d",
+        )
+        .unwrap();
+        let tx_hash = TxHash::repeat_byte(0xab);
+
+        //? Our response is buried under newer responses to other requests.
+        let mut messages = vec![response_message(&shared_secret, tx_hash)];
+        messages.extend((0..5).map(|_| other_message()));
+
+        //? Messages older than the limit are dropped
+        assert_eq!(
+            scan_responses(&shared_secret, &mut messages.clone(), 5).unwrap(),
+            None
+        );
+
+        //? Only the matching message is consumed
+        assert_eq!(
+            scan_responses(&shared_secret, &mut messages, 6).unwrap(),
+            Some(tx_hash)
+        );
+        assert_eq!(messages.len(), 5);
+        assert_eq!(
+            scan_responses(&shared_secret, &mut messages, 6).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_scan_responses_keeps_other_requests() {
+        let secret_a = SharedSecret::from_hex(
+            "7417f43de2c532f78f9f4faaa1626edea79f75c8c0cec5d1444ff34ab8e7836d",
+        )
+        .unwrap();
+        let secret_b = SharedSecret::from_hex(
+            "1b2c3d4e5f60718293a4b5c6d7e8f90112233445566778899aabbccddeeff001",
+        )
+        .unwrap();
+        let tx_hash_a = TxHash::repeat_byte(0xaa);
+        let tx_hash_b = TxHash::repeat_byte(0xbb);
+
+        let mut messages = vec![
+            response_message(&secret_b, tx_hash_b),
+            response_message(&secret_a, tx_hash_a),
+        ];
+
+        //? Scanning for request A leaves request B's response in place
+        assert_eq!(
+            scan_responses(&secret_a, &mut messages, 10).unwrap(),
+            Some(tx_hash_a)
+        );
+        assert_eq!(
+            scan_responses(&secret_b, &mut messages, 10).unwrap(),
+            Some(tx_hash_b)
+        );
+        assert!(messages.is_empty());
+    }
+
+    /// Returns each queued message once, like a waku store.
+    #[derive(Default)]
+    struct QueuedTransport {
+        messages: std::sync::Mutex<Vec<WakuMessage>>,
+    }
+
+    #[async_trait::async_trait]
+    impl WakuTransport for QueuedTransport {
+        async fn subscribe(
+            &self,
+            _: Vec<String>,
+        ) -> Result<crate::railgun::broadcaster::transport::MessageStream, WakuTransportError>
+        {
+            unimplemented!()
+        }
+
+        async fn send(&self, _: &str, _: Vec<u8>) -> Result<(), WakuTransportError> {
+            Ok(())
+        }
+
+        async fn retrieve_historical(
+            &self,
+            _: &str,
+        ) -> Result<Vec<WakuMessage>, WakuTransportError> {
+            Ok(std::mem::take(&mut *self.messages.lock().unwrap()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_status_remembers_match() {
+        let broadcaster_viewing = ViewingKey::from_bytes([5u8; 32]);
+        let params = test_params(broadcaster_viewing.public_key());
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let (encrypted_data, pubkey, shared_secret) =
+            encrypt_transaction(params, broadcaster_viewing.public_key(), &mut rng).unwrap();
+        let request = BroadcastRequest {
+            message: BroadcastMessage {
+                method: "transact".to_string(),
+                params: BroadcastMessageParams {
+                    pubkey,
+                    encrypted_data,
+                },
+            },
+            shared_secret,
+        };
+
+        let tx_hash = TxHash::repeat_byte(0xab);
+        let transport = Arc::new(QueuedTransport::default());
+        transport
+            .messages
+            .lock()
+            .unwrap()
+            .push(response_message(&request.shared_secret, tx_hash));
+
+        let token = address!("0x1c7d4b196cb0c7b01d743fbc6116a902379c7238");
+        let fee = Fee::from_broadcast_message(&fee_message("8.1.0"), token).unwrap();
+        let broadcaster = Broadcaster::new(
+            transport,
+            1,
+            BROADCASTER_ADDRESS.parse().unwrap(),
+            None,
+            fee,
+        );
+
+        assert_eq!(
+            broadcaster.check_status(&request).await.unwrap(),
+            Some(tx_hash)
+        );
+        //? The transport won't return the response again, so it's remembered
+        assert_eq!(
+            broadcaster.check_status(&request).await.unwrap(),
+            Some(tx_hash)
+        );
+    }
+
     // #[test]
     // fn test_decode_response() {
     //     let raw: &[u8] = &[
@@ -486,7 +942,7 @@ mod test {
     //     ];
 
     //     let shared_secret =
-    //         SharedKey::from_hex("7417f43de2c532f78f9f4faaa1626edea79f75c8c0cec5d1444ff34ab8e7836d")
+    //         SharedSecret::from_hex("7417f43de2c532f78f9f4faaa1626edea79f75c8c0cec5d1444ff34ab8e7836d")
     //             .unwrap();
 
     //     let tx_hash = decode_response(&shared_secret, raw).unwrap().unwrap();
@@ -499,7 +955,7 @@ mod test {
 
     fn test_params(broadcaster_viewing_key: ViewingPublicKey) -> BroadcastParamsRaw {
         let pre_transaction_pois_per_txid_leaf_per_list = HashMap::from([(
-            "test_list_key".into(),
+            ListKey::new_unchecked("test_list_key"),
             HashMap::from([(
                 uint!(20_U256).into(),
                 PreTransactionPoi {
@@ -0,0 +1,112 @@
+use argon2::Argon2;
+use rand::Rng;
+use thiserror::Error;
+
+use crate::{
+    crypto::aes::{AesError, Ciphertext, decrypt_gcm, encrypt_gcm},
+    railgun::address::RailgunAddress,
+};
+
+/// Format version of encrypted account backups.
+const BACKUP_VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+/// version (1) | salt (16) | iv (16) | tag (16)
+const HEADER_LENGTH: usize = 1 + SALT_LENGTH + 16 + 16;
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("Unknown account: {0}")]
+    UnknownAccount(RailgunAddress),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] bitcode::Error),
+    #[error("Key derivation error: {0}")]
+    KeyDerivation(String),
+    #[error("Encryption error: {0}")]
+    Aes(#[from] AesError),
+    #[error("Unsupported backup version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Backup is truncated")]
+    Truncated,
+    #[error("Backup notes don't belong to the provided signer")]
+    SignerMismatch,
+}
+
+/// Encrypts a backup under a password-derived key.
+///
+/// The key is derived with Argon2id from the password and a random salt, and
+/// the payload is encrypted with AES-256-GCM. A wrong password fails
+/// decryption with `BackupError::Aes`.
+pub(crate) fn encrypt<R: Rng + ?Sized>(
+    plaintext: &[u8],
+    password: &str,
+    rng: &mut R,
+) -> Result<Vec<u8>, BackupError> {
+    let salt: [u8; SALT_LENGTH] = rng.random();
+    let key = derive_key(password, &salt)?;
+    let ciphertext = encrypt_gcm(&[plaintext], &key, rng)?;
+
+    let mut backup = Vec::with_capacity(HEADER_LENGTH + plaintext.len());
+    backup.push(BACKUP_VERSION);
+    backup.extend_from_slice(&salt);
+    backup.extend_from_slice(&ciphertext.iv);
+    backup.extend_from_slice(&ciphertext.tag);
+    backup.extend(ciphertext.data.concat());
+    Ok(backup)
+}
+
+/// Decrypts a backup created by `encrypt`.
+pub(crate) fn decrypt(backup: &[u8], password: &str) -> Result<Vec<u8>, BackupError> {
+    if backup.len() < HEADER_LENGTH {
+        return Err(BackupError::Truncated);
+    }
+    if backup[0] != BACKUP_VERSION {
+        return Err(BackupError::UnsupportedVersion(backup[0]));
+    }
+
+    let (salt, rest) = backup[1..].split_at(SALT_LENGTH);
+    let (iv, rest) = rest.split_at(16);
+    let (tag, data) = rest.split_at(16);
+
+    let key = derive_key(password, salt)?;
+    let ciphertext = Ciphertext {
+        //? Safe to unwrap as the lengths are checked above
+        iv: iv.try_into().unwrap(),
+        tag: tag.try_into().unwrap(),
+        data: vec![data.to_vec()],
+    };
+
+    Ok(decrypt_gcm(&ciphertext, &key)?.concat())
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], BackupError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| BackupError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    #[test]
+    #[traced_test]
+    fn test_backup_round_trip() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let backup = encrypt(b"notes", "password", &mut rng).unwrap();
+
+        assert_eq!(decrypt(&backup, "password").unwrap(), b"notes");
+        assert!(matches!(
+            decrypt(&backup, "wrong password"),
+            Err(BackupError::Aes(_))
+        ));
+        assert!(matches!(
+            decrypt(&backup[..HEADER_LENGTH - 1], "password"),
+            Err(BackupError::Truncated)
+        ));
+    }
+}
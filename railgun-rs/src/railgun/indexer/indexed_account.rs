@@ -3,7 +3,9 @@ use std::{
     sync::Arc,
 };
 
+use rand::Rng;
 use ruint::aliases::U256;
+use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
 use crate::{
@@ -11,11 +13,14 @@ use crate::{
     caip::AssetId,
     railgun::{
         address::RailgunAddress,
-        indexer::notebook::Notebook,
+        indexer::{
+            backup::{self, BackupError},
+            notebook::{Notebook, NotebookBackup},
+        },
         merkle_tree::TOTAL_LEAVES,
         note::{
-            Note,
-            utxo::{NoteError, UtxoNote},
+            IncludedNote, Note,
+            utxo::{NoteError, SentNote, UtxoNote},
         },
         signer::Signer,
     },
@@ -32,6 +37,13 @@ pub struct IndexedAccount {
     notebooks: BTreeMap<u32, Notebook>,
 }
 
+/// An account's notes and the block they were synced to, for backups.
+#[derive(Serialize, Deserialize)]
+struct AccountBackup {
+    synced_block: u64,
+    notebooks: BTreeMap<u32, NotebookBackup>,
+}
+
 impl IndexedAccount {
     pub fn new(signer: Arc<dyn Signer>) -> Self {
         IndexedAccount {
@@ -40,14 +52,100 @@ impl IndexedAccount {
         }
     }
 
+    /// Restores an account from a backup created by `encrypted_backup`. The
+    /// signer must be the account the backup was created for.
+    ///
+    /// Returns the account and the block the backup was synced to. Events
+    /// after that block must be resynced for the account to be up to date.
+    pub fn from_backup(
+        signer: Arc<dyn Signer>,
+        backup: &[u8],
+        password: &str,
+    ) -> Result<(Self, u64), BackupError> {
+        let plaintext = backup::decrypt(backup, password)?;
+        let backup: AccountBackup = bitcode::deserialize(&plaintext)?;
+
+        let mut notebooks = BTreeMap::new();
+        for (tree_number, notebook) in backup.notebooks {
+            let notebook =
+                Notebook::restore(notebook, &signer).ok_or(BackupError::SignerMismatch)?;
+            notebooks.insert(tree_number, notebook);
+        }
+
+        Ok((IndexedAccount { signer, notebooks }, backup.synced_block))
+    }
+
+    /// Encrypts the account's notes under a password, so they can be restored
+    /// without a full rescan. Keys aren't included, and must be supplied on
+    /// restore.
+    ///
+    /// `synced_block` is the block the account's notes are synced to.
+    pub fn encrypted_backup<R: Rng + ?Sized>(
+        &self,
+        password: &str,
+        synced_block: u64,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, BackupError> {
+        let backup = AccountBackup {
+            synced_block,
+            notebooks: self
+                .notebooks
+                .iter()
+                .map(|(tree_number, notebook)| {
+                    //? Notes pending after the synced block aren't final
+                    let mut notebook = notebook.clone();
+                    notebook.rollback(synced_block);
+                    (*tree_number, notebook.backup())
+                })
+                .collect(),
+        };
+        let plaintext = bitcode::serialize(&backup)?;
+
+        backup::encrypt(&plaintext, password, rng)
+    }
+
     pub fn address(&self) -> RailgunAddress {
         self.signer.address()
     }
 
+    pub fn signer(&self) -> Arc<dyn Signer> {
+        self.signer.clone()
+    }
+
     pub fn notebooks(&self) -> BTreeMap<u32, Notebook> {
         self.notebooks.clone()
     }
 
+    /// Returns this account's notes on the given tree with the given
+    /// nullifiers, in the same order. Returns `None` unless every nullifier
+    /// matches one of the account's notes, spent or unspent.
+    pub fn notes_by_nullifiers(
+        &self,
+        tree_number: u32,
+        nullifiers: &[U256],
+    ) -> Option<Vec<UtxoNote>> {
+        let notes = self.notebooks.get(&tree_number)?.all();
+
+        nullifiers
+            .iter()
+            .map(|nullifier| {
+                notes
+                    .iter()
+                    .find(|(position, note)| note.nullifier(U256::from(**position)) == *nullifier)
+                    .map(|(_, note)| note.clone())
+            })
+            .collect()
+    }
+
+    /// Returns the nullifiers of every spent and unspent note.
+    pub fn nullifiers(&self) -> Vec<U256> {
+        self.notebooks
+            .values()
+            .flat_map(|notebook| notebook.all())
+            .map(|(position, note)| note.nullifier(U256::from(position)))
+            .collect()
+    }
+
     pub fn unspent(&self) -> Vec<UtxoNote> {
         let mut unspent = Vec::new();
         for notebook in self.notebooks.values() {
@@ -56,21 +154,59 @@ impl IndexedAccount {
         unspent
     }
 
+    /// Returns the unspent notes created at or before the given block.
+    pub fn unspent_before(&self, block_number: u64) -> Vec<UtxoNote> {
+        let mut unspent = Vec::new();
+        for notebook in self.notebooks.values() {
+            unspent.extend(notebook.unspent_before(block_number).cloned());
+        }
+        unspent
+    }
+
+    /// Returns true if the note is tracked by this account and has not been spent.
+    pub fn is_unspent(&self, note: &impl IncludedNote) -> bool {
+        self.notebooks
+            .get(&note.tree_number())
+            .is_some_and(|notebook| notebook.unspent().contains_key(&note.leaf_index()))
+    }
+
+    /// Removes the unspent notes at the given `(tree_number, leaf_index)`
+    /// positions for which `keep` returns false, returning the removed notes.
+    pub fn retain_unspent_at(
+        &mut self,
+        positions: impl IntoIterator<Item = (u32, u32)>,
+        mut keep: impl FnMut(&UtxoNote) -> bool,
+    ) -> Vec<UtxoNote> {
+        let mut removed = Vec::new();
+        for (tree_number, leaf_index) in positions {
+            let Some(notebook) = self.notebooks.get_mut(&tree_number) else {
+                continue;
+            };
+            if notebook
+                .unspent()
+                .get(&leaf_index)
+                .is_some_and(|note| !keep(note))
+            {
+                removed.extend(notebook.remove(leaf_index));
+            }
+        }
+        removed
+    }
+
+    /// Reverts notes created or spent after the given block.
+    pub fn rollback(&mut self, block_number: u64) {
+        for notebook in self.notebooks.values_mut() {
+            notebook.rollback(block_number);
+        }
+    }
+
     /// Calculates the balance of the account by summing up the values of all its notes.
     pub fn balance(&self) -> HashMap<AssetId, u128> {
         let mut balances: HashMap<AssetId, u128> = HashMap::new();
 
         for (_, notebook) in self.notebooks.iter() {
             for (_, note) in notebook.unspent().iter() {
-                match note.asset() {
-                    AssetId::Erc20(address) => {
-                        balances
-                            .entry(AssetId::Erc20(address))
-                            .and_modify(|e| *e += note.value())
-                            .or_insert(note.value());
-                    }
-                    _ => todo!(),
-                }
+                *balances.entry(note.asset()).or_insert(0) += note.value();
             }
         }
 
@@ -81,6 +217,7 @@ impl IndexedAccount {
     pub fn handle_shield_event(
         &mut self,
         event: &RailgunSmartWallet::Shield,
+        block_number: u64,
     ) -> Result<bool, NoteError> {
         let tree_number: u32 = event.treeNumber.saturating_to();
         let start_position: u32 = event.startPosition.saturating_to();
@@ -133,7 +270,7 @@ impl IndexedAccount {
             self.notebooks
                 .entry(tree_number)
                 .or_default()
-                .add(leaf_index, note);
+                .add(leaf_index, note, block_number);
             added = true;
         }
 
@@ -144,6 +281,7 @@ impl IndexedAccount {
     pub fn handle_transact_event(
         &mut self,
         event: &RailgunSmartWallet::Transact,
+        block_number: u64,
     ) -> Result<bool, NoteError> {
         let tree_number: u32 = event.treeNumber.saturating_to();
         let start_position: u32 = event.startPosition.saturating_to();
@@ -184,13 +322,24 @@ impl IndexedAccount {
             self.notebooks
                 .entry(tree_number)
                 .or_default()
-                .add(leaf_index, note);
+                .add(leaf_index, note, block_number);
             added = true;
         }
 
         Ok(added)
     }
 
+    /// Returns true if this account sent any of the Transact event's outputs.
+    ///
+    /// Outputs are encrypted to both parties, so the sender can decrypt them
+    /// even when none were sent to itself.
+    pub fn sent_transact_event(&self, event: &RailgunSmartWallet::Transact) -> bool {
+        event
+            .ciphertext
+            .iter()
+            .any(|ciphertext| SentNote::decrypt(self.signer.as_ref(), ciphertext).is_ok())
+    }
+
     /// Handles a nullified event for this account. Returns true if any notes were nullified.
     pub fn handle_nullified_event(
         &mut self,
@@ -213,3 +362,101 @@ impl IndexedAccount {
         matched
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+    use tracing_test::traced_test;
+
+    use super::*;
+    use crate::{
+        crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+        railgun::{
+            note::utxo::test_note,
+            signer::{PrivateKeySigner, test_signer},
+        },
+    };
+
+    #[test]
+    #[traced_test]
+    fn test_encrypted_backup_round_trip() {
+        let signer: Arc<dyn Signer> = test_signer();
+        let mut account = IndexedAccount::new(signer.clone());
+        account
+            .notebooks
+            .entry(1)
+            .or_default()
+            .add(0, test_note(), 10);
+
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let backup = account.encrypted_backup("password", 12, &mut rng).unwrap();
+
+        let (restored, synced_block) =
+            IndexedAccount::from_backup(signer, &backup, "password").unwrap();
+        assert_eq!(synced_block, 12);
+        assert_eq!(restored.unspent(), account.unspent());
+        assert_eq!(restored.unspent_before(9).len(), 0);
+
+        let other: Arc<dyn Signer> = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let err = IndexedAccount::from_backup(other, &backup, "password").err();
+        assert!(matches!(err, Some(BackupError::SignerMismatch)));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_notes_by_nullifiers() {
+        let signer: Arc<dyn Signer> = test_signer();
+        let mut account = IndexedAccount::new(signer);
+        let notebook = account.notebooks.entry(1).or_default();
+        notebook.add(0, test_note(), 10);
+        notebook.add(1, test_note(), 10);
+
+        let note = test_note();
+        let nullifiers = [note.nullifier(U256::from(1)), note.nullifier(U256::ZERO)];
+
+        //? Spent notes are found too, in nullifier order
+        account
+            .notebooks
+            .get_mut(&1)
+            .unwrap()
+            .nullify(nullifiers[0], 0);
+        let notes = account.notes_by_nullifiers(1, &nullifiers).unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].nullifier(U256::from(1)), nullifiers[0]);
+        assert_eq!(notes[1].nullifier(U256::ZERO), nullifiers[1]);
+
+        assert!(account.notes_by_nullifiers(2, &nullifiers).is_none());
+        assert!(
+            account
+                .notes_by_nullifiers(1, &[nullifiers[0], U256::from(42)])
+                .is_none()
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_retain_unspent_at() {
+        let signer: Arc<dyn Signer> = test_signer();
+        let mut account = IndexedAccount::new(signer);
+        let notebook = account.notebooks.entry(1).or_default();
+        notebook.add(0, test_note(), 10);
+        notebook.add(1, test_note(), 10);
+
+        //? Keep only the first note visited, at position 0. Position 2 has no
+        //? note and is skipped.
+        let mut first = true;
+        let removed =
+            account.retain_unspent_at([(1, 0), (1, 1), (1, 2)], |_| std::mem::take(&mut first));
+        assert_eq!(removed.len(), 1);
+        assert_eq!(account.unspent().len(), 1);
+        assert_eq!(account.balance().values().sum::<u128>(), 100);
+
+        let notebook = &account.notebooks[&1];
+        assert!(notebook.unspent().contains_key(&0));
+        assert!(!notebook.created_at.contains_key(&1));
+    }
+}
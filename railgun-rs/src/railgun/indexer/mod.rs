@@ -1,3 +1,4 @@
+mod backup;
 mod indexed_account;
 mod notebook;
 pub mod syncer;
@@ -5,5 +6,8 @@ mod txid_indexer;
 mod txid_tree_set;
 mod utxo_indexer;
 
+pub use backup::BackupError;
 pub use txid_indexer::{TxidIndexer, TxidIndexerError, TxidIndexerState};
 pub use utxo_indexer::{UtxoIndexer, UtxoIndexerError, UtxoIndexerState};
+#[cfg(test)]
+pub use utxo_indexer::{test_indexer, test_indexer_with_events};
@@ -1,8 +1,12 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 use ruint::aliases::U256;
+use serde::{Deserialize, Serialize};
 
-use crate::railgun::note::{IncludedNote, utxo::UtxoNote};
+use crate::railgun::{
+    note::{IncludedNote, utxo::UtxoNote},
+    signer::Signer,
+};
 
 /// A Notebook holds a collection of spent and unspent notes for a Railgun account,
 /// on a single tree.
@@ -10,11 +14,23 @@ use crate::railgun::note::{IncludedNote, utxo::UtxoNote};
 pub struct Notebook {
     pub unspent: BTreeMap<u32, UtxoNote>,
     pub spent: BTreeMap<u32, SpentNote>,
+    /// Block number each note was created in, keyed by note position.
+    pub created_at: BTreeMap<u32, u64>,
 }
 
 #[derive(Debug, Clone)]
 pub struct SpentNote {
     inner: UtxoNote,
+    /// Block number the note was spent in.
+    spent_at: u64,
+}
+
+/// A notebook's notes without their signer, for backups.
+#[derive(Serialize, Deserialize)]
+pub struct NotebookBackup {
+    unspent: BTreeMap<u32, UtxoNote<()>>,
+    spent: BTreeMap<u32, UtxoNote<()>>,
+    created_at: BTreeMap<u32, u64>,
 }
 
 impl Notebook {
@@ -22,6 +38,7 @@ impl Notebook {
         Notebook {
             unspent: BTreeMap::new(),
             spent: BTreeMap::new(),
+            created_at: BTreeMap::new(),
         }
     }
 
@@ -47,15 +64,67 @@ impl Notebook {
         all_notes
     }
 
-    /// Adds an unspent note to the notebook.
-    pub fn add(&mut self, note_position: u32, note: UtxoNote) {
+    /// Returns the unspent notes created at or before the given block.
+    pub fn unspent_before(&self, block_number: u64) -> impl Iterator<Item = &UtxoNote> {
+        self.unspent.iter().filter_map(move |(position, note)| {
+            let created_at = self.created_at.get(position).copied().unwrap_or(0);
+            (created_at <= block_number).then_some(note)
+        })
+    }
+
+    /// Adds an unspent note to the notebook, created in the given block.
+    pub fn add(&mut self, note_position: u32, note: UtxoNote, block_number: u64) {
         self.unspent.insert(note_position, note);
+        self.created_at.insert(note_position, block_number);
+    }
+
+    /// Removes an unspent note from the notebook.
+    pub fn remove(&mut self, note_position: u32) -> Option<UtxoNote> {
+        self.created_at.remove(&note_position);
+        self.unspent.remove(&note_position)
+    }
+
+    pub fn backup(&self) -> NotebookBackup {
+        NotebookBackup {
+            unspent: self
+                .unspent
+                .iter()
+                .map(|(position, note)| (*position, note.without_signer()))
+                .collect(),
+            spent: self
+                .spent
+                .iter()
+                .map(|(position, note)| (*position, note.inner.without_signer()))
+                .collect(),
+            created_at: self.created_at.clone(),
+        }
+    }
+
+    /// Restores a notebook from a backup. Returns `None` if any note doesn't
+    /// belong to the signer.
+    pub fn restore(backup: NotebookBackup, signer: &Arc<dyn Signer>) -> Option<Self> {
+        let mut unspent = BTreeMap::new();
+        for (position, note) in backup.unspent {
+            unspent.insert(position, note.with_signer(signer.clone())?);
+        }
+
+        let mut spent = BTreeMap::new();
+        for (position, note) in backup.spent {
+            let inner = note.with_signer(signer.clone())?;
+            spent.insert(position, SpentNote { inner, spent_at: 0 });
+        }
+
+        Some(Notebook {
+            unspent,
+            spent,
+            created_at: backup.created_at,
+        })
     }
 
     /// Nullifies (spends) a note in the notebook based on its nullifier.
     ///
     /// Returns the spent note if found, otherwise returns None.
-    pub fn nullify(&mut self, nullifier: U256, _timestamp: u64) -> Option<SpentNote> {
+    pub fn nullify(&mut self, nullifier: U256, block_number: u64) -> Option<SpentNote> {
         let Some((&leaf_index, _)) = self
             .unspent
             .iter()
@@ -65,8 +134,78 @@ impl Notebook {
         };
         let note = self.unspent.remove(&leaf_index).unwrap();
 
-        let spent_note = SpentNote { inner: note };
+        let spent_note = SpentNote {
+            inner: note,
+            spent_at: block_number,
+        };
         self.spent.insert(leaf_index, spent_note.clone());
         Some(spent_note)
     }
+
+    /// Reverts notes created or spent after the given block. Spent notes are
+    /// restored to unspent before notes are removed, so a note created and
+    /// spent after the block is removed entirely.
+    pub fn rollback(&mut self, block_number: u64) {
+        let respent: Vec<u32> = self
+            .spent
+            .iter()
+            .filter(|(_, note)| note.spent_at > block_number)
+            .map(|(position, _)| *position)
+            .collect();
+        for position in respent {
+            let note = self.spent.remove(&position).unwrap();
+            self.unspent.insert(position, note.inner);
+        }
+
+        let created: Vec<u32> = self
+            .created_at
+            .iter()
+            .filter(|(_, created_at)| **created_at > block_number)
+            .map(|(position, _)| *position)
+            .collect();
+        for position in created {
+            self.remove(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::*;
+    use crate::railgun::note::utxo::test_note;
+
+    #[test]
+    #[traced_test]
+    fn test_unspent_before() {
+        let mut notebook = Notebook::new();
+        notebook.add(0, test_note(), 10);
+        notebook.add(1, test_note(), 20);
+
+        assert_eq!(notebook.unspent_before(9).count(), 0);
+        assert_eq!(notebook.unspent_before(10).count(), 1);
+        assert_eq!(notebook.unspent_before(20).count(), 2);
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_rollback() {
+        let note = test_note();
+        let mut notebook = Notebook::new();
+        notebook.add(0, note.clone(), 10);
+        notebook.add(1, note.clone(), 10);
+        notebook.add(2, note.clone(), 20);
+        notebook.nullify(note.nullifier(U256::from(0)), 10);
+        notebook.nullify(note.nullifier(U256::from(1)), 20);
+        notebook.nullify(note.nullifier(U256::from(2)), 20);
+
+        notebook.rollback(10);
+
+        assert!(notebook.spent.contains_key(&0));
+        assert!(notebook.unspent.contains_key(&1));
+        assert!(!notebook.unspent.contains_key(&2));
+        assert!(!notebook.spent.contains_key(&2));
+        assert!(!notebook.created_at.contains_key(&2));
+    }
 }
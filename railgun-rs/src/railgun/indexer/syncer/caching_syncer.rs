@@ -0,0 +1,302 @@
+use std::path::{Path, PathBuf};
+
+use futures::{StreamExt, TryStreamExt, stream};
+use tracing::{info, warn};
+
+use super::{
+    compat::{BoxedSyncStream, stop_after_error},
+    syncer::{NoteSyncer, SyncError, SyncEvent},
+};
+
+/// Default number of blocks stored per cache file.
+const DEFAULT_CHUNK_SIZE: u64 = 100_000;
+
+/// A syncer that caches events fetched by an inner syncer to disk.
+///
+/// Events are cached in fixed-size block ranges ("chunks"). Only chunks that
+/// are entirely at or below the inner syncer's latest block are cached, so
+/// ranges near the chain tip are always fetched fresh. Subsequent syncs over
+/// cached ranges are served from disk without touching the inner syncer.
+///
+/// Chunks are only cached once the inner syncer's stream completes without
+/// errors. Errors are passed through so the caller can retry the range.
+pub struct CachingSyncer<S> {
+    inner: S,
+    cache_dir: PathBuf,
+    chunk_size: u64,
+}
+
+impl<S: NoteSyncer> CachingSyncer<S> {
+    /// Creates a new CachingSyncer storing its cache files in `cache_dir`.
+    pub fn new(inner: S, cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            cache_dir: cache_dir.into(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Sets the number of blocks stored per cache file.
+    ///
+    /// Changing the chunk size invalidates previously cached chunks, since
+    /// they are keyed by block range.
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    fn chunk_path(&self, chunk_start: u64, chunk_end: u64) -> PathBuf {
+        self.cache_dir
+            .join(format!("{}-{}.bitcode", chunk_start, chunk_end))
+    }
+
+    /// Returns the events for the given chunk, either from the cache or by
+    /// fetching them from the inner syncer and caching the result.
+    async fn chunk_events(
+        &self,
+        chunk_start: u64,
+        chunk_end: u64,
+    ) -> Result<Vec<SyncEvent>, SyncError> {
+        let path = self.chunk_path(chunk_start, chunk_end);
+        if let Some(events) = read_chunk(&path) {
+            info!("Loaded blocks {}-{} from cache", chunk_start, chunk_end);
+            return Ok(events);
+        }
+
+        let events = self.fetch(chunk_start, chunk_end).await?;
+        if let Err(e) = write_chunk(&self.cache_dir, &path, &events) {
+            warn!(
+                "Failed to cache blocks {}-{}: {}",
+                chunk_start, chunk_end, e
+            );
+        }
+        Ok(events)
+    }
+
+    async fn fetch(&self, from_block: u64, to_block: u64) -> Result<Vec<SyncEvent>, SyncError> {
+        let stream = self
+            .inner
+            .sync(from_block, to_block)
+            .await
+            .map_err(|e| SyncError::Provider(e.to_string()))?;
+
+        stream.try_collect().await.inspect_err(|e| {
+            warn!(
+                "Inner syncer failed for blocks {}-{}: {}",
+                from_block, to_block, e
+            );
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: NoteSyncer> NoteSyncer for CachingSyncer<S> {
+    async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        self.inner.latest_block().await
+    }
+
+    async fn sync(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<BoxedSyncStream<'_>, Box<dyn std::error::Error>> {
+        let latest_block = self.inner.latest_block().await?;
+
+        let mut ranges = Vec::new();
+        let mut chunk_start = from_block - from_block % self.chunk_size;
+        while chunk_start <= to_block {
+            let chunk_end = chunk_start + self.chunk_size - 1;
+            ranges.push((chunk_start, chunk_end));
+            chunk_start += self.chunk_size;
+        }
+
+        let stream = stream::iter(ranges)
+            .then(move |(chunk_start, chunk_end)| async move {
+                if chunk_end <= latest_block {
+                    self.chunk_events(chunk_start, chunk_end).await
+                } else {
+                    //? Partial chunk at the chain tip, don't cache it
+                    self.fetch(from_block.max(chunk_start), to_block.min(chunk_end))
+                        .await
+                }
+            })
+            .flat_map(move |events| {
+                let events: Vec<_> = match events {
+                    Ok(events) => events
+                        .into_iter()
+                        .filter(|e| {
+                            let block = e.block_number();
+                            block >= from_block && block <= to_block
+                        })
+                        .map(Ok)
+                        .collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                stream::iter(events)
+            });
+
+        Ok(Box::pin(stop_after_error(stream)))
+    }
+}
+
+fn read_chunk(path: &Path) -> Option<Vec<SyncEvent>> {
+    let bytes = std::fs::read(path).ok()?;
+    match bitcode::deserialize(&bytes) {
+        Ok(events) => Some(events),
+        Err(e) => {
+            warn!("Ignoring corrupt cache file {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn write_chunk(
+    cache_dir: &Path,
+    path: &Path,
+    events: &[SyncEvent],
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(cache_dir)?;
+    let bytes = bitcode::serialize(events)?;
+
+    //? Write to a temporary file first so an interrupted write never leaves a
+    //? truncated chunk behind.
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, bytes)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use futures::executor::block_on;
+    use ruint::aliases::U256;
+
+    use super::*;
+    use crate::railgun::indexer::syncer::LegacyCommitment;
+
+    struct MockSyncer {
+        latest_block: u64,
+        calls: Arc<AtomicUsize>,
+        /// Fails the stream at the first event at or after this block.
+        fail_at: Option<u64>,
+    }
+
+    #[async_trait::async_trait]
+    impl NoteSyncer for MockSyncer {
+        async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error>> {
+            Ok(self.latest_block)
+        }
+
+        async fn sync(
+            &self,
+            from_block: u64,
+            to_block: u64,
+        ) -> Result<BoxedSyncStream<'_>, Box<dyn std::error::Error>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            // One event every 10 blocks
+            let events = (from_block..=to_block)
+                .filter(|b| b % 10 == 0)
+                .map(|b| {
+                    SyncEvent::Legacy(
+                        LegacyCommitment {
+                            hash: U256::from(b),
+                            tree_number: 0,
+                            leaf_index: b as u32,
+                        },
+                        b,
+                    )
+                })
+                .map(|e| match self.fail_at {
+                    Some(block) if e.block_number() >= block => {
+                        Err(SyncError::Provider("rate limited".to_string()))
+                    }
+                    _ => Ok(e),
+                })
+                .collect::<Vec<_>>();
+            Ok(Box::pin(stop_after_error(stream::iter(events))))
+        }
+    }
+
+    fn blocks(syncer: &CachingSyncer<MockSyncer>, from: u64, to: u64) -> Vec<u64> {
+        block_on(async {
+            let stream = syncer.sync(from, to).await.unwrap();
+            stream.map(|e| e.unwrap().block_number()).collect().await
+        })
+    }
+
+    #[test]
+    fn test_caching_syncer() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("railgun-caching-syncer-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&cache_dir);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let syncer = CachingSyncer::new(
+            MockSyncer {
+                latest_block: 250,
+                calls: calls.clone(),
+                fail_at: None,
+            },
+            &cache_dir,
+        )
+        .with_chunk_size(100);
+
+        // Chunks 0-99 and 100-199 are cached, 200-250 is at the tip
+        let first = blocks(&syncer, 50, 250);
+        assert_eq!(first, (50..=250).step_by(10).collect::<Vec<_>>());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Cached chunks are served from disk, only the tip is refetched
+        let second = blocks(&syncer, 50, 250);
+        assert_eq!(second, first);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        // Fully cached ranges never hit the inner syncer
+        let third = blocks(&syncer, 120, 180);
+        assert_eq!(third, (120..=180).step_by(10).collect::<Vec<_>>());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_caching_syncer_errors() {
+        let cache_dir = std::env::temp_dir().join(format!(
+            "railgun-caching-syncer-errors-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&cache_dir);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let syncer = CachingSyncer::new(
+            MockSyncer {
+                latest_block: 250,
+                calls: calls.clone(),
+                fail_at: Some(150),
+            },
+            &cache_dir,
+        )
+        .with_chunk_size(100);
+
+        // The error is passed through and the stream ends
+        let events: Vec<_> = block_on(async {
+            let stream = syncer.sync(0, 250).await.unwrap();
+            stream.collect().await
+        });
+        assert_eq!(events.len(), 11);
+        assert!(events[..10].iter().all(|e| e.is_ok()));
+        assert!(matches!(events[10], Err(SyncError::Provider(_))));
+
+        // Only the complete chunk was cached
+        assert!(syncer.chunk_path(0, 99).exists());
+        assert!(!syncer.chunk_path(100, 199).exists());
+
+        let _ = std::fs::remove_dir_all(&cache_dir);
+    }
+}
@@ -1,12 +1,30 @@
 use std::pin::Pin;
 
-use futures::Stream;
+use futures::{Stream, StreamExt, future};
 
-use super::syncer::SyncEvent;
+use super::syncer::{SyncError, SyncEvent};
 
 /// Boxed stream type that is `Send` on native but not on WASM.
+///
+/// Streams end after yielding their first error.
 #[cfg(not(feature = "wasm"))]
-pub type BoxedSyncStream<'a> = Pin<Box<dyn Stream<Item = SyncEvent> + Send + 'a>>;
+pub type BoxedSyncStream<'a> =
+    Pin<Box<dyn Stream<Item = Result<SyncEvent, SyncError>> + Send + 'a>>;
 
 #[cfg(feature = "wasm")]
-pub type BoxedSyncStream<'a> = Pin<Box<dyn Stream<Item = SyncEvent> + 'a>>;
+pub type BoxedSyncStream<'a> = Pin<Box<dyn Stream<Item = Result<SyncEvent, SyncError>> + 'a>>;
+
+/// Ends a sync stream after its first error, since events after a gap can't be
+/// applied to the trees.
+pub fn stop_after_error<S, T, E>(stream: S) -> impl Stream<Item = Result<T, E>>
+where
+    S: Stream<Item = Result<T, E>>,
+{
+    stream.scan(false, |failed, item| {
+        if *failed {
+            return future::ready(None);
+        }
+        *failed = item.is_err();
+        future::ready(Some(item))
+    })
+}
@@ -1,3 +1,5 @@
+#[cfg(not(feature = "wasm"))]
+mod caching_syncer;
 mod chained_syncer;
 mod compat;
 mod decimal_bigint;
@@ -5,7 +7,12 @@ mod rpc_syncer;
 mod subsquid_syncer;
 pub(super) mod syncer;
 
+#[cfg(not(feature = "wasm"))]
+pub use caching_syncer::CachingSyncer;
 pub use chained_syncer::ChainedSyncer;
-pub use rpc_syncer::RpcSyncer;
+pub use compat::BoxedSyncStream;
+pub use rpc_syncer::{RpcSyncer, SyncConfig};
 pub use subsquid_syncer::SubsquidSyncer;
-pub use syncer::{LegacyCommitment, NoteSyncer, Operation, SyncEvent, TransactionSyncer};
+pub use syncer::{
+    LegacyCommitment, NoteSyncer, Operation, SyncError, SyncEvent, TransactionSyncer,
+};
@@ -1,6 +1,7 @@
 use alloy::{
+    primitives::TxHash,
     providers::{DynProvider, Provider},
-    rpc::types::Filter,
+    rpc::types::{Filter, Log},
 };
 use alloy_sol_types::SolEvent;
 use futures::{Stream, StreamExt, stream};
@@ -9,21 +10,51 @@ use tracing::{info, warn};
 use crate::{
     abis::railgun::RailgunSmartWallet,
     chain_config::ChainConfig,
-    railgun::indexer::syncer::{compat::BoxedSyncStream, syncer::{NoteSyncer, SyncEvent}},
+    railgun::indexer::syncer::{
+        compat::{BoxedSyncStream, stop_after_error},
+        syncer::{NoteSyncer, SyncError, SyncEvent},
+    },
     sleep::sleep,
 };
 
 pub struct RpcSyncer {
     provider: DynProvider,
     batch_size: u64,
-    timeout: web_time::Duration,
+    config: SyncConfig,
     chain: ChainConfig,
 }
 
+/// Controls how `eth_getLogs` requests are issued during sync. Batches may be
+/// fetched concurrently, but events are always emitted in block order.
+#[derive(Debug, Clone, Copy)]
+pub struct SyncConfig {
+    /// Maximum number of batches fetched concurrently.
+    pub concurrency: usize,
+    /// Maximum number of requests started per second.
+    pub requests_per_sec: u32,
+    /// Number of times a failed read is retried before giving up.
+    pub max_retries: u32,
+    /// Delay before the first retry. Doubles after each failed attempt.
+    pub retry_backoff: web_time::Duration,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            requests_per_sec: 10,
+            max_retries: 3,
+            retry_backoff: web_time::Duration::from_millis(500),
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum RpcSyncerError {
     #[error("Error decoding log: {0}")]
     LogDecodeError(#[from] alloy_sol_types::Error),
+    #[error("Log is missing its block number (tx {0:?})")]
+    MissingBlockNumber(Option<TxHash>),
 }
 
 impl RpcSyncer {
@@ -31,7 +62,7 @@ impl RpcSyncer {
         Self {
             provider,
             batch_size: 10000,
-            timeout: web_time::Duration::from_millis(100),
+            config: SyncConfig::default(),
             chain,
         }
     }
@@ -41,8 +72,17 @@ impl RpcSyncer {
         self
     }
 
+    pub fn with_sync_config(mut self, config: SyncConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Waits at least `timeout` between requests, rounded to the nearest
+    /// whole number of requests per second.
+    #[deprecated(note = "use `with_sync_config` and set `requests_per_sec`")]
     pub fn with_timeout(mut self, timeout: web_time::Duration) -> Self {
-        self.timeout = timeout;
+        let per_sec = 1.0 / timeout.as_secs_f64();
+        self.config.requests_per_sec = per_sec.round().clamp(1.0, u32::MAX as f64) as u32;
         self
     }
 }
@@ -51,7 +91,10 @@ impl RpcSyncer {
 #[cfg_attr(feature = "wasm", async_trait::async_trait(?Send))]
 impl NoteSyncer for RpcSyncer {
     async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error>> {
-        let block_number = self.provider.get_block_number().await?;
+        let block_number = with_retry(self.config, "eth_blockNumber", || {
+            self.provider.get_block_number()
+        })
+        .await?;
         Ok(block_number)
     }
 
@@ -75,12 +118,16 @@ impl RpcSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + Send + '_ {
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + Send + '_ {
         self.event_stream_inner(from_block, to_block)
     }
 
     #[cfg(feature = "wasm")]
-    fn event_stream(&self, from_block: u64, to_block: u64) -> impl Stream<Item = SyncEvent> + '_ {
+    fn event_stream(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + '_ {
         self.event_stream_inner(from_block, to_block)
     }
 
@@ -88,90 +135,300 @@ impl RpcSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + '_ {
-        stream::unfold(from_block, move |current_block| async move {
-            if current_block > to_block {
-                return None;
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + '_ {
+        let ranges = batch_ranges(from_block, to_block, self.batch_size);
+        let interval = web_time::Duration::from_secs(1) / self.config.requests_per_sec.max(1);
+        let start = web_time::Instant::now();
+
+        let events = stream::iter(ranges.enumerate())
+            .map(move |(i, (batch_start, batch_end))| async move {
+                //? Schedule each request at a fixed offset from the start of the
+                //? sync so concurrent requests still respect the rate limit.
+                let scheduled = interval * i as u32;
+                let elapsed = start.elapsed();
+                if scheduled > elapsed {
+                    sleep(scheduled - elapsed).await;
+                }
+
+                self.fetch_events(batch_start, batch_end).await
+            })
+            //? `buffered` yields results in order, so events are applied to the
+            //? trees in block order regardless of which request finishes first.
+            .buffered(self.config.concurrency.max(1))
+            .flat_map(|events| {
+                let events: Vec<_> = match events {
+                    Ok(events) => events.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                stream::iter(events)
+            });
+
+        stop_after_error(events)
+    }
+
+    /// Fetches and decodes the logs for a single batch. Returns an error if
+    /// the batch could not be fetched after retrying.
+    async fn fetch_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<SyncEvent>, SyncError> {
+        let filter = Filter::new()
+            .address(self.chain.railgun_smart_wallet)
+            .from_block(from_block)
+            .to_block(to_block);
+
+        let logs = with_retry(self.config, "eth_getLogs", || {
+            self.provider.get_logs(&filter)
+        })
+        .await;
+        let logs = match logs {
+            Ok(logs) => logs,
+            Err(e) => {
+                warn!(
+                    "Failed to fetch logs from blocks {} to {}: {}",
+                    from_block, to_block, e
+                );
+                return Err(SyncError::Provider(e.to_string()));
             }
+        };
 
-            // Fetch the next batch of logs
-            let batch_end = std::cmp::min(current_block + self.batch_size - 1, to_block);
-            let filter = Filter::new()
-                .address(self.chain.railgun_smart_wallet)
-                .from_block(current_block)
-                .to_block(batch_end);
+        info!(
+            "Fetched {} logs from blocks {} to {}",
+            logs.len(),
+            from_block,
+            to_block
+        );
 
-            let start = web_time::Instant::now();
-            let logs = match self.provider.get_logs(&filter).await {
-                Ok(logs) => logs,
+        // Decode logs into events
+        let mut events = Vec::new();
+        for log in logs {
+            match decode_log(&log) {
+                Ok(Some(event)) => events.push(event),
+                Ok(None) => {}
+                Err(RpcSyncerError::LogDecodeError(e)) => {
+                    warn!("Failed to decode log: {}", e);
+                }
                 Err(e) => {
-                    warn!(
-                        "Failed to fetch logs from blocks {} to {}: {}",
-                        current_block, batch_end, e
-                    );
-                    return None;
+                    //? Skipping the log would silently corrupt the merkle
+                    //? trees, so fail the batch instead.
+                    warn!("Failed to process logs from block {}: {}", from_block, e);
+                    return Err(SyncError::Provider(e.to_string()));
                 }
-            };
-            let duration = start.elapsed();
-            let sleep_duration = self.timeout.saturating_sub(duration);
-            if sleep_duration > web_time::Duration::from_secs(0) {
-                sleep(sleep_duration).await;
             }
+        }
 
-            info!(
-                "Fetched {} logs from blocks {} to {}",
-                logs.len(),
-                current_block,
-                batch_end
-            );
-
-            // Decode logs into events
-            let mut events = Vec::new();
-            for log in logs {
-                let topic0 = log.topics()[0];
-                let block_number = log.block_number.unwrap_or(0);
-                let block_timestamp = log.block_timestamp.unwrap_or(0);
-
-                match topic0 {
-                    RailgunSmartWallet::Shield::SIGNATURE_HASH => {
-                        match RailgunSmartWallet::Shield::decode_log(&log.inner) {
-                            Ok(event) => events.push(SyncEvent::Shield(event.data, block_number)),
-                            Err(e) => warn!("Failed to decode Shield event: {}", e),
-                        }
-                    }
-                    RailgunSmartWallet::Transact::SIGNATURE_HASH => {
-                        match RailgunSmartWallet::Transact::decode_log(&log.inner) {
-                            Ok(event) => {
-                                events.push(SyncEvent::Transact(event.data, block_timestamp))
-                            }
-                            Err(e) => warn!("Failed to decode Transact event: {}", e),
-                        }
-                    }
-                    RailgunSmartWallet::Nullified::SIGNATURE_HASH => {
-                        match RailgunSmartWallet::Nullified::decode_log(&log.inner) {
-                            Ok(event) => {
-                                events.push(SyncEvent::Nullified(event.data, block_timestamp))
-                            }
-                            Err(e) => warn!("Failed to decode Nullified event: {}", e),
-                        }
-                    }
-                    RailgunSmartWallet::Unshield::SIGNATURE_HASH => {
-                        // Unshield events not needed. Spent notes are already
-                        // tracked via Nullified events.
-                    }
-                    _ => {
-                        warn!("Unknown event with topic0: {:?}", topic0);
-                    }
-                }
+        // TODO: Operation events are not implemented for RPC syncer.
+        // Constructing Operations requires call tracing to correlate which events
+        // belong to which Railgun transaction within a block.
+
+        Ok(events)
+    }
+}
+
+/// Runs an idempotent provider read, retrying failures with exponential backoff
+/// so transient RPC errors don't abort the sync.
+async fn with_retry<T, E, F, Fut>(config: SyncConfig, method: &str, mut call: F) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut backoff = config.retry_backoff;
+    let mut attempt = 0;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < config.max_retries => {
+                attempt += 1;
+                warn!(
+                    "{} failed (attempt {} of {}), retrying in {:?}: {}",
+                    method,
+                    attempt,
+                    config.max_retries + 1,
+                    backoff,
+                    e
+                );
+                sleep(backoff).await;
+                backoff *= 2;
             }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Splits `[from_block, to_block]` into inclusive ranges of at most `batch_size` blocks.
+fn batch_ranges(
+    from_block: u64,
+    to_block: u64,
+    batch_size: u64,
+) -> impl Iterator<Item = (u64, u64)> + Send {
+    let batch_size = batch_size.max(1);
+    let mut next = Some(from_block).filter(|b| *b <= to_block);
+    std::iter::from_fn(move || {
+        let start = next?;
+        let end = start.saturating_add(batch_size - 1).min(to_block);
+        next = end.checked_add(1).filter(|b| *b <= to_block);
+        Some((start, end))
+    })
+}
+
+/// Decodes a railgun log into a sync event. Returns `Ok(None)` for logs that
+/// aren't needed for syncing.
+///
+/// Events are keyed by block number. The block timestamp is never read, since
+/// some providers omit it from `eth_getLogs` responses.
+fn decode_log(log: &Log) -> Result<Option<SyncEvent>, RpcSyncerError> {
+    let Some(topic0) = log.topic0() else {
+        return Ok(None);
+    };
+
+    let event = match *topic0 {
+        RailgunSmartWallet::Shield::SIGNATURE_HASH => {
+            let event = RailgunSmartWallet::Shield::decode_log(&log.inner)?;
+            SyncEvent::Shield(event.data, block_number(log)?)
+        }
+        RailgunSmartWallet::Transact::SIGNATURE_HASH => {
+            let event = RailgunSmartWallet::Transact::decode_log(&log.inner)?;
+            SyncEvent::Transact(event.data, block_number(log)?)
+        }
+        RailgunSmartWallet::Nullified::SIGNATURE_HASH => {
+            let event = RailgunSmartWallet::Nullified::decode_log(&log.inner)?;
+            SyncEvent::Nullified(event.data, block_number(log)?)
+        }
+        RailgunSmartWallet::Unshield::SIGNATURE_HASH => {
+            // Unshield events not needed. Spent notes are already
+            // tracked via Nullified events.
+            return Ok(None);
+        }
+        _ => {
+            warn!("Unknown event with topic0: {:?}", topic0);
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(event))
+}
+
+fn block_number(log: &Log) -> Result<u64, RpcSyncerError> {
+    log.block_number
+        .ok_or(RpcSyncerError::MissingBlockNumber(log.transaction_hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, B256};
+    use futures::future;
+
+    use super::*;
+
+    fn nullified_log(block_number: Option<u64>) -> Log {
+        let event = RailgunSmartWallet::Nullified {
+            treeNumber: 1,
+            nullifier: vec![B256::repeat_byte(42)],
+        };
+
+        Log {
+            inner: alloy::primitives::Log {
+                address: Address::ZERO,
+                data: event.encode_log_data(),
+            },
+            block_number,
+            block_timestamp: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_batch_ranges() {
+        assert_eq!(
+            batch_ranges(0, 25, 10).collect::<Vec<_>>(),
+            vec![(0, 9), (10, 19), (20, 25)]
+        );
+        assert_eq!(batch_ranges(5, 5, 10).collect::<Vec<_>>(), vec![(5, 5)]);
+        assert_eq!(batch_ranges(6, 5, 10).count(), 0);
+    }
 
-            // TODO: Operation events are not implemented for RPC syncer.
-            // Constructing Operations requires call tracing to correlate which events
-            // belong to which Railgun transaction within a block.
+    #[tokio::test]
+    async fn test_with_retry() {
+        let config = SyncConfig {
+            max_retries: 2,
+            retry_backoff: web_time::Duration::ZERO,
+            ..SyncConfig::default()
+        };
 
-            let next_block = batch_end + 1;
-            Some((stream::iter(events), next_block))
+        let mut calls = 0;
+        let result: Result<u32, String> = with_retry(config, "test", || {
+            calls += 1;
+            let result = if calls < 3 {
+                Err("transient".to_string())
+            } else {
+                Ok(calls)
+            };
+            future::ready(result)
+        })
+        .await;
+        assert_eq!(result, Ok(3));
+
+        let mut calls = 0;
+        let result: Result<u32, String> = with_retry(config, "test", || {
+            calls += 1;
+            future::ready(Err("down".to_string()))
         })
-        .flatten()
+        .await;
+        assert_eq!(result, Err("down".to_string()));
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_propagates_provider_error() {
+        let asserter = alloy::transport::mock::Asserter::new();
+        let provider = alloy::providers::ProviderBuilder::new()
+            .connect_mocked_client(asserter.clone())
+            .erased();
+        let config = SyncConfig {
+            max_retries: 0,
+            requests_per_sec: 1000,
+            ..SyncConfig::default()
+        };
+        let syncer = RpcSyncer::new(provider, crate::chain_config::MAINNET_CONFIG)
+            .with_batch_size(10)
+            .with_sync_config(config);
+
+        asserter.push_success(&vec![nullified_log(Some(5))]);
+        asserter.push_failure_msg("rate limited");
+        asserter.push_success(&vec![nullified_log(Some(25))]);
+
+        let events: Vec<_> = syncer.sync(0, 29).await.unwrap().collect().await;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].as_ref().unwrap().block_number(), 5);
+        assert!(matches!(events[1], Err(SyncError::Provider(_))));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_with_timeout() {
+        let asserter = alloy::transport::mock::Asserter::new();
+        let provider = alloy::providers::ProviderBuilder::new()
+            .connect_mocked_client(asserter)
+            .erased();
+        let syncer = RpcSyncer::new(provider, crate::chain_config::MAINNET_CONFIG);
+
+        let syncer = syncer.with_timeout(web_time::Duration::from_millis(100));
+        assert_eq!(syncer.config.requests_per_sec, 10);
+        let syncer = syncer.with_timeout(web_time::Duration::from_secs(5));
+        assert_eq!(syncer.config.requests_per_sec, 1);
+    }
+
+    #[test]
+    fn test_decode_log_without_timestamp() {
+        let event = decode_log(&nullified_log(Some(100))).unwrap().unwrap();
+        assert_eq!(event.block_number(), 100);
+    }
+
+    #[test]
+    fn test_decode_log_missing_block_number() {
+        let err = decode_log(&nullified_log(None)).unwrap_err();
+        assert!(matches!(err, RpcSyncerError::MissingBlockNumber(None)));
     }
 }
@@ -13,9 +13,11 @@ use crate::{
         TokenType,
     },
     railgun::indexer::syncer::{
-        compat::BoxedSyncStream,
+        compat::{BoxedSyncStream, stop_after_error},
         decimal_bigint,
-        syncer::{LegacyCommitment, NoteSyncer, Operation, SyncEvent, TransactionSyncer},
+        syncer::{
+            LegacyCommitment, NoteSyncer, Operation, SyncError, SyncEvent, TransactionSyncer,
+        },
     },
     sleep::sleep,
 };
@@ -84,6 +86,8 @@ pub enum SubsquidError {
     ParseInt(#[from] ParseError),
     #[error("Invalid data: {0}")]
     InvalidData(String),
+    #[error("Endpoint {0} is not a compatible Railgun squid: {1}")]
+    IncompatibleEndpoint(String, Box<SubsquidError>),
 }
 
 const MAX_RETRIES: u32 = 3;
@@ -99,6 +103,34 @@ impl SubsquidSyncer {
             batch_size: 20000,
         }
     }
+
+    /// Creates a syncer and probes the endpoint, failing if it isn't a
+    /// compatible Railgun squid.
+    pub async fn new_probed(endpoint: &str) -> Result<Self, SubsquidError> {
+        let syncer = Self::new(endpoint);
+        syncer.probe().await?;
+        Ok(syncer)
+    }
+
+    /// Checks that the endpoint responds to a minimal query with the expected
+    /// schema. Returns the endpoint's latest indexed block.
+    pub async fn probe(&self) -> Result<u64, SubsquidError> {
+        let request_body = BlockNumberQuery::build_query(block_number_query::Variables {});
+
+        let data: block_number_query::ResponseData = self
+            .post_graphql("probe", request_body)
+            .await
+            .map_err(|e| SubsquidError::IncompatibleEndpoint(self.endpoint.clone(), Box::new(e)))?;
+
+        let block_number = data
+            .transactions
+            .into_iter()
+            .next()
+            .map(|t| t.block_number.0.saturating_to::<u64>())
+            .unwrap_or(0);
+
+        Ok(block_number)
+    }
 }
 
 #[cfg_attr(not(feature = "wasm"), async_trait::async_trait)]
@@ -133,7 +165,7 @@ impl NoteSyncer for SubsquidSyncer {
         let commitment_stream = self.commitment_stream(from_block, to_block);
         let nullified_stream = self.nullified_stream(from_block, to_block);
 
-        let stream = commitment_stream.chain(nullified_stream);
+        let stream = stop_after_error(commitment_stream.chain(nullified_stream));
 
         Ok(Box::pin(stream))
     }
@@ -193,7 +225,7 @@ impl SubsquidSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + Send + '_ {
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + Send + '_ {
         self.commitment_stream_inner(from_block, to_block)
     }
 
@@ -202,7 +234,7 @@ impl SubsquidSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + '_ {
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + '_ {
         self.commitment_stream_inner(from_block, to_block)
     }
 
@@ -210,8 +242,11 @@ impl SubsquidSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + '_ {
-        stream::unfold(String::new(), move |last_id| async move {
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + '_ {
+        stream::unfold(Some(String::new()), move |last_id| async move {
+            //? A failed fetch clears the cursor so the stream ends after
+            //? yielding the error.
+            let last_id = last_id?;
             info!("Fetching commitments");
 
             let batch = match self
@@ -221,7 +256,8 @@ impl SubsquidSyncer {
                 Ok(batch) => batch,
                 Err(e) => {
                     warn!("Failed to fetch commitments: {}", e);
-                    return None;
+                    let error = Err(SyncError::Provider(e.to_string()));
+                    return Some((stream::iter(vec![error]), None));
                 }
             };
 
@@ -236,15 +272,16 @@ impl SubsquidSyncer {
                 batch.1.len(),
                 batch.2.len()
             );
-            let events: Vec<SyncEvent> = batch
+            let events: Vec<_> = batch
                 .0
                 .into_iter()
                 .map(|(s, b)| SyncEvent::Shield(s, b))
                 .chain(batch.1.into_iter().map(|(t, b)| SyncEvent::Transact(t, b)))
                 .chain(batch.2.into_iter().map(|(l, b)| SyncEvent::Legacy(l, b)))
+                .map(Ok)
                 .collect();
 
-            Some((stream::iter(events), batch.3))
+            Some((stream::iter(events), Some(batch.3)))
         })
         .flatten()
     }
@@ -254,7 +291,7 @@ impl SubsquidSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + Send + '_ {
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + Send + '_ {
         self.nullified_stream_inner(from_block, to_block)
     }
 
@@ -263,7 +300,7 @@ impl SubsquidSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + '_ {
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + '_ {
         self.nullified_stream_inner(from_block, to_block)
     }
 
@@ -271,8 +308,9 @@ impl SubsquidSyncer {
         &self,
         from_block: u64,
         to_block: u64,
-    ) -> impl Stream<Item = SyncEvent> + '_ {
-        stream::unfold(String::new(), move |last_id| async move {
+    ) -> impl Stream<Item = Result<SyncEvent, SyncError>> + '_ {
+        stream::unfold(Some(String::new()), move |last_id| async move {
+            let last_id = last_id?;
             info!("Fetching nullifieds");
 
             let batch = match self
@@ -282,7 +320,8 @@ impl SubsquidSyncer {
                 Ok(batch) => batch,
                 Err(e) => {
                     warn!("Failed to fetch nullifieds: {}", e);
-                    return None;
+                    let error = Err(SyncError::Provider(e.to_string()));
+                    return Some((stream::iter(vec![error]), None));
                 }
             };
 
@@ -292,13 +331,13 @@ impl SubsquidSyncer {
             }
 
             info!("Fetched batch of nullifieds: {}", batch.0.len());
-            let events: Vec<SyncEvent> = batch
+            let events: Vec<_> = batch
                 .0
                 .into_iter()
-                .map(|(n, b)| SyncEvent::Nullified(n, b))
+                .map(|(n, b)| Ok(SyncEvent::Nullified(n, b)))
                 .collect();
 
-            Some((stream::iter(events), batch.1))
+            Some((stream::iter(events), Some(batch.1)))
         })
         .flatten()
     }
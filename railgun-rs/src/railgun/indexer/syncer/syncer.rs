@@ -6,6 +6,8 @@ use crate::abis::railgun::RailgunSmartWallet;
 
 /// TODO: Consider making types for shield, transact, and nullified so we don't need to use the anvil
 /// types if it's more convenient.
+///
+/// Each event carries the block number it was emitted in.
 #[derive(Clone, Serialize, Deserialize)]
 pub enum SyncEvent {
     Shield(RailgunSmartWallet::Shield, u64),
@@ -14,6 +16,27 @@ pub enum SyncEvent {
     Legacy(LegacyCommitment, u64),
 }
 
+impl SyncEvent {
+    /// Returns the block number the event was emitted in.
+    pub fn block_number(&self) -> u64 {
+        match self {
+            SyncEvent::Shield(_, block)
+            | SyncEvent::Transact(_, block)
+            | SyncEvent::Nullified(_, block)
+            | SyncEvent::Legacy(_, block) => *block,
+        }
+    }
+}
+
+/// Error yielded by a sync stream when events for a range could not be fetched.
+/// Events before the error are complete, so callers can retry from the last
+/// fully synced block.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SyncError {
+    #[error("Provider error: {0}")]
+    Provider(String),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Operation {
     pub nullifiers: Vec<U256>,
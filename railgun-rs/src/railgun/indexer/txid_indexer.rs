@@ -1,18 +1,28 @@
 use std::sync::Arc;
 
+use alloy::primitives::ChainId;
+use ruint::aliases::U256;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::info;
 
-use crate::railgun::{
-    indexer::{
-        syncer::TransactionSyncer,
-        txid_tree_set::{TxidTreeError, TxidTreeSet, TxidTreeSetState},
+use crate::{
+    chain_config::ChainConfig,
+    railgun::{
+        indexer::{
+            syncer::{SubsquidSyncer, TransactionSyncer},
+            txid_tree_set::{TxidTreeError, TxidTreeSet, TxidTreeSetState},
+        },
+        merkle_tree::MerkleRoot,
+        poi::{PoiClient, PoiClientError},
     },
-    poi::PoiClient,
 };
 
 /// TxID indexer that maintains the set of Txid merkle trees.
+///
+/// Independent of any account, so services that only need validated txid
+/// trees (e.g. broadcasters or POI submitters) can run it without a
+/// `UtxoIndexer`.
 pub struct TxidIndexer {
     pub txid_set: TxidTreeSet,
     pub synced_block: u64,
@@ -31,6 +41,10 @@ pub enum TxidIndexerError {
     SyncerError(Box<dyn std::error::Error>),
     #[error("TXID tree error: {0}")]
     TxidTreeError(#[from] TxidTreeError),
+    #[error("POI client error: {0}")]
+    PoiClient(#[from] PoiClientError),
+    #[error("No transaction syncer available for chain {0}")]
+    NoSyncer(ChainId),
 }
 
 impl TxidIndexer {
@@ -42,6 +56,20 @@ impl TxidIndexer {
         }
     }
 
+    /// Creates an indexer from the chain config's defaults: syncing operations
+    /// from the chain's subsquid endpoint and validating against its POI node.
+    pub async fn for_chain(chain: ChainConfig) -> Result<Self, TxidIndexerError> {
+        let endpoint = chain
+            .subsquid_endpoint
+            .ok_or(TxidIndexerError::NoSyncer(chain.id))?;
+        let poi_client = PoiClient::for_chain(chain).await?;
+
+        Ok(Self::new(
+            Arc::new(SubsquidSyncer::new(endpoint)),
+            poi_client,
+        ))
+    }
+
     pub fn from_state(
         txid_syncer: Arc<dyn TransactionSyncer>,
         poi_client: PoiClient,
@@ -65,6 +93,30 @@ impl TxidIndexer {
         self.synced_block
     }
 
+    /// Returns the current root of the given TXID tree, if it exists.
+    pub fn txid_root(&self, tree_number: u32) -> Option<MerkleRoot> {
+        self.txid_set.tree(tree_number).map(|t| t.root())
+    }
+
+    /// Returns the number of synced operations still waiting to be validated
+    /// by the POI node.
+    pub fn pending_operations(&self) -> usize {
+        self.txid_set.pending_len()
+    }
+
+    /// Keeps validated operations spending any of the given nullifiers so
+    /// POIs can be proved for them later. See `UtxoIndexer::nullifiers`.
+    pub fn watch_nullifiers(&mut self, nullifiers: impl IntoIterator<Item = U256>) {
+        self.txid_set.watch(nullifiers);
+    }
+
+    /// Advances the txid trees over any pending operations the POI node has
+    /// since validated, without fetching new operations.
+    pub async fn validate(&mut self) -> Result<(), TxidIndexerError> {
+        self.txid_set.validate().await?;
+        Ok(())
+    }
+
     pub async fn sync(&mut self) -> Result<(), TxidIndexerError> {
         self.sync_to(u64::MAX).await
     }
@@ -96,8 +148,6 @@ impl TxidIndexer {
         self.synced_block = to_block;
 
         // Advance
-        self.txid_set.validate().await?;
-
-        Ok(())
+        self.validate().await
     }
 }
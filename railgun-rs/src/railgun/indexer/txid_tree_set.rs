@@ -1,4 +1,6 @@
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use ruint::aliases::U256;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -24,6 +26,13 @@ pub struct TxidTreeSet {
     /// Maps txid → (tree_number, leaf_index) for validated leaves.
     txid_to_position: HashMap<Txid, (u32, u32)>,
 
+    /// Validated operations spending a watched nullifier, kept so POIs can be
+    /// proved for them after the fact.
+    operations: HashMap<Txid, Operation>,
+
+    /// Nullifiers of notes whose spends should be kept in `operations`.
+    watched: HashSet<U256>,
+
     /// The packed validated txid index from the last successful `advance_to_validated()`.
     /// Format: `(tree_number << 16) | leaf_index_within_tree`.
     validated_index: u64,
@@ -35,6 +44,10 @@ pub struct TxidTreeSetState {
     pub trees: BTreeMap<u32, MerkleTreeState>,
     pub pending: Vec<(Operation, u64)>,
     pub txid_to_position: HashMap<Txid, (u32, u32)>,
+    #[serde(default)]
+    pub operations: HashMap<Txid, Operation>,
+    #[serde(default)]
+    pub watched: HashSet<U256>,
     pub validated_index: u64,
 }
 
@@ -53,6 +66,8 @@ impl TxidTreeSet {
             pending: VecDeque::new(),
             poi_client,
             txid_to_position: HashMap::new(),
+            operations: HashMap::new(),
+            watched: HashSet::new(),
             validated_index: 0,
         }
     }
@@ -69,6 +84,8 @@ impl TxidTreeSet {
             pending: state.pending.into_iter().collect(),
             poi_client,
             txid_to_position: state.txid_to_position,
+            operations: state.operations,
+            watched: state.watched,
             validated_index: state.validated_index,
         }
     }
@@ -79,6 +96,8 @@ impl TxidTreeSet {
             trees,
             pending: self.pending.iter().cloned().collect(),
             txid_to_position: self.txid_to_position.clone(),
+            operations: self.operations.clone(),
+            watched: self.watched.clone(),
             validated_index: self.validated_index,
         }
     }
@@ -88,12 +107,31 @@ impl TxidTreeSet {
         self.pending.push_back((op, block));
     }
 
+    /// Keeps operations spending any of the given nullifiers once they're
+    /// validated, so they can be looked up with `operation()`.
+    ///
+    /// Only operations validated after the call are kept.
+    pub fn watch(&mut self, nullifiers: impl IntoIterator<Item = U256>) {
+        self.watched.extend(nullifiers);
+    }
+
+    /// Returns the number of operations queued but not yet validated.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
     /// Returns the `(tree_number, leaf_index)` of a validated txid, or `None`
     /// if the txid has not yet been inserted into the validated trees.
     pub fn position_of(&self, txid: &Txid) -> Option<(u32, u32)> {
         self.txid_to_position.get(txid).copied()
     }
 
+    /// Returns the validated operation with the given txid, if it spends a
+    /// watched nullifier.
+    pub fn operation(&self, txid: &Txid) -> Option<&Operation> {
+        self.operations.get(txid)
+    }
+
     /// Returns a reference to the validated TXID tree with the given number.
     pub fn tree(&self, number: u32) -> Option<&TxidMerkleTree> {
         self.trees.get(&number)
@@ -138,6 +176,9 @@ impl TxidTreeSet {
 
             self.txid_to_position
                 .insert(txid, (tree_number, position as u32));
+            if op.nullifiers.iter().any(|n| self.watched.contains(n)) {
+                self.operations.insert(txid, op);
+            }
             total += 1;
         }
 
@@ -166,3 +207,50 @@ impl TxidTreeSet {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::*;
+    use crate::railgun::poi::TestPoiNode;
+
+    fn operation(nullifier: u64, start_position: u32) -> (Txid, Operation) {
+        let op = Operation {
+            nullifiers: vec![U256::from(nullifier)],
+            commitment_hashes: vec![U256::from(nullifier + 100)],
+            bound_params_hash: U256::ZERO,
+            utxo_tree_in: 0,
+            utxo_tree_out: 0,
+            utxo_out_start_index: start_position,
+        };
+        let txid = Txid::new(&op.nullifiers, &op.commitment_hashes, op.bound_params_hash);
+        (txid, op)
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_validate_keeps_watched_operations() {
+        let poi_client = PoiClient::with_transport(
+            TestPoiNode {
+                validated_txid_index: 1,
+            },
+            1,
+        )
+        .await
+        .unwrap();
+        let mut set = TxidTreeSet::new(poi_client);
+        set.watch([U256::from(1)]);
+
+        let (watched_txid, watched) = operation(1, 0);
+        let (other_txid, other) = operation(2, 1);
+        set.enqueue(watched, 1);
+        set.enqueue(other, 1);
+        set.validate().await.unwrap();
+
+        assert_eq!(set.position_of(&watched_txid), Some((0, 0)));
+        assert_eq!(set.position_of(&other_txid), Some((0, 1)));
+        assert!(set.operation(&watched_txid).is_some());
+        assert!(set.operation(&other_txid).is_none());
+    }
+}
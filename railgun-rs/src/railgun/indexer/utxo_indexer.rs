@@ -5,27 +5,34 @@ use std::{
 };
 
 use futures::StreamExt;
+use rand::Rng;
 use ruint::aliases::U256;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
-    abis::railgun::RailgunSmartWallet,
+    abis::{self, railgun::RailgunSmartWallet},
     caip::AssetId,
     crypto::poseidon::poseidon_hash,
     railgun::{
         address::RailgunAddress,
         indexer::{
+            backup::BackupError,
             indexed_account::IndexedAccount,
             syncer::{LegacyCommitment, NoteSyncer, SyncEvent},
         },
         merkle_tree::{
-            MerkleTreeState, MerkleTreeVerifier, TOTAL_LEAVES, UtxoLeafHash, UtxoMerkleTree,
-            VerificationError,
+            MerkleRoot, MerkleTreeState, MerkleTreeVerifier, TOTAL_LEAVES, UtxoLeafHash,
+            UtxoMerkleTree, VerificationError, railgun_merkle_tree_zero,
         },
-        note::utxo::{NoteError, UtxoNote},
-        signer::Signer,
+        note::{
+            IncludedNote, Note,
+            operation::Operation,
+            utxo::{NoteError, UtxoNote},
+        },
+        signer::{Signer, WatchOnlyAccount},
+        transaction::ProvedTransaction,
     },
 };
 
@@ -44,6 +51,10 @@ pub struct UtxoIndexer {
 
     accounts: Vec<IndexedAccount>,
     matched_events: Vec<SyncEvent>,
+
+    /// Tree states from before any pending outputs were inserted. Restored on
+    /// the next sync.
+    pending_trees: Option<BTreeMap<u32, MerkleTreeState>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -75,6 +86,7 @@ impl UtxoIndexer {
             utxo_verifier,
             accounts: vec![],
             matched_events: vec![],
+            pending_trees: None,
         }
     }
 
@@ -98,6 +110,7 @@ impl UtxoIndexer {
             utxo_verifier,
             accounts: vec![],
             matched_events: state.matched_events,
+            pending_trees: None,
         }
     }
 
@@ -119,6 +132,11 @@ impl UtxoIndexer {
         self.synced_block
     }
 
+    /// Returns the current root of the given UTXO tree, if it exists.
+    pub fn utxo_root(&self, tree_number: u32) -> Option<MerkleRoot> {
+        self.utxo_trees.get(&tree_number).map(|t| t.root())
+    }
+
     /// Adds an account to the indexer. The indexer will track the balance and
     /// transactions for this account as it syncs.
     pub fn register(&mut self, signer: Arc<dyn Signer>) {
@@ -133,6 +151,66 @@ impl UtxoIndexer {
         }
     }
 
+    /// Adds a watch-only account to the indexer. Its balance and transactions
+    /// are tracked as with `register`, but transactions can't be built from it.
+    pub fn register_watch_only(&mut self, account: WatchOnlyAccount) {
+        self.register(Arc::new(account));
+    }
+
+    /// Adds an account to the indexer from an encrypted backup, restoring its
+    /// notes without a full rescan. Matched events up to the backup's block are
+    /// then replayed as with `register`.
+    ///
+    /// If the indexer is synced past the block the backup was taken at, the
+    /// indexer is rewound to that block so the next sync refetches the events
+    /// the restored account hasn't seen. Leaf inserts are positional and notes
+    /// are keyed by position, so existing accounts are unaffected by the
+    /// refetch.
+    pub fn register_from_backup(
+        &mut self,
+        signer: Arc<dyn Signer>,
+        backup: &[u8],
+        password: &str,
+    ) -> Result<(), BackupError> {
+        let (account, backup_block) = IndexedAccount::from_backup(signer, backup, password)?;
+        self.accounts.push(account);
+
+        if backup_block < self.synced_block {
+            info!(
+                "Restored backup from block {}, resyncing from there on the next sync",
+                backup_block
+            );
+            //? Refetched events are matched again, so drop them here to avoid
+            //? recording them twice.
+            self.matched_events
+                .retain(|event| event.block_number() <= backup_block);
+            self.synced_block = backup_block;
+        }
+
+        for event in self.matched_events.clone() {
+            if let Err(e) = self.handle_event(&event) {
+                tracing::error!("Error handling event for restored account: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encrypts an account's notes under a password, along with the block the
+    /// indexer is synced to. See `register_from_backup`.
+    pub fn encrypted_backup<R: Rng + ?Sized>(
+        &self,
+        address: RailgunAddress,
+        password: &str,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, BackupError> {
+        self.accounts
+            .iter()
+            .find(|a| a.address() == address)
+            .ok_or(BackupError::UnknownAccount(address))?
+            .encrypted_backup(password, self.synced_block, rng)
+    }
+
     /// Adds an account to the indexer and immediately resync to populate its state.
     ///
     /// Resyncing is necessary to initially populate an account's state. Resyncing
@@ -167,6 +245,78 @@ impl UtxoIndexer {
         notes
     }
 
+    /// Returns a list of unspent notes across all accounts with at least
+    /// `min_confirmations` confirmations, relative to the last synced block.
+    ///
+    /// A note created in the last synced block has one confirmation, so
+    /// `min_confirmations` of 0 or 1 returns every unspent note.
+    pub fn all_unspent_confirmed(&self, min_confirmations: u64) -> Vec<UtxoNote> {
+        let Some(max_block) = (self.synced_block + 1).checked_sub(min_confirmations) else {
+            return vec![];
+        };
+
+        let mut notes = Vec::new();
+        for account in self.accounts.iter() {
+            notes.extend(account.unspent_before(max_block));
+        }
+
+        notes
+    }
+
+    /// Returns the nullifiers of every note held by a registered account, for
+    /// `TxidIndexer::watch_nullifiers`.
+    pub fn nullifiers(&self) -> Vec<U256> {
+        self.accounts
+            .iter()
+            .flat_map(|account| account.nullifiers())
+            .collect()
+    }
+
+    /// Finds the registered account that spent the given nullifiers on a tree,
+    /// returning its signer and the spent notes in nullifier order.
+    pub fn notes_by_nullifiers(
+        &self,
+        tree_number: u32,
+        nullifiers: &[U256],
+    ) -> Option<(Arc<dyn Signer>, Vec<UtxoNote>)> {
+        self.accounts.iter().find_map(|account| {
+            let notes = account.notes_by_nullifiers(tree_number, nullifiers)?;
+            Some((account.signer(), notes))
+        })
+    }
+
+    /// Returns the matched Transact event that inserted the commitment at the
+    /// given position.
+    ///
+    /// Only events where a registered account received or sent a note are
+    /// retained. A single event may cover several operations of a batch.
+    pub fn transact_event(
+        &self,
+        tree_number: u32,
+        position: u32,
+    ) -> Option<&RailgunSmartWallet::Transact> {
+        let position = U256::from(position);
+        self.matched_events.iter().find_map(|event| match event {
+            SyncEvent::Transact(transact, _)
+                if transact.treeNumber == U256::from(tree_number)
+                    && transact.startPosition <= position
+                    && position < transact.startPosition + U256::from(transact.hash.len()) =>
+            {
+                Some(transact)
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns true if the note's commitment matches the leaf at its claimed
+    /// position in the UTXO tree.
+    ///
+    /// Guards against malformed ciphertexts that decrypt to a note whose
+    /// commitment was never inserted on-chain. Such notes can't be spent.
+    pub fn verify_note_inclusion<N: IncludedNote>(&self, note: &N) -> bool {
+        leaf_at(&self.utxo_trees, note) == Some(note.hash())
+    }
+
     /// Returns the balance of a given address by summing the values of all
     /// unspent notes for that address.
     pub fn balance(&self, address: RailgunAddress) -> HashMap<AssetId, u128> {
@@ -179,6 +329,50 @@ impl UtxoIndexer {
         HashMap::new()
     }
 
+    /// Returns the projected balance of a given address after the given
+    /// operations are applied, without modifying the indexer.
+    ///
+    /// Operations whose sender is tracked by the indexer and whose input notes
+    /// are no longer unspent are assumed to be synced already, and are skipped
+    /// so they aren't counted twice.
+    ///
+    /// ```ignore
+    /// let ops = tx.proved_operations.iter().map(|p| &p.operation);
+    /// let projected = indexer.project_balance(address, ops);
+    /// ```
+    pub fn project_balance<'a, N: IncludedNote + 'a>(
+        &self,
+        address: RailgunAddress,
+        operations: impl IntoIterator<Item = &'a Operation<N>>,
+    ) -> HashMap<AssetId, u128> {
+        let mut balances = self.balance(address);
+
+        for operation in operations {
+            let from = operation.from.address();
+            let sender = self.accounts.iter().find(|a| a.address() == from);
+            let pending = match sender {
+                Some(sender) => operation.in_notes().iter().all(|n| sender.is_unspent(n)),
+                None => true,
+            };
+            if !pending {
+                continue;
+            }
+
+            if from == address {
+                let balance = balances.entry(operation.asset).or_insert(0);
+                *balance = balance.saturating_sub(operation.in_value());
+            }
+
+            for note in operation.out_notes.iter().chain(operation.fee_note.iter()) {
+                if note.to == address {
+                    *balances.entry(note.asset()).or_insert(0) += note.value();
+                }
+            }
+        }
+
+        balances
+    }
+
     pub async fn sync(&mut self) -> Result<(), UtxoIndexerError> {
         self.sync_to(u64::MAX).await
     }
@@ -199,18 +393,27 @@ impl UtxoIndexer {
             return Ok(());
         }
 
+        //? Discard pending outputs so on-chain events land at their real positions.
+        self.discard_pending();
+
         // Sync
         let mut stream = syncer
             .sync(from_block, to_block)
             .await
             .map_err(UtxoIndexerError::SyncerError)?;
 
+        //? On a syncer error, return before advancing `synced_block` so the
+        //? next sync retries the range. Leaf inserts are positional, so
+        //? replaying the already-applied events is harmless.
+        let mut matched_events = Vec::new();
         while let Some(event) = stream.next().await {
+            let event = event.map_err(|e| UtxoIndexerError::SyncerError(Box::new(e)))?;
             let matched = self.handle_event(&event)?;
             if matched {
-                self.matched_events.push(event);
+                matched_events.push(event);
             }
         }
+        self.matched_events.extend(matched_events);
 
         // Rebuild
         for tree in self.utxo_trees.values_mut() {
@@ -224,11 +427,115 @@ impl UtxoIndexer {
         Ok(())
     }
 
+    /// Provisionally inserts a broadcast transaction's output commitments and
+    /// marks its inputs as spent, so the account's change and self-transfer
+    /// notes can be spent before the transaction is synced.
+    ///
+    /// Outputs are inserted at estimated positions directly after the last
+    /// known leaf. The next sync that fetches new events discards this pending
+    /// state and replays matched events, so positions are corrected from the
+    /// on-chain events. If the transaction hasn't landed by then, its outputs
+    /// are dropped until it does.
+    pub fn insert_pending_outputs(
+        &mut self,
+        tx: &ProvedTransaction,
+    ) -> Result<(), UtxoIndexerError> {
+        self.insert_pending_transactions(tx.proved_operations.iter().map(|p| &p.transaction))
+    }
+
+    fn insert_pending_transactions<'a>(
+        &mut self,
+        transactions: impl IntoIterator<Item = &'a abis::railgun::Transaction>,
+    ) -> Result<(), UtxoIndexerError> {
+        if self.pending_trees.is_none() {
+            let trees = self
+                .utxo_trees
+                .iter()
+                .map(|(k, v)| (*k, v.state()))
+                .collect();
+            self.pending_trees = Some(trees);
+        }
+
+        //? Pending notes are treated as created in the next block, so they have
+        //? zero confirmations.
+        let block_number = self.synced_block + 1;
+        for transaction in transactions {
+            let ciphertext = transaction.boundParams.commitmentCiphertext.clone();
+
+            //? Unshield commitments have no ciphertext and aren't inserted into the tree.
+            let hash: Vec<_> = transaction
+                .commitments
+                .iter()
+                .take(ciphertext.len())
+                .copied()
+                .collect();
+            let (tree_number, start_position) = self.next_position(hash.len());
+
+            let transact = RailgunSmartWallet::Transact {
+                treeNumber: U256::from(tree_number),
+                startPosition: U256::from(start_position),
+                hash,
+                ciphertext,
+            };
+            let nullified = RailgunSmartWallet::Nullified {
+                treeNumber: transaction.boundParams.treeNumber,
+                nullifier: transaction.nullifiers.clone(),
+            };
+
+            self.handle_transact(&transact, block_number)?;
+            self.handle_nullified(&nullified, block_number);
+        }
+
+        for tree in self.utxo_trees.values_mut() {
+            tree.rebuild();
+        }
+
+        Ok(())
+    }
+
+    /// Returns the tree number and position the contract would insert `count`
+    /// new commitments at.
+    fn next_position(&self, count: usize) -> (u32, usize) {
+        let Some((number, tree)) = self.utxo_trees.last_key_value() else {
+            return (0, 0);
+        };
+
+        let leaves_len = tree.leaves_len();
+        if leaves_len + count > TOTAL_LEAVES {
+            (number + 1, 0)
+        } else {
+            (*number, leaves_len)
+        }
+    }
+
+    /// Restores the trees from before any pending outputs were inserted and
+    /// rolls back the notes pending outputs created or spent.
+    ///
+    /// Pending notes are the only ones recorded after `synced_block`, since
+    /// this runs before any newly synced events are handled.
+    fn discard_pending(&mut self) {
+        let Some(trees) = self.pending_trees.take() else {
+            return;
+        };
+
+        self.utxo_trees = trees
+            .into_iter()
+            .map(|(number, state)| {
+                let tree =
+                    UtxoMerkleTree::from_state(state).with_verifier(self.utxo_verifier.clone());
+                (number, tree)
+            })
+            .collect();
+        for account in self.accounts.iter_mut() {
+            account.rollback(self.synced_block);
+        }
+    }
+
     /// Handles a sync event. Returns true if the event was matched to any account.
     fn handle_event(&mut self, event: &SyncEvent) -> Result<bool, UtxoIndexerError> {
         let matched = match event {
-            SyncEvent::Shield(shield, _) => self.handle_shield(shield)?,
-            SyncEvent::Transact(transact, _) => self.handle_transact(transact)?,
+            SyncEvent::Shield(shield, block) => self.handle_shield(shield, *block)?,
+            SyncEvent::Transact(transact, block) => self.handle_transact(transact, *block)?,
             SyncEvent::Nullified(nullified, ts) => self.handle_nullified(nullified, *ts),
             SyncEvent::Legacy(legacy, _) => self.handle_legacy(legacy),
         };
@@ -240,6 +547,7 @@ impl UtxoIndexer {
     fn handle_shield(
         &mut self,
         event: &RailgunSmartWallet::Shield,
+        block_number: u64,
     ) -> Result<bool, UtxoIndexerError> {
         let leaves: Vec<UtxoLeafHash> = event
             .commitments
@@ -254,26 +562,35 @@ impl UtxoIndexer {
             })
             .collect();
 
+        let tree_number = event.treeNumber.saturating_to();
+        let start_position = event.startPosition.saturating_to();
         insert_utxo_leaves(
             &mut self.utxo_trees,
-            event.treeNumber.saturating_to(),
-            event.startPosition.saturating_to(),
+            tree_number,
+            start_position,
             &leaves,
             self.utxo_verifier.clone(),
         );
 
         let mut matched = false;
         for account in self.accounts.iter_mut() {
-            matched |= account.handle_shield_event(event)?;
+            matched |= account.handle_shield_event(event, block_number)?;
+        }
+        if matched {
+            self.discard_uncommitted_notes(tree_number, start_position, leaves.len());
         }
 
         Ok(matched)
     }
 
-    /// Handles a transact event. Returns true if the event was matched to any account.
+    /// Handles a transact event. Returns true if the event was matched to any
+    /// account, either as a receiver or as the sender.
+    ///
+    /// Sent events are kept so POIs can be proved for spends with no change.
     fn handle_transact(
         &mut self,
         event: &RailgunSmartWallet::Transact,
+        block_number: u64,
     ) -> Result<bool, UtxoIndexerError> {
         let leaves: Vec<UtxoLeafHash> = event
             .hash
@@ -281,20 +598,29 @@ impl UtxoIndexer {
             .map(|h| U256::from_be_bytes(**h).into())
             .collect();
 
+        let tree_number = event.treeNumber.saturating_to();
+        let start_position = event.startPosition.saturating_to();
         insert_utxo_leaves(
             &mut self.utxo_trees,
-            event.treeNumber.saturating_to(),
-            event.startPosition.saturating_to(),
+            tree_number,
+            start_position,
             &leaves,
             self.utxo_verifier.clone(),
         );
 
         let mut matched = false;
         for account in self.accounts.iter_mut() {
-            matched |= account.handle_transact_event(event)?;
+            matched |= account.handle_transact_event(event, block_number)?;
+        }
+        if matched {
+            self.discard_uncommitted_notes(tree_number, start_position, leaves.len());
+            return Ok(true);
         }
 
-        Ok(matched)
+        Ok(self
+            .accounts
+            .iter()
+            .any(|account| account.sent_transact_event(event)))
     }
 
     /// Handles a nullified event. Returns true if the event was matched to any account.
@@ -320,6 +646,29 @@ impl UtxoIndexer {
         false
     }
 
+    /// Drops unspent notes at an event's positions whose commitment doesn't
+    /// match the leaf, so they aren't counted toward spendable balance.
+    ///
+    /// Only the event's positions are checked, since notes elsewhere were
+    /// checked when their own event was handled.
+    fn discard_uncommitted_notes(&mut self, tree_number: u32, start_position: usize, count: usize) {
+        let trees = &self.utxo_trees;
+        let positions = leaf_positions(tree_number, start_position, count);
+        for account in self.accounts.iter_mut() {
+            let removed = account.retain_unspent_at(positions.clone(), |note| {
+                leaf_at(trees, note).is_none_or(|leaf| leaf == note.hash())
+            });
+
+            for note in removed {
+                warn!(
+                    "Discarding note at tree {}, leaf {}: commitment not in tree",
+                    note.tree_number(),
+                    note.leaf_index()
+                );
+            }
+        }
+    }
+
     async fn verify(&self) -> Result<(), VerificationError> {
         for tree in self.utxo_trees.values() {
             tree.verify().await?;
@@ -328,6 +677,34 @@ impl UtxoIndexer {
     }
 }
 
+/// Returns the UTXO tree leaf at the note's position, if it has been synced.
+///
+/// Gaps before an inserted leaf are filled with the zero value, so a zero leaf
+/// is treated as not synced.
+fn leaf_at<N: IncludedNote>(
+    trees: &BTreeMap<u32, UtxoMerkleTree>,
+    note: &N,
+) -> Option<UtxoLeafHash> {
+    trees
+        .get(&note.tree_number())?
+        .leaf(note.leaf_index() as usize)
+        .filter(|leaf| U256::from(*leaf) != railgun_merkle_tree_zero())
+}
+
+/// Returns the `(tree_number, leaf_index)` positions of `count` leaves
+/// inserted at `start_position`, crossing into the next tree as
+/// `insert_utxo_leaves` does.
+fn leaf_positions(
+    tree_number: u32,
+    start_position: usize,
+    count: usize,
+) -> impl Iterator<Item = (u32, u32)> + Clone {
+    (start_position..start_position + count).map(move |position| {
+        let tree = tree_number + (position / TOTAL_LEAVES) as u32;
+        (tree, (position % TOTAL_LEAVES) as u32)
+    })
+}
+
 /// Inserts UTXO leaves into the appropriate tree, handling tree boundaries.
 ///
 /// If the leaves cross a tree boundary, it will fill the first tree, then
@@ -357,3 +734,539 @@ fn insert_utxo_leaves(
         position = 0;
     }
 }
+
+/// Creates an indexer tracking `signer`, synced to block 1 with a note of
+/// `asset` for each of `values` at consecutive positions in tree 0.
+#[cfg(test)]
+pub fn test_indexer(signer: Arc<dyn Signer>, asset: AssetId, values: &[u128]) -> UtxoIndexer {
+    use alloy::primitives::B256;
+    use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+
+    use crate::railgun::note::{EncryptableNote, transfer::TransferNote};
+
+    let mut rng = ChaChaRng::seed_from_u64(0);
+    let (hash, ciphertext) = values
+        .iter()
+        .map(|value| {
+            let note = TransferNote::with_fixed_random(signer.address(), asset, *value);
+            let hash = B256::from(U256::from(note.hash()));
+            (hash, note.encrypt(&mut rng).unwrap())
+        })
+        .unzip();
+    let event = SyncEvent::Transact(
+        RailgunSmartWallet::Transact {
+            treeNumber: U256::ZERO,
+            startPosition: U256::ZERO,
+            hash,
+            ciphertext,
+        },
+        1,
+    );
+
+    test_indexer_with_events(signer, vec![event])
+}
+
+/// Creates an indexer tracking `signer`, synced over the given events.
+#[cfg(test)]
+pub fn test_indexer_with_events(signer: Arc<dyn Signer>, events: Vec<SyncEvent>) -> UtxoIndexer {
+    use crate::railgun::indexer::syncer::ChainedSyncer;
+
+    let syncer = Arc::new(ChainedSyncer::new(vec![]));
+    let mut indexer = UtxoIndexer::new(syncer, Arc::new(AcceptAllVerifier));
+    indexer.register(signer);
+    for event in events {
+        indexer.synced_block = indexer.synced_block.max(event.block_number());
+        if indexer.handle_event(&event).unwrap() {
+            indexer.matched_events.push(event);
+        }
+    }
+    for tree in indexer.utxo_trees.values_mut() {
+        tree.rebuild();
+    }
+    indexer
+}
+
+#[cfg(test)]
+struct AcceptAllVerifier;
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl MerkleTreeVerifier for AcceptAllVerifier {
+    async fn verify_root(
+        &self,
+        _tree_number: u32,
+        _tree_index: u64,
+        _root: MerkleRoot,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use alloy::primitives::{Address, B256};
+    use futures::stream;
+    use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+    use tracing_test::traced_test;
+
+    use super::*;
+    use crate::{
+        abis::railgun::{
+            BoundParams, CommitmentCiphertext, CommitmentPreimage, G1Point, G2Point, SnarkProof,
+            UnshieldType,
+        },
+        crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+        railgun::{
+            indexer::syncer::{BoxedSyncStream, SyncError},
+            note::{EncryptableNote, transfer::TransferNote, utxo::test_note},
+            signer::{PrivateKeySigner, ViewingKeyProvider, test_signer},
+        },
+    };
+
+    /// Serves events pushed by the test, up to the latest pushed block.
+    #[derive(Default)]
+    struct MockSyncer {
+        events: Mutex<Vec<SyncEvent>>,
+    }
+
+    impl MockSyncer {
+        fn push(&self, event: SyncEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl NoteSyncer for MockSyncer {
+        async fn latest_block(&self) -> Result<u64, Box<dyn std::error::Error>> {
+            let events = self.events.lock().unwrap();
+            Ok(events.iter().map(|e| e.block_number()).max().unwrap_or(0))
+        }
+
+        async fn sync(
+            &self,
+            from_block: u64,
+            to_block: u64,
+        ) -> Result<BoxedSyncStream<'_>, Box<dyn std::error::Error>> {
+            let events: Vec<Result<SyncEvent, SyncError>> = self
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|e| (from_block..=to_block).contains(&e.block_number()))
+                .cloned()
+                .map(Ok)
+                .collect();
+            Ok(Box::pin(stream::iter(events)))
+        }
+    }
+
+    fn indexer() -> (UtxoIndexer, Arc<MockSyncer>) {
+        indexer_with(Arc::new(MockSyncer::default()))
+    }
+
+    fn indexer_with(syncer: Arc<MockSyncer>) -> (UtxoIndexer, Arc<MockSyncer>) {
+        let indexer = UtxoIndexer::new(syncer.clone(), Arc::new(AcceptAllVerifier));
+        (indexer, syncer)
+    }
+
+    fn asset() -> AssetId {
+        AssetId::Erc20(Address::repeat_byte(1))
+    }
+
+    /// Creates an output commitment and ciphertext sending `value` to `to`.
+    fn output(to: RailgunAddress, value: u128) -> (B256, CommitmentCiphertext) {
+        let mut rng = ChaChaRng::seed_from_u64(value as u64);
+        let note = TransferNote::with_fixed_random(to, asset(), value);
+        let hash = B256::from(U256::from(note.hash()));
+        (hash, note.encrypt(&mut rng).unwrap())
+    }
+
+    fn transact_event(
+        start_position: u32,
+        outputs: Vec<(B256, CommitmentCiphertext)>,
+        block_number: u64,
+    ) -> SyncEvent {
+        let (hash, ciphertext) = outputs.into_iter().unzip();
+        let transact = RailgunSmartWallet::Transact {
+            treeNumber: U256::ZERO,
+            startPosition: U256::from(start_position),
+            hash,
+            ciphertext,
+        };
+        SyncEvent::Transact(transact, block_number)
+    }
+
+    fn nullified_event(nullifiers: &[U256], block_number: u64) -> SyncEvent {
+        let nullified = RailgunSmartWallet::Nullified {
+            treeNumber: 0,
+            nullifier: nullifiers.iter().map(|n| B256::from(*n)).collect(),
+        };
+        SyncEvent::Nullified(nullified, block_number)
+    }
+
+    /// Builds an unproved transaction spending `nullifiers` into `outputs`.
+    fn pending_transaction(
+        nullifiers: &[U256],
+        outputs: Vec<(B256, CommitmentCiphertext)>,
+    ) -> abis::railgun::Transaction {
+        let point = || G1Point {
+            x: U256::ZERO,
+            y: U256::ZERO,
+        };
+        let (commitments, ciphertext) = outputs.into_iter().unzip();
+
+        abis::railgun::Transaction {
+            proof: SnarkProof {
+                a: point(),
+                b: G2Point {
+                    x: [U256::ZERO; 2],
+                    y: [U256::ZERO; 2],
+                },
+                c: point(),
+            },
+            merkleRoot: B256::ZERO,
+            nullifiers: nullifiers.iter().map(|n| B256::from(*n)).collect(),
+            commitments,
+            boundParams: BoundParams {
+                treeNumber: 0,
+                minGasPrice: Default::default(),
+                unshield: UnshieldType::NONE,
+                chainID: 1,
+                adaptContract: Address::ZERO,
+                adaptParams: B256::ZERO,
+                commitmentCiphertext: ciphertext,
+            },
+            unshieldPreimage: CommitmentPreimage::default(),
+        }
+    }
+
+    /// Syncs a signer with a single 100 value note at position 0, returning the
+    /// note's nullifier.
+    async fn funded_indexer(signer: Arc<PrivateKeySigner>) -> (UtxoIndexer, Arc<MockSyncer>, U256) {
+        let (mut indexer, syncer) = indexer();
+        indexer.register(signer.clone());
+        syncer.push(transact_event(0, vec![output(signer.address(), 100)], 1));
+        indexer.sync().await.unwrap();
+
+        let note = &indexer.unspent(signer.address())[0];
+        let nullifier = note.nullifier(U256::from(note.leaf_index()));
+        (indexer, syncer, nullifier)
+    }
+
+    fn values(indexer: &UtxoIndexer, address: RailgunAddress) -> Vec<(u32, u128)> {
+        let mut values: Vec<_> = indexer
+            .unspent(address)
+            .iter()
+            .map(|n| (n.leaf_index(), n.value()))
+            .collect();
+        values.sort();
+        values
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_sync_keeps_sent_transact_events() {
+        let signer = test_signer();
+        let receiver = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([5u8; 32]),
+            ViewingKey::from_bytes([6u8; 32]),
+            1,
+        );
+        let (mut indexer, syncer, _) = funded_indexer(signer.clone()).await;
+
+        //? Sends the whole note to the receiver, so none of the outputs belong
+        //? to the signer and the event is only matched as the sender.
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let sent = TransferNote::new(
+            signer.viewing_key(),
+            receiver.address(),
+            asset(),
+            100,
+            [4u8; 16],
+            "",
+        );
+        let sent_hash = B256::from(U256::from(sent.hash()));
+        syncer.push(transact_event(
+            1,
+            vec![(sent_hash, sent.encrypt(&mut rng).unwrap())],
+            2,
+        ));
+
+        //? Unrelated to the signer on either side
+        let unrelated = TransferNote::new(
+            receiver.viewing_key(),
+            receiver.address(),
+            asset(),
+            50,
+            [5u8; 16],
+            "",
+        );
+        let unrelated_hash = B256::from(U256::from(unrelated.hash()));
+        syncer.push(transact_event(
+            2,
+            vec![(unrelated_hash, unrelated.encrypt(&mut rng).unwrap())],
+            3,
+        ));
+        indexer.sync().await.unwrap();
+
+        assert_eq!(values(&indexer, signer.address()), vec![(0, 100)]);
+        assert!(indexer.transact_event(0, 1).is_some());
+        assert!(indexer.transact_event(0, 2).is_none());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_pending_outputs_commit() {
+        let signer = test_signer();
+        let address = signer.address();
+        let (mut indexer, syncer, nullifier) = funded_indexer(signer).await;
+
+        let change = output(address, 60);
+        indexer
+            .insert_pending_transactions([&pending_transaction(&[nullifier], vec![change.clone()])])
+            .unwrap();
+        assert_eq!(values(&indexer, address), vec![(1, 60)]);
+        //? Pending notes have zero confirmations
+        assert!(indexer.all_unspent_confirmed(1).is_empty());
+
+        // The transaction lands at the estimated position
+        syncer.push(transact_event(1, vec![change], 2));
+        syncer.push(nullified_event(&[nullifier], 2));
+        indexer.sync().await.unwrap();
+
+        assert_eq!(values(&indexer, address), vec![(1, 60)]);
+        assert_eq!(indexer.synced_block, 2);
+        assert!(indexer.pending_trees.is_none());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_pending_outputs_discard() {
+        let signer = test_signer();
+        let other = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let address = signer.address();
+        let (mut indexer, syncer, nullifier) = funded_indexer(signer).await;
+        let root = indexer.utxo_root(0);
+
+        let change = output(address, 60);
+        indexer
+            .insert_pending_transactions([&pending_transaction(&[nullifier], vec![change])])
+            .unwrap();
+        assert_ne!(indexer.utxo_root(0), root);
+
+        // An unrelated transaction takes the estimated position first
+        syncer.push(transact_event(1, vec![output(other.address(), 5)], 2));
+        indexer.sync().await.unwrap();
+
+        assert_eq!(values(&indexer, address), vec![(0, 100)]);
+        assert_eq!(
+            indexer.utxo_trees[&0].leaf(1),
+            Some(U256::from_be_bytes(output(other.address(), 5).0.0).into())
+        );
+        assert!(indexer.pending_trees.is_none());
+    }
+
+    #[test]
+    fn test_next_position() {
+        let (mut indexer, _) = indexer();
+        assert_eq!(indexer.next_position(2), (0, 0));
+
+        let verifier: Arc<dyn MerkleTreeVerifier> = Arc::new(AcceptAllVerifier);
+        let leaf: UtxoLeafHash = U256::from(1).into();
+        insert_utxo_leaves(&mut indexer.utxo_trees, 0, 0, &[leaf; 3], verifier.clone());
+        assert_eq!(indexer.next_position(2), (0, 3));
+
+        // Outputs are never split across trees
+        insert_utxo_leaves(
+            &mut indexer.utxo_trees,
+            0,
+            TOTAL_LEAVES - 2,
+            &[leaf],
+            verifier,
+        );
+        assert_eq!(indexer.next_position(1), (0, TOTAL_LEAVES - 1));
+        assert_eq!(indexer.next_position(2), (1, 0));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_discard_uncommitted_notes() {
+        let signer = test_signer();
+        let address = signer.address();
+        let (mut indexer, syncer) = indexer();
+        indexer.register(signer);
+
+        // The second event's commitment doesn't match its ciphertext
+        let (_, ciphertext) = output(address, 50);
+        syncer.push(transact_event(0, vec![output(address, 100)], 1));
+        syncer.push(transact_event(
+            1,
+            vec![(B256::from(U256::from(7)), ciphertext)],
+            2,
+        ));
+        indexer.sync().await.unwrap();
+
+        assert_eq!(values(&indexer, address), vec![(0, 100)]);
+    }
+
+    #[test]
+    fn test_leaf_at_zero_gap() {
+        let (mut indexer, _) = indexer();
+        let note = test_note();
+        let leaf: UtxoLeafHash = U256::from(1).into();
+
+        //? Position 0 is zero-filled when position 1 is inserted
+        insert_utxo_leaves(
+            &mut indexer.utxo_trees,
+            note.tree_number(),
+            note.leaf_index() as usize + 1,
+            &[leaf],
+            Arc::new(AcceptAllVerifier),
+        );
+        assert_eq!(leaf_at(&indexer.utxo_trees, &note), None);
+
+        insert_utxo_leaves(
+            &mut indexer.utxo_trees,
+            note.tree_number(),
+            note.leaf_index() as usize,
+            &[leaf],
+            Arc::new(AcceptAllVerifier),
+        );
+        assert_eq!(leaf_at(&indexer.utxo_trees, &note), Some(leaf));
+    }
+
+    #[test]
+    fn test_leaf_positions() {
+        assert_eq!(
+            leaf_positions(0, TOTAL_LEAVES - 1, 2).collect::<Vec<_>>(),
+            vec![(0, TOTAL_LEAVES as u32 - 1), (1, 0)]
+        );
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_restore_spend_resync() {
+        let signer = test_signer();
+        let address = signer.address();
+        let (indexer, syncer, nullifier) = funded_indexer(signer.clone()).await;
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let backup = indexer
+            .encrypted_backup(address, "password", &mut rng)
+            .unwrap();
+
+        // A note received after the backup, synced by an indexer that doesn't
+        // track the account yet
+        syncer.push(transact_event(1, vec![output(address, 30)], 2));
+        let (mut restored, _) = indexer_with(syncer.clone());
+        restored.sync().await.unwrap();
+        restored
+            .register_from_backup(signer, &backup, "password")
+            .unwrap();
+        assert_eq!(restored.synced_block, 1);
+        assert_eq!(values(&restored, address), vec![(0, 100)]);
+
+        // Spend the restored note before resyncing
+        let change = output(address, 60);
+        restored
+            .insert_pending_transactions([&pending_transaction(&[nullifier], vec![change.clone()])])
+            .unwrap();
+        assert_eq!(values(&restored, address), vec![(2, 60)]);
+
+        syncer.push(transact_event(2, vec![change], 3));
+        syncer.push(nullified_event(&[nullifier], 3));
+        restored.sync().await.unwrap();
+
+        assert_eq!(restored.synced_block, 3);
+        assert_eq!(values(&restored, address), vec![(1, 30), (2, 60)]);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_project_balance_empty() {
+        let signer = test_signer();
+        let address = signer.address();
+        let (indexer, _, _) = funded_indexer(signer).await;
+
+        let operations: Vec<Operation<UtxoNote>> = vec![];
+        let projected = indexer.project_balance(address, &operations);
+        assert_eq!(projected, indexer.balance(address));
+        assert_eq!(projected[&asset()], 100);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_project_balance_pending_outgoing() {
+        let signer = test_signer();
+        let other = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let address = signer.address();
+        let (mut indexer, syncer, nullifier) = funded_indexer(signer.clone()).await;
+
+        let operation = Operation::new(
+            0,
+            signer,
+            asset(),
+            indexer.unspent(address),
+            vec![
+                TransferNote::with_fixed_random(other.address(), asset(), 40),
+                TransferNote::with_fixed_random(address, asset(), 60),
+            ],
+            None,
+            None,
+        );
+        let operations = [operation];
+
+        let projected = indexer.project_balance(address, &operations);
+        assert_eq!(projected[&asset()], 60);
+        let projected = indexer.project_balance(other.address(), &operations);
+        assert_eq!(projected[&asset()], 40);
+
+        // Once synced, the operation isn't counted twice
+        syncer.push(transact_event(
+            1,
+            vec![output(other.address(), 40), output(address, 60)],
+            2,
+        ));
+        syncer.push(nullified_event(&[nullifier], 2));
+        indexer.sync().await.unwrap();
+
+        let projected = indexer.project_balance(address, &operations);
+        assert_eq!(projected[&asset()], 60);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_project_balance_pending_incoming() {
+        let signer = test_signer();
+        let other = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let address = signer.address();
+        let (indexer, _, _) = funded_indexer(signer).await;
+
+        //? The sender isn't tracked, so the operation is always pending
+        let operation: Operation<UtxoNote> = Operation::new(
+            0,
+            other,
+            asset(),
+            vec![],
+            vec![TransferNote::with_fixed_random(address, asset(), 25)],
+            None,
+            None,
+        );
+
+        let projected = indexer.project_balance(address, [&operation]);
+        assert_eq!(projected[&asset()], 125);
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt::Display;
 use alloy::primitives::FixedBytes;
 use ruint::aliases::U256;
 use serde::{Deserialize, Serialize, Serializer};
-use serde_with::serde_as;
+use serde_with::{DeserializeAs, serde_as};
 
 use crate::{crypto::poseidon::poseidon_hash, padded_u256::PaddedU256};
 
@@ -120,8 +120,7 @@ impl<'de> Deserialize<'de> for MerkleRoot {
     where
         D: serde::Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        let value = U256::from_str_radix(&s, 16).map_err(serde::de::Error::custom)?;
+        let value = PaddedU256::deserialize_as(deserializer)?;
         Ok(MerkleRoot(value))
     }
 }
@@ -135,3 +134,72 @@ impl Display for MerkleRoot {
 fn hash_left_right(left: U256, right: U256) -> U256 {
     poseidon_hash(&[left, right]).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `ppoi_merkle_proofs` response in the aggregator's wire format.
+    const MERKLE_PROOFS_FIXTURE: &str =
+        include_str!("../../../tests/fixtures/poi_merkle_proofs.json");
+
+    #[derive(Deserialize)]
+    struct JsonRpcResult<T> {
+        result: T,
+    }
+
+    #[test]
+    fn test_deserialize_node_merkle_proofs() {
+        let raw: serde_json::Value = serde_json::from_str(MERKLE_PROOFS_FIXTURE).unwrap();
+        let proofs: JsonRpcResult<Vec<MerkleProof>> =
+            serde_json::from_str(MERKLE_PROOFS_FIXTURE).unwrap();
+        let proofs = proofs.result;
+
+        assert_eq!(proofs.len(), 2);
+        assert_eq!(
+            proofs[0].element,
+            U256::from_str_radix(
+                "0c9a455b4dae168d93f5c24cf91fb193f578206180576586d170c0b678b7bc10",
+                16
+            )
+            .unwrap()
+        );
+        assert_eq!(proofs[0].elements.len(), 16);
+        assert_eq!(proofs[0].indices, U256::from(5));
+        assert_eq!(proofs[1].indices, U256::from(1042));
+        assert_eq!(
+            proofs[1].root.to_string(),
+            "2fb51c04836db78d1d21a6b6e1a6c6f407dca6d9dbc02ee7ad690ecbb0471258"
+        );
+
+        //? Re-serializing must reproduce the node's layout exactly
+        let reserialized = serde_json::to_value(&proofs).unwrap();
+        assert_eq!(reserialized, raw["result"]);
+    }
+
+    #[test]
+    fn test_deserialize_unpadded() {
+        let json = serde_json::json!({
+            "leaf": "0x1",
+            "elements": ["2", "0x03"],
+            "indices": "0",
+            "root": "0x04",
+        });
+
+        let proof: MerkleProof = serde_json::from_value(json).unwrap();
+        assert_eq!(proof.element, U256::from(1));
+        assert_eq!(proof.elements, vec![U256::from(2), U256::from(3)]);
+        assert_eq!(proof.indices, U256::ZERO);
+        assert_eq!(proof.root, MerkleRoot::from(U256::from(4)));
+    }
+
+    #[test]
+    fn test_serde_roundtrip_verifies() {
+        let proof = MerkleProof::new_deterministic(U256::from(12345));
+        assert!(proof.verify());
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let proof: MerkleProof = serde_json::from_str(&json).unwrap();
+        assert!(proof.verify());
+    }
+}
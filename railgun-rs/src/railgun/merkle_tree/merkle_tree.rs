@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 
 use alloy::primitives::utils::keccak256_cached;
 use ruint::aliases::U256;
@@ -36,6 +36,16 @@ pub enum MerkleTreeError {
     ElementNotFound(U256),
     #[error("Invalid proof")]
     InvalidProof,
+    #[error("Expected {expected} tree levels, found {actual}")]
+    InvalidLevelCount { expected: usize, actual: usize },
+    #[error("Level {level} has {actual} nodes, expected {expected}")]
+    InvalidLevelWidth {
+        level: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Inconsistent node at level {level}, index {index}")]
+    InconsistentNode { level: usize, index: usize },
 }
 
 pub const TREE_DEPTH: usize = 16;
@@ -85,6 +95,11 @@ impl MerkleTree {
         self.tree[0].len()
     }
 
+    /// Returns the leaf at `index`, if it has been inserted.
+    pub fn leaf(&self, index: usize) -> Option<U256> {
+        self.tree[0].get(index).copied()
+    }
+
     pub fn state(&self) -> MerkleTreeState {
         self.clone().into_state()
     }
@@ -98,6 +113,14 @@ impl MerkleTree {
     }
 
     pub fn generate_proof(&self, element: U256) -> Result<MerkleProof, MerkleTreeError> {
+        let mut proofs = self.generate_proofs(&[element])?;
+        Ok(proofs.remove(0))
+    }
+
+    /// Generates proofs for several leaves at once, locating all of them in a
+    /// single scan over the leaf level. Proofs are returned in the same order
+    /// as `elements`.
+    pub fn generate_proofs(&self, elements: &[U256]) -> Result<Vec<MerkleProof>, MerkleTreeError> {
         debug_assert!(
             self.dirty_parents.is_empty(),
             "Merkle tree has dirty parents, root may be outdated"
@@ -107,11 +130,38 @@ impl MerkleTree {
             warn!("Merkle tree has dirty parents, root may be outdated");
         }
 
-        let initial_index = self.tree[0]
+        let mut positions: HashMap<U256, Option<usize>> =
+            elements.iter().map(|element| (*element, None)).collect();
+        let mut remaining = positions.len();
+
+        for (index, leaf) in self.tree[0].iter().enumerate() {
+            if remaining == 0 {
+                break;
+            }
+
+            //? Match `position` semantics: the first occurrence wins
+            if let Some(slot @ None) = positions.get_mut(leaf) {
+                *slot = Some(index);
+                remaining -= 1;
+            }
+        }
+
+        let root = self.root();
+        elements
             .iter()
-            .position(|val| *val == element)
-            .ok_or(MerkleTreeError::ElementNotFound(element))?;
+            .map(|&element| {
+                let index = positions[&element].ok_or(MerkleTreeError::ElementNotFound(element))?;
+                let proof = self.proof_at(element, index, root);
+                if !proof.verify() {
+                    return Err(MerkleTreeError::InvalidProof);
+                }
+
+                Ok(proof)
+            })
+            .collect()
+    }
 
+    fn proof_at(&self, element: U256, initial_index: usize, root: MerkleRoot) -> MerkleProof {
         let mut elements = Vec::with_capacity(self.depth);
         let mut index = initial_index;
 
@@ -128,12 +178,60 @@ impl MerkleTree {
             index /= 2;
         }
 
-        let proof = MerkleProof::new(element, elements, U256::from(initial_index), self.root());
-        if !proof.verify() {
-            return Err(MerkleTreeError::InvalidProof);
+        MerkleProof::new(element, elements, U256::from(initial_index), root)
+    }
+
+    /// Recomputes every parent node from the leaves and compares it against the
+    /// stored tree, returning the first inconsistency found.
+    ///
+    /// Trees loaded from untrusted state should be checked before relying on
+    /// their root. Trees with pending (un-rebuilt) inserts will fail this check.
+    pub fn verify_integrity(&self) -> Result<(), MerkleTreeError> {
+        if self.tree.len() != self.depth + 1 {
+            return Err(MerkleTreeError::InvalidLevelCount {
+                expected: self.depth + 1,
+                actual: self.tree.len(),
+            });
+        }
+
+        for level in 0..self.depth {
+            let children = &self.tree[level];
+            let parents = &self.tree[level + 1];
+
+            //? The root level always holds a single node, even for empty trees
+            let expected_width = if level + 1 == self.depth {
+                1
+            } else {
+                children.len().div_ceil(2)
+            };
+            if parents.len() != expected_width {
+                return Err(MerkleTreeError::InvalidLevelWidth {
+                    level: level + 1,
+                    expected: expected_width,
+                    actual: parents.len(),
+                });
+            }
+
+            for (index, parent) in parents.iter().enumerate() {
+                let left = children
+                    .get(index * 2)
+                    .copied()
+                    .unwrap_or(self.zeros[level]);
+                let right = children
+                    .get(index * 2 + 1)
+                    .copied()
+                    .unwrap_or(self.zeros[level]);
+
+                if hash_left_right(left, right) != *parent {
+                    return Err(MerkleTreeError::InconsistentNode {
+                        level: level + 1,
+                        index,
+                    });
+                }
+            }
         }
 
-        Ok(proof)
+        Ok(())
     }
 
     /// Insert one leaf and immediately rebuilds.
@@ -276,6 +374,31 @@ mod tests {
         assert_eq!(tree_leaves_len, leaves.len());
     }
 
+    #[test]
+    #[traced_test]
+    fn test_generate_proofs() {
+        let mut tree = MerkleTree::new(0);
+        let leaves: Vec<U256> = (0..10u64).map(|i| U256::from(i + 1)).collect();
+        tree.insert_leaves_raw(&leaves, 0);
+        tree.rebuild();
+
+        let requested = [leaves[7], leaves[0], leaves[3], leaves[7]];
+        let proofs = tree.generate_proofs(&requested).unwrap();
+        assert_eq!(proofs.len(), requested.len());
+
+        for (leaf, proof) in requested.iter().zip(&proofs) {
+            let expected = tree.generate_proof(*leaf).unwrap();
+            assert_eq!(proof.element, *leaf);
+            assert_eq!(proof.elements, expected.elements);
+            assert_eq!(proof.indices, expected.indices);
+            assert_eq!(proof.root, expected.root);
+        }
+
+        let missing = U256::from(42);
+        let err = tree.generate_proofs(&[leaves[0], missing]).unwrap_err();
+        assert!(matches!(err, MerkleTreeError::ElementNotFound(e) if e == missing));
+    }
+
     #[test]
     #[traced_test]
     fn test_state() {
@@ -289,4 +412,50 @@ mod tests {
 
         assert_eq!(tree.root(), rebuilt_tree.root());
     }
+
+    #[test]
+    #[traced_test]
+    fn test_verify_integrity() {
+        let mut tree = MerkleTree::new(0);
+        tree.verify_integrity().unwrap();
+
+        let leaves: Vec<U256> = (0..10u64).map(|i| U256::from(i + 1)).collect();
+        tree.insert_leaves_raw(&leaves, 0);
+        tree.rebuild();
+        tree.verify_integrity().unwrap();
+
+        // Tampered leaf
+        let mut state = tree.state();
+        state.tree[0][3] = U256::from(42);
+        let err = MerkleTree::from_state(state)
+            .verify_integrity()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MerkleTreeError::InconsistentNode { level: 1, index: 1 }
+        ));
+
+        // Truncated level
+        let mut state = tree.state();
+        state.tree[2].pop();
+        let err = MerkleTree::from_state(state)
+            .verify_integrity()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MerkleTreeError::InvalidLevelWidth {
+                level: 2,
+                expected: 3,
+                actual: 2
+            }
+        ));
+
+        // Missing levels
+        let mut state = tree.state();
+        state.tree.pop();
+        let err = MerkleTree::from_state(state)
+            .verify_integrity()
+            .unwrap_err();
+        assert!(matches!(err, MerkleTreeError::InvalidLevelCount { .. }));
+    }
 }
@@ -50,6 +50,10 @@ impl UtxoMerkleTree {
         self.inner.leaves_len()
     }
 
+    pub fn leaf(&self, index: usize) -> Option<UtxoLeafHash> {
+        self.inner.leaf(index).map(UtxoLeafHash::from)
+    }
+
     pub fn state(&self) -> MerkleTreeState {
         self.inner.state()
     }
@@ -58,10 +62,26 @@ impl UtxoMerkleTree {
         self.inner.into_state()
     }
 
+    /// Checks that the stored tree is consistent with its leaves. See
+    /// `MerkleTree::verify_integrity`.
+    pub fn verify_integrity(&self) -> Result<(), MerkleTreeError> {
+        self.inner.verify_integrity()
+    }
+
     pub fn generate_proof(&self, leaf: UtxoLeafHash) -> Result<MerkleProof, MerkleTreeError> {
         self.inner.generate_proof(leaf.into())
     }
 
+    /// Generates proofs for several leaves in one pass. See
+    /// `MerkleTree::generate_proofs`.
+    pub fn generate_proofs(
+        &self,
+        leaves: &[UtxoLeafHash],
+    ) -> Result<Vec<MerkleProof>, MerkleTreeError> {
+        let u256s: Vec<U256> = leaves.iter().map(|l| (*l).into()).collect();
+        self.inner.generate_proofs(&u256s)
+    }
+
     /// Insert one UTXO leaf and immediately rebuild.
     pub fn insert_leaf(&mut self, leaf: UtxoLeafHash, position: usize) {
         self.inner.insert_leaf(leaf.into(), position);
@@ -23,9 +23,21 @@ pub enum EncryptError {
     Key(#[from] KeyError),
 }
 
+/// Padded memos are padded to a multiple of this many bytes.
+pub const MEMO_PADDED_LENGTH: usize = 128;
+
+/// Marks the end of a padded memo. A lone `0x80` is never valid UTF-8, so
+/// padded memos can't be mistaken for unpadded ones.
+const MEMO_PADDING_MARKER: u8 = 0x80;
+
 /// Encrypts a note into a CommitmentCiphertext
 ///
+/// If `pad_memo` is set, the memo is padded to a multiple of
+/// `MEMO_PADDED_LENGTH` bytes so the ciphertext length doesn't reveal the
+/// memo's length.
+///
 /// TODO: Add details on blind
+#[allow(clippy::too_many_arguments)]
 pub fn encrypt_note<R: Rng + ?Sized>(
     receiver: &RailgunAddress,
     shared_random: &[u8; 16],
@@ -34,6 +46,7 @@ pub fn encrypt_note<R: Rng + ?Sized>(
     memo: &str,
     viewing_key: ViewingKey,
     blind: bool,
+    pad_memo: bool,
     rng: &mut R,
 ) -> Result<CommitmentCiphertext, EncryptError> {
     let output_type = 0;
@@ -47,13 +60,19 @@ pub fn encrypt_note<R: Rng + ?Sized>(
         &concat_arrays(&sender_random, &[0u8; 17]),
     )?;
 
+    let memo = if pad_memo {
+        pad(memo)
+    } else {
+        memo.as_bytes().to_vec()
+    };
+
     let shared_key = viewing_key.derive_shared_key_blinded(blinded_receiver)?;
     let gcm = shared_key.encrypt_gcm(
         &[
             receiver.master_key().as_bytes(),
             &asset.hash().to_be_bytes_vec(),
             &concat_arrays::<16, 16, 32>(shared_random, &value.to_be_bytes()),
-            memo.as_bytes(),
+            &memo,
         ],
         rng,
     )?;
@@ -92,19 +111,52 @@ pub fn encrypt_note<R: Rng + ?Sized>(
     })
 }
 
+/// Decodes a decrypted memo, removing any padding added by `encrypt_note`.
+/// Memos that aren't valid UTF-8 decode as empty.
+pub fn decode_memo(bytes: &[u8]) -> &str {
+    if let Ok(memo) = std::str::from_utf8(bytes) {
+        return memo;
+    }
+
+    match bytes.iter().rposition(|b| *b != 0) {
+        Some(end) if bytes[end] == MEMO_PADDING_MARKER => {
+            std::str::from_utf8(&bytes[..end]).unwrap_or("")
+        }
+        _ => "",
+    }
+}
+
+/// Pads a memo with a marker byte followed by zeros, up to the next multiple
+/// of `MEMO_PADDED_LENGTH`.
+fn pad(memo: &str) -> Vec<u8> {
+    let mut padded = memo.as_bytes().to_vec();
+    padded.push(MEMO_PADDING_MARKER);
+
+    let len = padded.len().div_ceil(MEMO_PADDED_LENGTH) * MEMO_PADDED_LENGTH;
+    padded.resize(len, 0);
+    padded
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use alloy::primitives::address;
+    use alloy::primitives::{Address, U256, address};
     use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
     use tracing_test::traced_test;
 
     use super::*;
     use crate::{
+        abis::railgun::LegacyCommitmentCiphertext,
         crypto::keys::SpendingKey,
         railgun::{
-            note::utxo::{UtxoNote, UtxoType},
+            note::{
+                Note,
+                utxo::{
+                    NOTE_VERSION_LEGACY, NOTE_VERSION_V2, NoteCiphertext, NoteError, UtxoNote,
+                    UtxoType,
+                },
+            },
             signer::{PrivateKeySigner, Signer},
         },
     };
@@ -137,6 +189,7 @@ mod tests {
             memo,
             sender_viewing_key,
             false,
+            false,
             &mut rand,
         )
         .unwrap();
@@ -173,6 +226,7 @@ mod tests {
             memo,
             sender_viewing_key,
             false,
+            false,
             &mut rand,
         )
         .unwrap();
@@ -192,4 +246,246 @@ mod tests {
 
         assert_eq!(expected, decrypted);
     }
+
+    #[test]
+    #[traced_test]
+    fn test_decrypt_versioned() {
+        let mut rand = ChaChaRng::seed_from_u64(0);
+
+        let sender_viewing_key = ViewingKey::from_bytes([2u8; 32]);
+        let signer = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let asset = AssetId::Erc20(address!("0x1234567890123456789012345678901234567890"));
+
+        let encrypted = encrypt_note(
+            &signer.address(),
+            &[5u8; 16],
+            1000,
+            &asset,
+            "",
+            sender_viewing_key,
+            false,
+            false,
+            &mut rand,
+        )
+        .unwrap();
+
+        let ciphertext = NoteCiphertext::V2(&encrypted);
+        assert_eq!(ciphertext.version(), NOTE_VERSION_V2);
+        let decrypted = UtxoNote::decrypt_versioned(signer.clone(), 1, 0, ciphertext).unwrap();
+        assert_eq!(decrypted.asset(), asset);
+        assert_eq!(decrypted.value(), 1000);
+
+        //? The same words in the legacy event shape aren't parsed with the V2 layout
+        let legacy = LegacyCommitmentCiphertext {
+            ciphertext: encrypted.ciphertext.map(|word| U256::from_be_bytes(word.0)),
+            ephemeralKeys: [
+                U256::from_be_bytes(encrypted.blindedSenderViewingKey.0),
+                U256::from_be_bytes(encrypted.blindedReceiverViewingKey.0),
+            ],
+            memo: vec![],
+        };
+        let ciphertext = NoteCiphertext::Legacy(&legacy);
+        assert_eq!(ciphertext.version(), NOTE_VERSION_LEGACY);
+        let result = UtxoNote::decrypt_versioned(signer.clone(), 1, 0, ciphertext);
+        assert!(matches!(
+            result,
+            Err(NoteError::UnsupportedVersion(NOTE_VERSION_LEGACY))
+        ));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_decrypt_hashed_token() {
+        let mut rand = ChaChaRng::seed_from_u64(0);
+        let sender_viewing_key = ViewingKey::from_bytes([2u8; 32]);
+        let signer = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let asset = AssetId::Erc1155(
+            address!("0x1234567890123456789012345678901234567890"),
+            U256::from(7),
+        );
+
+        let encrypted = encrypt_note(
+            &signer.address(),
+            &[5u8; 16],
+            1000,
+            &asset,
+            "",
+            sender_viewing_key,
+            false,
+            false,
+            &mut rand,
+        )
+        .unwrap();
+
+        //? Only the token hash is encrypted, so the note is tracked under its
+        //? hash with the same commitment as the original asset.
+        let decrypted = UtxoNote::decrypt(signer.clone(), 1, 0, &encrypted).unwrap();
+        let expected = UtxoNote::new(1, 0, signer, asset, 1000, [5u8; 16], "", UtxoType::Transact);
+        assert_eq!(decrypted.asset(), AssetId::Unknown(asset.hash()));
+        assert_eq!(decrypted.value(), 1000);
+        assert_eq!(decrypted.hash(), expected.hash());
+    }
+
+    /// Encrypts a note with the given inputs and asserts that the receiver
+    /// recovers them exactly.
+    fn assert_round_trip<R: Rng>(
+        rng: &mut R,
+        sender_viewing_key: ViewingKey,
+        signer: Arc<PrivateKeySigner>,
+        asset: AssetId,
+        value: u128,
+        memo: &str,
+        blind: bool,
+    ) {
+        let shared_random: [u8; 16] = rng.random();
+        let encrypted = encrypt_note(
+            &signer.address(),
+            &shared_random,
+            value,
+            &asset,
+            memo,
+            sender_viewing_key,
+            blind,
+            false,
+            rng,
+        )
+        .unwrap();
+
+        let decrypted = UtxoNote::decrypt(signer.clone(), 1, 0, &encrypted).unwrap();
+        let expected = UtxoNote::new(
+            1,
+            0,
+            signer,
+            asset,
+            value,
+            shared_random,
+            memo,
+            UtxoType::Transact,
+        );
+
+        assert_eq!(decrypted, expected);
+        assert_eq!(decrypted.asset(), asset);
+        assert_eq!(decrypted.value(), value);
+        assert_eq!(decrypted.memo(), memo);
+    }
+
+    fn random_memo<R: Rng>(rng: &mut R) -> String {
+        const CHARS: &[char] = &['a', 'Z', '0', ' ', '\n', 'é', 'ß', '漢', '字', '🚀', '\0'];
+
+        let len = rng.random_range(0..128);
+        (0..len)
+            .map(|_| CHARS[rng.random_range(0..CHARS.len())])
+            .collect()
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_randomized() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+
+        for _ in 0..64 {
+            let sender_viewing_key: ViewingKey = rng.random();
+            let signer = PrivateKeySigner::new_evm(rng.random(), rng.random(), 1);
+            let asset = AssetId::Erc20(Address::from(rng.random::<[u8; 20]>()));
+            let value: u128 = rng.random();
+            let memo = random_memo(&mut rng);
+            let blind: bool = rng.random();
+
+            assert_round_trip(
+                &mut rng,
+                sender_viewing_key,
+                signer,
+                asset,
+                value,
+                &memo,
+                blind,
+            );
+        }
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_edge_cases() {
+        let mut rng = ChaChaRng::seed_from_u64(1);
+        let sender_viewing_key = ViewingKey::from_bytes([2u8; 32]);
+        let signer = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+
+        let long_memo: &str = &"x".repeat(1024);
+        let cases: &[(AssetId, u128, &str)] = &[
+            (AssetId::Erc20(Address::ZERO), 0, ""),
+            (AssetId::Erc20(Address::repeat_byte(0xff)), u128::MAX, ""),
+            (AssetId::Erc20(Address::repeat_byte(1)), 1, "héllo wörld 🚀"),
+            (AssetId::Erc20(Address::repeat_byte(2)), 1, long_memo),
+        ];
+
+        for (asset, value, memo) in cases {
+            for blind in [false, true] {
+                assert_round_trip(
+                    &mut rng,
+                    sender_viewing_key,
+                    signer.clone(),
+                    *asset,
+                    *value,
+                    memo,
+                    blind,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_padded_memo_round_trip() {
+        let mut rng = ChaChaRng::seed_from_u64(2);
+        let sender_viewing_key = ViewingKey::from_bytes([2u8; 32]);
+        let signer = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let asset = AssetId::Erc20(Address::repeat_byte(1));
+
+        let lengths = [0, 1, MEMO_PADDED_LENGTH - 1, MEMO_PADDED_LENGTH, 300];
+        for len in lengths {
+            let memo = "m".repeat(len);
+            let encrypted = encrypt_note(
+                &signer.address(),
+                &[5u8; 16],
+                100,
+                &asset,
+                &memo,
+                sender_viewing_key,
+                false,
+                true,
+                &mut rng,
+            )
+            .unwrap();
+
+            //? The marker byte always fits, so a memo of exactly
+            //? `MEMO_PADDED_LENGTH` bytes spills into a second block.
+            let expected_len = (len + 1).div_ceil(MEMO_PADDED_LENGTH) * MEMO_PADDED_LENGTH;
+            assert_eq!(encrypted.memo.len(), expected_len);
+
+            let decrypted = UtxoNote::decrypt(signer.clone(), 1, 0, &encrypted).unwrap();
+            assert_eq!(decrypted.memo(), memo);
+        }
+    }
+
+    #[test]
+    fn test_decode_memo() {
+        assert_eq!(decode_memo(b""), "");
+        assert_eq!(decode_memo("héllo\0".as_bytes()), "héllo\0");
+        assert_eq!(decode_memo(&pad("")), "");
+        assert_eq!(decode_memo(&pad("héllo\0")), "héllo\0");
+        assert_eq!(decode_memo(&[0xff, 0x00]), "");
+    }
 }
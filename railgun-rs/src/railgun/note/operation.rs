@@ -151,6 +151,10 @@ impl<N: Note> Operation<N> {
         &self.in_notes
     }
 
+    /// Returns the operation's output notes in commitment order: the fee note,
+    /// then transfer notes, then the unshield note. The railgun contract requires
+    /// the unshield to be the final commitment, so it is always emitted last
+    /// regardless of when it was added.
     pub fn out_notes(&self) -> Vec<Box<dyn Note>> {
         let mut notes: Vec<Box<dyn Note>> = Vec::new();
 
@@ -226,7 +230,7 @@ mod tests {
 
     use crate::{
         caip::AssetId,
-        crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+        crypto::keys::{ByteKey, ViewingKey},
         railgun::{
             note::{
                 Note,
@@ -235,7 +239,7 @@ mod tests {
                 unshield::UnshieldNote,
                 utxo::test_note,
             },
-            signer::{PrivateKeySigner, Signer},
+            signer::{Signer, test_signer},
         },
     };
 
@@ -243,11 +247,7 @@ mod tests {
     #[test]
     #[traced_test]
     fn test_operation_ordering() {
-        let from_account = PrivateKeySigner::new_evm(
-            SpendingKey::from_bytes([1u8; 32]),
-            ViewingKey::from_bytes([2u8; 32]),
-            1,
-        );
+        let from_account = test_signer();
 
         let in_note = test_note();
         let fee_note = TransferNote::new(
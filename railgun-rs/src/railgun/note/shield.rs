@@ -3,8 +3,7 @@ use rand::Rng;
 use thiserror::Error;
 
 use crate::{
-    abis::railgun::{CommitmentPreimage, ShieldCiphertext, ShieldRequest},
-    caip::AssetId,
+    abis::railgun::{CommitmentPreimage, ShieldCiphertext, ShieldRequest, TokenData},
     crypto::{
         concat_arrays,
         keys::{ByteKey, U256Key, ViewingKey},
@@ -18,7 +17,7 @@ pub enum ShieldError {}
 
 pub fn create_shield_request<R: Rng>(
     recipient: RailgunAddress,
-    asset: AssetId,
+    token: TokenData,
     value: u128,
     rng: &mut R,
 ) -> Result<ShieldRequest, ShieldError> {
@@ -45,7 +44,7 @@ pub fn create_shield_request<R: Rng>(
     Ok(ShieldRequest {
         preimage: CommitmentPreimage {
             npk: npk.into(),
-            token: asset.into(),
+            token,
             value: Uint::from(value),
         },
         ciphertext: ShieldCiphertext {
@@ -66,12 +65,13 @@ pub fn create_shield_request<R: Rng>(
 mod tests {
     use std::sync::Arc;
 
-    use alloy::primitives::Address;
+    use alloy::primitives::{Address, U256};
     use rand::Rng;
     use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
     use tracing_test::traced_test;
 
     use crate::{
+        abis::railgun::{TokenData, TokenType},
         caip::AssetId,
         crypto::keys::{SpendingKey, ViewingKey},
         railgun::{
@@ -94,7 +94,8 @@ mod tests {
         let asset: AssetId = AssetId::Erc20(Address::from([0u8; 20]));
         let value: u128 = 1_000_000;
 
-        let shield_request = create_shield_request(recipient, asset, value, &mut rng).unwrap();
+        let shield_request =
+            create_shield_request(recipient, asset.into(), value, &mut rng).unwrap();
         insta::assert_debug_snapshot!(shield_request);
     }
 
@@ -111,7 +112,8 @@ mod tests {
         let asset: AssetId = AssetId::Erc20(Address::from([0u8; 20]));
         let value: u128 = 1_000_000;
 
-        let shield_request = create_shield_request(recipient, asset, value, &mut rng).unwrap();
+        let shield_request =
+            create_shield_request(recipient, asset.into(), value, &mut rng).unwrap();
 
         // Decrypt the note
         let decrypted = UtxoNote::decrypt_shield_request(signer, 1, 0, shield_request)
@@ -121,4 +123,25 @@ mod tests {
         assert_eq!(decrypted.asset(), asset);
         assert_eq!(decrypted.memo(), "");
     }
+
+    #[test]
+    #[traced_test]
+    fn test_shield_raw_token_data() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+
+        let signer = PrivateKeySigner::new_evm(rng.random(), rng.random(), 1);
+        let token = TokenData {
+            tokenType: TokenType::ERC1155,
+            tokenAddress: Address::from([1u8; 20]),
+            tokenSubID: U256::from(7),
+        };
+
+        let shield_request =
+            create_shield_request(signer.address(), token.clone(), 5, &mut rng).unwrap();
+        assert_eq!(shield_request.preimage.token.hash(), token.hash());
+
+        let decrypted = UtxoNote::decrypt_shield_request(signer, 1, 0, shield_request)
+            .expect("Failed to decrypt shield note");
+        assert_eq!(decrypted.asset().hash(), token.hash());
+    }
 }
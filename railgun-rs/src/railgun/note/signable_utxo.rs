@@ -87,7 +87,7 @@ impl PartialEq for SignableUtxoNote {
     fn eq(&self, other: &Self) -> bool {
         self.inner == other.inner
             && self.signer.viewing_key() == other.signer.viewing_key()
-            && self.signer.spending_key() == other.signer.spending_key()
+            && self.signer.spending_public_key() == other.signer.spending_public_key()
     }
 }
 
@@ -27,6 +27,10 @@ pub struct TransferNote {
     pub value: u128,
     pub random: [u8; 16],
     pub memo: String,
+    /// Pad the encrypted memo to hide its length, see `MEMO_PADDED_LENGTH`.
+    pub pad_memo: bool,
+    /// Let the recipient see the sender's address. Defaults to blinded.
+    pub reveal_sender: bool,
 }
 
 impl TransferNote {
@@ -45,8 +49,37 @@ impl TransferNote {
             value,
             random,
             memo: memo.to_string(),
+            pad_memo: false,
+            reveal_sender: false,
         }
     }
+
+    /// Sets whether the recipient can see the sender's address.
+    pub fn with_revealed_sender(mut self, reveal_sender: bool) -> Self {
+        self.reveal_sender = reveal_sender;
+        self
+    }
+
+    /// Pads the encrypted memo so the ciphertext length doesn't reveal the
+    /// memo's length.
+    pub fn with_padded_memo(mut self) -> Self {
+        self.pad_memo = true;
+        self
+    }
+
+    /// Creates a transfer note with a fixed sender key, random, and memo so its
+    /// commitment is reproducible in assertions.
+    #[cfg(test)]
+    pub fn with_fixed_random(to: RailgunAddress, asset: AssetId, value: u128) -> Self {
+        TransferNote::new(
+            ViewingKey::from_bytes([3u8; 32]),
+            to,
+            asset,
+            value,
+            [2u8; 16],
+            "memo",
+        )
+    }
 }
 
 impl EncryptableNote for TransferNote {
@@ -58,7 +91,8 @@ impl EncryptableNote for TransferNote {
             &self.asset,
             &self.memo,
             self.from_key,
-            false,
+            !self.reveal_sender,
+            self.pad_memo,
             rng,
         )
     }
@@ -108,15 +142,14 @@ mod tests {
         railgun::{
             address::{ChainId, RailgunAddress},
             merkle_tree::UtxoLeafHash,
-            note::{Note, transfer::TransferNote},
+            note::{Note, transfer::TransferNote, utxo::test_note},
         },
     };
 
     #[test]
     #[traced_test]
     fn test_transfer_note_hash() {
-        let note = TransferNote::new(
-            ViewingKey::from_bytes([3u8; 32]),
+        let note = TransferNote::with_fixed_random(
             RailgunAddress::from_private_keys(
                 SpendingKey::from_bytes([1u8; 32]),
                 ViewingKey::from_bytes([2u8; 32]),
@@ -124,8 +157,6 @@ mod tests {
             ),
             AssetId::Erc20(address!("0x1234567890123456789012345678901234567890")),
             90,
-            [2u8; 16],
-            "memo",
         );
         let hash: UtxoLeafHash = note.hash();
 
@@ -135,4 +166,32 @@ mod tests {
         .into();
         assert_eq!(hash, expected);
     }
+
+    /// Transfer notes derive the NPK from the recipient's address, while UTXO
+    /// notes derive it from the recipient's keys. Both must agree, otherwise
+    /// sent notes can't be spent by their recipient.
+    #[test]
+    #[traced_test]
+    fn test_note_public_key_matches_utxo() {
+        let utxo = test_note();
+        let transfer = TransferNote::new(
+            ViewingKey::from_bytes([9u8; 32]),
+            RailgunAddress::from_private_keys(
+                SpendingKey::from_bytes([1u8; 32]),
+                ViewingKey::from_bytes([2u8; 32]),
+                ChainId::EVM(1),
+            ),
+            utxo.asset(),
+            utxo.value(),
+            [3u8; 16],
+            "",
+        );
+
+        //? Matches the JS SDK's NPK for the same keys and random
+        let expected =
+            uint!(0x0d8534b283818d7e3c855e07d28d3d6a04c0a88b488516f45c04d71c8833177e_U256);
+        assert_eq!(utxo.note_public_key(), expected);
+        assert_eq!(transfer.note_public_key(), expected);
+        assert_eq!(transfer.hash(), utxo.hash());
+    }
 }
@@ -4,6 +4,7 @@ use ruint::aliases::U256;
 use crate::{
     abis,
     caip::AssetId,
+    chain_config::{ChainConfig, FeeError},
     crypto::poseidon::poseidon_hash,
     railgun::{merkle_tree::UtxoLeafHash, note::Note},
 };
@@ -33,6 +34,11 @@ impl UnshieldNote {
         }
     }
 
+    /// Returns the value the receiver gets after the railgun unshield fee.
+    pub fn net_value(&self, chain: &ChainConfig) -> Result<u128, FeeError> {
+        Ok(chain.unshield_fee(self.value)?.0)
+    }
+
     pub fn unshield_type(&self) -> abis::railgun::UnshieldType {
         abis::railgun::UnshieldType::NORMAL
     }
@@ -3,9 +3,12 @@ use std::{fmt::Debug, sync::Arc};
 use ruint::aliases::U256;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::warn;
 
 use crate::{
-    abis::railgun::{CommitmentCiphertext, ShieldRequest, TokenData, TokenDataError},
+    abis::railgun::{
+        CommitmentCiphertext, LegacyCommitmentCiphertext, ShieldRequest, TokenData, TokenDataError,
+    },
     caip::AssetId,
     crypto::{
         aes::{AesError, Ciphertext},
@@ -17,8 +20,8 @@ use crate::{
     },
     railgun::{
         merkle_tree::UtxoLeafHash,
-        note::{IncludedNote, Note, SignableNote},
-        poi::BlindedCommitmentType,
+        note::{IncludedNote, Note, SignableNote, encrypt::decode_memo},
+        poi::{BlindedCommitmentData, BlindedCommitmentType},
         signer::{Signer, SpendingKeyProvider, ViewingKeyProvider},
     },
 };
@@ -60,6 +63,35 @@ pub enum NoteError {
     TokenData(#[from] TokenDataError),
     #[error("Key error: {0}")]
     Key(#[from] KeyError),
+    #[error("Unsupported note ciphertext version: {0}")]
+    UnsupportedVersion(u8),
+}
+
+/// Ciphertext format version for transact notes emitted by the pre-V2 smart wallet.
+pub const NOTE_VERSION_LEGACY: u8 = 1;
+/// Ciphertext format version for transact notes emitted by the V2 smart wallet.
+pub const NOTE_VERSION_V2: u8 = 2;
+
+/// A transact note ciphertext, tagged with the shape of the event that emitted it.
+///
+/// Ciphertexts don't carry an inline version byte, so the format is implied by
+/// the event shape rather than read from the note.
+#[derive(Debug, Clone, Copy)]
+pub enum NoteCiphertext<'a> {
+    /// From a pre-V2 `CommitmentBatch` event, keyed by ephemeral keys.
+    Legacy(&'a LegacyCommitmentCiphertext),
+    /// From a V2 `Transact` event, keyed by blinded viewing keys.
+    V2(&'a CommitmentCiphertext),
+}
+
+impl NoteCiphertext<'_> {
+    /// Returns the ciphertext format version implied by the event shape.
+    pub fn version(&self) -> u8 {
+        match self {
+            NoteCiphertext::Legacy(_) => NOTE_VERSION_LEGACY,
+            NoteCiphertext::V2(_) => NOTE_VERSION_V2,
+        }
+    }
 }
 
 impl UtxoNote<Arc<dyn Signer>> {
@@ -81,7 +113,7 @@ impl UtxoNote<Arc<dyn Signer>> {
         UtxoNote {
             tree_number,
             leaf_index,
-            spending_pubkey: signer.as_ref().spending_key().public_key(),
+            spending_pubkey: signer.as_ref().spending_public_key(),
             viewing_pubkey: signer.as_ref().viewing_key().public_key(),
             asset,
             value,
@@ -96,41 +128,70 @@ impl UtxoNote<Arc<dyn Signer>> {
         }
     }
 
-    /// Decrypt a note
+    /// Decrypt a V2 note
     pub fn decrypt(
         signer: Arc<dyn Signer>,
         tree_number: u32,
         leaf_index: u32,
         encrypted: &CommitmentCiphertext,
+    ) -> Result<Self, NoteError> {
+        Self::decrypt_versioned(
+            signer,
+            tree_number,
+            leaf_index,
+            NoteCiphertext::V2(encrypted),
+        )
+    }
+
+    /// Decrypt a note in whichever format its ciphertext's event shape implies.
+    ///
+    /// Returns `NoteError::UnsupportedVersion` for formats this library can't
+    /// parse yet rather than attempting to parse them with the wrong layout.
+    pub fn decrypt_versioned(
+        signer: Arc<dyn Signer>,
+        tree_number: u32,
+        leaf_index: u32,
+        ciphertext: NoteCiphertext,
+    ) -> Result<Self, NoteError> {
+        match ciphertext {
+            NoteCiphertext::V2(encrypted) => {
+                Self::decrypt_v2(signer, tree_number, leaf_index, encrypted)
+            }
+            NoteCiphertext::Legacy(_) => Err(NoteError::UnsupportedVersion(ciphertext.version())),
+        }
+    }
+
+    fn decrypt_v2(
+        signer: Arc<dyn Signer>,
+        tree_number: u32,
+        leaf_index: u32,
+        encrypted: &CommitmentCiphertext,
     ) -> Result<Self, NoteError> {
         let blinded_sender = BlindedKey::from_bytes(encrypted.blindedSenderViewingKey.into());
         let shared_key = signer
             .viewing_key()
             .derive_shared_key_blinded(blinded_sender)?;
 
-        let data: Vec<Vec<u8>> = vec![
-            encrypted.ciphertext[1].to_vec(),
-            encrypted.ciphertext[2].to_vec(),
-            encrypted.ciphertext[3].to_vec(),
-            encrypted.memo.to_vec(),
-        ];
-
-        let mut iv = [0u8; 16];
-        let mut tag = [0u8; 16];
-
-        iv.copy_from_slice(&encrypted.ciphertext[0][..16]);
-        tag.copy_from_slice(&encrypted.ciphertext[0][16..]);
-
-        let ciphertext = Ciphertext { iv, tag, data };
-
         // iv (16) | tag (16)
         // master_public_key (32)
         // token_hash (32)
         // random (16) | value (16)
-        let bundle = shared_key.decrypt_gcm(&ciphertext)?;
-
-        let token_data = TokenData::from_hash(&bundle[1])?;
-        let asset_id = AssetId::from(token_data);
+        let bundle = shared_key.decrypt_gcm(&transact_ciphertext(encrypted))?;
+
+        //? ERC-20 token hashes are the token address, left-padded. Any other
+        //? token type is hashed, and its token data can't be recovered from
+        //? the note. Keep those notes visible rather than dropping them, so
+        //? their value isn't silently lost.
+        let token_hash = &bundle[1];
+        let asset_id = if token_hash[..12].iter().all(|b| *b == 0) {
+            AssetId::from(TokenData::from_hash(token_hash)?)
+        } else {
+            warn!(
+                "Non-ERC20 token hash in note at tree {}, leaf {}",
+                tree_number, leaf_index
+            );
+            AssetId::Unknown(TokenData::raw_hash(token_hash))
+        };
 
         let mut random = [0u8; 16];
         random.copy_from_slice(&bundle[2][..16]);
@@ -140,7 +201,7 @@ impl UtxoNote<Arc<dyn Signer>> {
         let value = u128::from_be_bytes(value_bytes);
 
         let memo = if bundle.len() > 3 {
-            std::str::from_utf8(&bundle[3]).unwrap_or("")
+            decode_memo(&bundle[3])
         } else {
             ""
         };
@@ -223,6 +284,78 @@ impl UtxoNote<Arc<dyn Signer>> {
     }
 }
 
+impl UtxoNote<()> {
+    /// Attaches a signer to this note. Returns `None` if the note doesn't
+    /// belong to the signer.
+    pub fn with_signer(&self, signer: Arc<dyn Signer>) -> Option<UtxoNote> {
+        let note = UtxoNote::new(
+            self.tree_number,
+            self.leaf_index,
+            signer,
+            self.asset,
+            self.value,
+            self.random,
+            &self.memo,
+            self.type_,
+        );
+
+        (note.hash == self.hash).then_some(note)
+    }
+}
+
+/// A transact output decrypted from the sender's side.
+///
+/// Senders can't recover the receiver's keys, only the fields needed to
+/// recompute the output's commitment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SentNote {
+    pub npk: U256,
+    pub token_hash: U256,
+    pub value: u128,
+}
+
+impl SentNote {
+    /// Decrypts a V2 note that was encrypted by the owner of `vk`.
+    pub fn decrypt(
+        vk: &dyn ViewingKeyProvider,
+        encrypted: &CommitmentCiphertext,
+    ) -> Result<Self, NoteError> {
+        let blinded_receiver = BlindedKey::from_bytes(encrypted.blindedReceiverViewingKey.into());
+        let shared_key = vk
+            .viewing_key()
+            .derive_shared_key_blinded(blinded_receiver)?;
+        let bundle = shared_key.decrypt_gcm(&transact_ciphertext(encrypted))?;
+
+        let master_key = U256::from_be_slice(&bundle[0]);
+        let token_hash = U256::from_be_slice(&bundle[1]);
+
+        let mut value_bytes = [0u8; 16];
+        value_bytes.copy_from_slice(&bundle[2][16..]);
+
+        Ok(SentNote {
+            npk: poseidon_hash(&[master_key, U256::from_be_slice(&bundle[2][..16])]).unwrap(),
+            token_hash,
+            value: u128::from_be_bytes(value_bytes),
+        })
+    }
+
+    /// Commitment hash
+    pub fn hash(&self) -> U256 {
+        poseidon_hash(&[self.npk, self.token_hash, U256::from(self.value)]).unwrap()
+    }
+}
+
+impl<S> UtxoNote<S> {
+    /// Returns the blinded commitment and its type, as used to look up this
+    /// note's POI status on the POI node.
+    pub fn blinded_commitment_data(&self) -> BlindedCommitmentData {
+        BlindedCommitmentData {
+            commitment_type: self.type_.into(),
+            blinded_commitment: self.blinded_commitment.into(),
+        }
+    }
+}
+
 impl<S> Note for UtxoNote<S> {
     fn asset(&self) -> AssetId {
         self.asset
@@ -335,7 +468,7 @@ impl PartialEq for UtxoNote<Arc<dyn Signer>> {
             && self.leaf_index == other.leaf_index
             && self.hash == other.hash
             && self.signer.viewing_key() == other.signer.viewing_key()
-            && self.signer.spending_key() == other.signer.spending_key()
+            && self.signer.spending_public_key() == other.signer.spending_public_key()
     }
 }
 
@@ -350,6 +483,24 @@ impl From<UtxoType> for BlindedCommitmentType {
     }
 }
 
+/// Unpacks a V2 transact ciphertext for AES-GCM decryption.
+fn transact_ciphertext(encrypted: &CommitmentCiphertext) -> Ciphertext {
+    let data: Vec<Vec<u8>> = vec![
+        encrypted.ciphertext[1].to_vec(),
+        encrypted.ciphertext[2].to_vec(),
+        encrypted.ciphertext[3].to_vec(),
+        encrypted.memo.to_vec(),
+    ];
+
+    let mut iv = [0u8; 16];
+    let mut tag = [0u8; 16];
+
+    iv.copy_from_slice(&encrypted.ciphertext[0][..16]);
+    tag.copy_from_slice(&encrypted.ciphertext[0][16..]);
+
+    Ciphertext { iv, tag, data }
+}
+
 fn note_hash(
     sk: &dyn SpendingKeyProvider,
     vk: &dyn ViewingKeyProvider,
@@ -371,16 +522,14 @@ fn note_public_key(
     vk: &dyn ViewingKeyProvider,
     random: &[u8; 16],
 ) -> U256 {
-    let master_key = MasterPublicKey::new(
-        sk.spending_key().public_key(),
-        vk.viewing_key().nullifying_key(),
-    );
+    let master_key =
+        MasterPublicKey::new(sk.spending_public_key(), vk.viewing_key().nullifying_key());
 
     poseidon_hash(&[master_key.to_u256(), U256::from_be_slice(random)]).unwrap()
 }
 
 fn spending_pubkey(sk: &dyn SpendingKeyProvider) -> [U256; 2] {
-    let pubkey = sk.spending_key().public_key();
+    let pubkey = sk.spending_public_key();
     [pubkey.x_u256(), pubkey.y_u256()]
 }
 
@@ -399,16 +548,9 @@ fn blinded_commitment(hash: U256, npk: U256, tree_number: u32, leaf_index: u32)
 
 #[cfg(test)]
 pub fn test_note() -> UtxoNote<Arc<dyn Signer>> {
-    use crate::{
-        crypto::keys::{SpendingKey, ViewingKey},
-        railgun::signer::PrivateKeySigner,
-    };
-
-    let signer = PrivateKeySigner::new_evm(
-        SpendingKey::from_bytes([1u8; 32]),
-        ViewingKey::from_bytes([2u8; 32]),
-        1,
-    );
+    use crate::railgun::signer::test_signer;
+
+    let signer = test_signer();
     UtxoNote::new(
         1,
         0,
@@ -484,4 +626,106 @@ mod tests {
 
         insta::assert_debug_snapshot!(pub_key);
     }
+
+    #[test]
+    #[traced_test]
+    fn test_watch_only_note() {
+        use crate::railgun::signer::test_signer;
+
+        let note = test_note();
+        let signer = test_signer();
+        let watch_only = note
+            .without_signer()
+            .with_signer(Arc::new(signer.export_watch_only()))
+            .unwrap();
+
+        assert_eq!(watch_only.hash(), note.hash());
+        assert_eq!(watch_only.nullifier(U256::ZERO), note.nullifier(U256::ZERO));
+        assert_eq!(watch_only.blinded_commitment(), note.blinded_commitment());
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_sent_note_decrypt() {
+        use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+
+        use crate::{
+            crypto::keys::{SpendingKey, ViewingKey},
+            railgun::{note::encrypt::encrypt_note, signer::PrivateKeySigner},
+        };
+
+        let sender = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([5u8; 32]),
+            ViewingKey::from_bytes([6u8; 32]),
+            1,
+        );
+        let note = test_note();
+
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let encrypted = encrypt_note(
+            &note.signer.address(),
+            &note.random,
+            note.value,
+            &note.asset,
+            &note.memo,
+            sender.viewing_key(),
+            true,
+            false,
+            &mut rng,
+        )
+        .unwrap();
+
+        let sent = SentNote::decrypt(sender.as_ref(), &encrypted).unwrap();
+        assert_eq!(sent.npk, note.note_public_key());
+        assert_eq!(sent.value, note.value);
+        assert_eq!(sent.hash(), U256::from(note.hash()));
+
+        //? Only the sender can decrypt from the sender's side
+        assert!(SentNote::decrypt(note.signer.as_ref(), &encrypted).is_err());
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_blinded_commitment_data() {
+        let transact = test_note();
+        let shield = UtxoNote::new(
+            transact.tree_number,
+            transact.leaf_index,
+            transact.signer.clone(),
+            transact.asset,
+            transact.value,
+            transact.random,
+            "",
+            UtxoType::Shield,
+        );
+
+        for (note, commitment_type) in [
+            (&transact, BlindedCommitmentType::Transact),
+            (&shield, BlindedCommitmentType::Shield),
+        ] {
+            let data = note.blinded_commitment_data();
+            let expected = poseidon_hash(&[
+                note.hash().into(),
+                note.note_public_key(),
+                U256::from(1u64 << 16),
+            ])
+            .unwrap();
+
+            assert_eq!(data.commitment_type, commitment_type);
+            assert_eq!(U256::from(data.blinded_commitment), expected);
+            assert_eq!(
+                serde_json::to_value(&data).unwrap(),
+                serde_json::json!({
+                    "blindedCommitment": format!("0x{:064x}", expected),
+                    "type": format!("{:?}", commitment_type),
+                })
+            );
+        }
+
+        //? The type is reported alongside the commitment, not hashed into it
+        assert_eq!(
+            shield.blinded_commitment_data().blinded_commitment,
+            transact.blinded_commitment_data().blinded_commitment
+        );
+    }
 }
@@ -1,12 +1,17 @@
+pub mod pending_poi_submitter;
 mod poi_client;
 mod poi_note;
-pub mod pending_poi_submitter;
 mod types;
 
-pub use poi_client::{PoiClient, PoiClientError};
+pub use pending_poi_submitter::{
+    PendingPoiEntry, PendingPoiError, PendingPoiSubmitter, submit_pois_for_operation,
+};
+#[cfg(test)]
+pub use poi_client::TestPoiNode;
+pub use poi_client::{JsonRpcTransport, PoiClient, PoiClientError, ReqwestTransport};
 pub use poi_note::PoiNote;
-pub use pending_poi_submitter::{PendingPoiEntry, PendingPoiError, PendingPoiSubmitter};
 pub use types::{
-    BlindedCommitment, BlindedCommitmentType, ListKey, PreTransactionPoi,
-    PreTransactionPoisPerTxidLeafPerList, TxidVersion,
+    BlindedCommitment, BlindedCommitmentData, BlindedCommitmentType, ListKey, ListKeyError,
+    PoiStatus, PoisPerListMap, PreTransactionPoi, PreTransactionPoisPerTxidLeafPerList,
+    TxidVersion,
 };
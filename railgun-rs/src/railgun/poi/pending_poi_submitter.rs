@@ -16,7 +16,10 @@ use crate::{
     railgun::{
         indexer::{TxidIndexer, UtxoIndexer},
         merkle_tree::UtxoTreeIndex,
-        note::utxo::UtxoNote,
+        note::{
+            Note,
+            utxo::{NoteError, SentNote, UtxoNote},
+        },
         poi::{ListKey, PoiClient, PoiClientError, types::TransactProofData},
         transaction::PoiProvedOperation,
     },
@@ -72,6 +75,97 @@ pub enum PendingPoiError {
     MissingUtxoTree(u32),
     #[error("Missing TXID tree {0}")]
     MissingTxidTree(u32),
+    #[error("No validated operation for txid {0:?}")]
+    UnknownOperation(Txid),
+    #[error("Inputs of operation {0:?} don't belong to a registered account")]
+    UnknownInputs(Txid),
+    #[error("No matched Transact event for operation {0:?}")]
+    MissingTransactEvent(Txid),
+    #[error("Decrypted output of operation {0:?} doesn't match its commitment")]
+    OutputMismatch(Txid),
+    #[error("Note error: {0}")]
+    Note(#[from] NoteError),
+}
+
+impl PendingPoiEntry {
+    /// Reconstructs an entry for an already-broadcast operation from the synced
+    /// trees.
+    ///
+    /// The operation's inputs must belong to an account registered with
+    /// `utxo_indexer`, whose nullifiers were passed to
+    /// `TxidIndexer::watch_nullifiers` before the operation was validated.
+    /// Its outputs are decrypted from the sender's side out of the matched
+    /// Transact event, which is retained even for spends with no change.
+    ///
+    /// Fails with `MissingTransactEvent` if the event wasn't synced by
+    /// `utxo_indexer`, or for an unshield-only operation, which has no
+    /// encrypted outputs to identify the sender by.
+    pub fn from_indexed(
+        txid: Txid,
+        txid_indexer: &TxidIndexer,
+        utxo_indexer: &UtxoIndexer,
+        list_keys: Vec<ListKey>,
+    ) -> Result<Self, PendingPoiError> {
+        let op = txid_indexer
+            .txid_set
+            .operation(&txid)
+            .ok_or(PendingPoiError::UnknownOperation(txid))?;
+
+        let (signer, in_notes) = utxo_indexer
+            .notes_by_nullifiers(op.utxo_tree_in, &op.nullifiers)
+            .ok_or(PendingPoiError::UnknownInputs(txid))?;
+        let token = in_notes
+            .first()
+            .ok_or(PendingPoiError::UnknownInputs(txid))?
+            .asset()
+            .hash();
+
+        let event = utxo_indexer
+            .transact_event(op.utxo_tree_out, op.utxo_out_start_index)
+            .ok_or(PendingPoiError::MissingTransactEvent(txid))?;
+
+        //? Unshield commitments have no ciphertext and aren't inserted into the
+        //? tree, so they're the only commitments missing from the event.
+        let mut out_npks = Vec::new();
+        let mut out_values = Vec::new();
+        let mut unshields = 0;
+        for commitment in &op.commitment_hashes {
+            let Some(index) = event
+                .hash
+                .iter()
+                .position(|hash| U256::from_be_bytes(hash.0) == *commitment)
+            else {
+                unshields += 1;
+                continue;
+            };
+
+            let sent = SentNote::decrypt(signer.as_ref(), &event.ciphertext[index])?;
+            if sent.hash() != *commitment {
+                return Err(PendingPoiError::OutputMismatch(txid));
+            }
+
+            out_npks.push(sent.npk);
+            out_values.push(U256::from(sent.value));
+        }
+        if unshields > 1 {
+            return Err(PendingPoiError::OutputMismatch(txid));
+        }
+
+        Ok(PendingPoiEntry {
+            txid,
+            spending_pubkey: signer.spending_public_key(),
+            nullifying_key: signer.viewing_key().nullifying_key(),
+            utxo_tree_in: op.utxo_tree_in,
+            bound_params_hash: op.bound_params_hash,
+            in_notes: in_notes.iter().map(|n| n.without_signer()).collect(),
+            out_commitments: op.commitment_hashes.clone(),
+            out_npks,
+            out_values,
+            token,
+            has_unshield: unshields == 1,
+            list_keys,
+        })
+    }
 }
 
 impl PendingPoiSubmitter {
@@ -106,7 +200,7 @@ impl PendingPoiSubmitter {
     /// Register a proved operation for post-transaction POI submission.
     pub fn register(&mut self, op: &PoiProvedOperation) {
         let Some(txid) = op.txid else { return };
-        let spending_pubkey = op.operation.from.spending_key().public_key();
+        let spending_pubkey = op.operation.from.spending_public_key();
 
         self.pending.push(PendingPoiEntry {
             txid,
@@ -159,70 +253,10 @@ impl PendingPoiSubmitter {
         let mut submitted = Vec::new();
         for i in 0..self.pending.len() {
             let entry = &self.pending[i];
-
-            let Some((tree_number, leaf_index)) = txid_indexer.txid_set.position_of(&entry.txid)
-            else {
+            if !submit_entry(entry, txid_indexer, utxo_indexer, poi_client, prover).await? {
                 continue;
-            };
-
-            let txid_tree = txid_indexer
-                .txid_set
-                .tree(tree_number)
-                .ok_or(PendingPoiError::MissingTxidTree(tree_number))?;
-
-            let utxo_tree = utxo_indexer
-                .utxo_trees
-                .get(&entry.utxo_tree_in)
-                .ok_or(PendingPoiError::MissingUtxoTree(entry.utxo_tree_in))?;
-
-            let included = UtxoTreeIndex::included(tree_number, leaf_index);
-
-            // Re-fetch fresh POI merkle proofs from the aggregator.
-            let fresh_poi_notes = poi_client
-                .note_to_poi_note(entry.in_notes.clone(), &entry.list_keys)
-                .await?;
-
-            // Build and submit a proof for each list key.
-            let mut proof_data_map = HashMap::new();
-            for list_key in &entry.list_keys {
-                let inputs = PoiCircuitInputs::from_inputs_included(
-                    entry.spending_pubkey,
-                    entry.nullifying_key,
-                    utxo_tree,
-                    entry.utxo_tree_in,
-                    entry.bound_params_hash,
-                    &fresh_poi_notes,
-                    &entry.out_commitments,
-                    &entry.out_npks,
-                    &entry.out_values,
-                    entry.token,
-                    entry.has_unshield,
-                    list_key.clone(),
-                    included,
-                    txid_tree,
-                )?;
-
-                let (proof, public_inputs) = prover
-                    .prove_poi(&inputs)
-                    .await
-                    .map_err(PendingPoiError::Prover)?;
-
-                let blinded_commitments_out = public_inputs[0..inputs.nullifiers.len()].to_vec();
-
-                proof_data_map.insert(
-                    list_key.clone(),
-                    TransactProofData {
-                        proof,
-                        poi_merkleroots: inputs.poi_merkleroots,
-                        txid_merkleroot: inputs.railgun_txid_merkleroot_after_transaction,
-                        txid_merkleroot_index: leaf_index as u64,
-                        blinded_commitments_out,
-                        railgun_txid_if_has_unshield: inputs.railgun_txid_if_has_unshield,
-                    },
-                );
             }
 
-            poi_client.submit_operation(proof_data_map).await?;
             let txid = entry.txid;
             self.pending.remove(i);
             submitted.push(txid);
@@ -231,3 +265,224 @@ impl PendingPoiSubmitter {
         Ok(submitted)
     }
 }
+
+/// Proves and submits POIs for an operation that has already been broadcast,
+/// looked up by its txid in the synced trees.
+///
+/// This is the recovery path for operations that were self-broadcast without
+/// POIs, or whose POI submission failed, so their outputs can become spendable
+/// on POI-enforcing lists. See `PendingPoiEntry::from_indexed` for what must
+/// have been indexed.
+pub async fn submit_pois_for_operation<P: PoiProver>(
+    txid_indexer: &TxidIndexer,
+    utxo_indexer: &UtxoIndexer,
+    poi_client: &PoiClient,
+    prover: &P,
+    txid: Txid,
+) -> Result<(), PendingPoiError> {
+    let entry =
+        PendingPoiEntry::from_indexed(txid, txid_indexer, utxo_indexer, poi_client.list_keys())?;
+
+    if !submit_entry(&entry, txid_indexer, utxo_indexer, poi_client, prover).await? {
+        return Err(PendingPoiError::UnknownOperation(txid));
+    }
+
+    Ok(())
+}
+
+/// Re-proves an entry with its real TXID Merkle position and submits it to the
+/// POI aggregator. Returns false if the entry's txid hasn't been validated yet.
+async fn submit_entry<P: PoiProver>(
+    entry: &PendingPoiEntry,
+    txid_indexer: &TxidIndexer,
+    utxo_indexer: &UtxoIndexer,
+    poi_client: &PoiClient,
+    prover: &P,
+) -> Result<bool, PendingPoiError> {
+    let Some((tree_number, leaf_index)) = txid_indexer.txid_set.position_of(&entry.txid) else {
+        return Ok(false);
+    };
+
+    let txid_tree = txid_indexer
+        .txid_set
+        .tree(tree_number)
+        .ok_or(PendingPoiError::MissingTxidTree(tree_number))?;
+
+    let utxo_tree = utxo_indexer
+        .utxo_trees
+        .get(&entry.utxo_tree_in)
+        .ok_or(PendingPoiError::MissingUtxoTree(entry.utxo_tree_in))?;
+
+    let included = UtxoTreeIndex::included(tree_number, leaf_index);
+
+    // Re-fetch fresh POI merkle proofs from the aggregator.
+    let fresh_poi_notes = poi_client
+        .note_to_poi_note(entry.in_notes.clone(), &entry.list_keys)
+        .await?;
+
+    // Build and submit a proof for each list key.
+    let mut proof_data_map = HashMap::new();
+    for list_key in &entry.list_keys {
+        let inputs = PoiCircuitInputs::from_inputs_included(
+            entry.spending_pubkey,
+            entry.nullifying_key,
+            utxo_tree,
+            entry.utxo_tree_in,
+            entry.bound_params_hash,
+            &fresh_poi_notes,
+            &entry.out_commitments,
+            &entry.out_npks,
+            &entry.out_values,
+            entry.token,
+            entry.has_unshield,
+            list_key.clone(),
+            included,
+            txid_tree,
+        )?;
+
+        let (proof, public_inputs) = prover
+            .prove_poi(&inputs)
+            .await
+            .map_err(PendingPoiError::Prover)?;
+
+        let blinded_commitments_out = public_inputs[0..inputs.nullifiers.len()].to_vec();
+
+        proof_data_map.insert(
+            list_key.clone(),
+            TransactProofData {
+                proof,
+                poi_merkleroots: inputs.poi_merkleroots,
+                txid_merkleroot: inputs.railgun_txid_merkleroot_after_transaction,
+                txid_merkleroot_index: leaf_index as u64,
+                blinded_commitments_out,
+                railgun_txid_if_has_unshield: inputs.railgun_txid_if_has_unshield,
+            },
+        );
+    }
+
+    poi_client.submit_operation(proof_data_map).await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use alloy::primitives::{B256, address};
+    use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+    use tracing_test::traced_test;
+
+    use super::*;
+    use crate::{
+        abis::railgun::RailgunSmartWallet,
+        caip::AssetId,
+        crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+        railgun::{
+            indexer::{
+                syncer::{Operation, SubsquidSyncer, SyncEvent},
+                test_indexer_with_events,
+            },
+            note::{EncryptableNote, transfer::TransferNote},
+            poi::TestPoiNode,
+            signer::{
+                PrivateKeySigner, Signer, SpendingKeyProvider, ViewingKeyProvider, test_signer,
+            },
+        },
+    };
+
+    fn transact_event(start_position: u32, notes: &[TransferNote], block_number: u64) -> SyncEvent {
+        let mut rng = ChaChaRng::seed_from_u64(block_number);
+        let (hash, ciphertext) = notes
+            .iter()
+            .map(|note| {
+                let hash = B256::from(U256::from(note.hash()));
+                (hash, note.encrypt(&mut rng).unwrap())
+            })
+            .unzip();
+        let transact = RailgunSmartWallet::Transact {
+            treeNumber: U256::ZERO,
+            startPosition: U256::from(start_position),
+            hash,
+            ciphertext,
+        };
+        SyncEvent::Transact(transact, block_number)
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_from_indexed_without_change() {
+        let signer = test_signer();
+        let receiver = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([5u8; 32]),
+            ViewingKey::from_bytes([6u8; 32]),
+            1,
+        );
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+
+        //? Spend the whole note to the receiver with no change note, so the
+        //? spend's event is only matched from the sender's side. The unshield
+        //? commitment has no ciphertext in the event.
+        let funding = TransferNote::with_fixed_random(signer.address(), asset, 100);
+        let transfer = TransferNote::new(
+            signer.viewing_key(),
+            receiver.address(),
+            asset,
+            100,
+            [4u8; 16],
+            "",
+        );
+        let utxo_indexer = test_indexer_with_events(
+            signer.clone(),
+            vec![
+                transact_event(0, &[funding], 1),
+                transact_event(1, &[transfer.clone()], 2),
+            ],
+        );
+
+        let unshield_commitment = U256::from(7);
+        let op = Operation {
+            nullifiers: utxo_indexer.nullifiers(),
+            commitment_hashes: vec![U256::from(transfer.hash()), unshield_commitment],
+            bound_params_hash: U256::from(1),
+            utxo_tree_in: 0,
+            utxo_tree_out: 0,
+            utxo_out_start_index: 1,
+        };
+        let txid = Txid::new(&op.nullifiers, &op.commitment_hashes, op.bound_params_hash);
+
+        let poi_client = PoiClient::with_transport(
+            TestPoiNode {
+                validated_txid_index: 0,
+            },
+            1,
+        )
+        .await
+        .unwrap();
+        let mut txid_indexer =
+            TxidIndexer::new(Arc::new(SubsquidSyncer::new("")), poi_client.clone());
+        txid_indexer.watch_nullifiers(utxo_indexer.nullifiers());
+        txid_indexer.txid_set.enqueue(op.clone(), 2);
+        txid_indexer.validate().await.unwrap();
+
+        let entry = PendingPoiEntry::from_indexed(
+            txid,
+            &txid_indexer,
+            &utxo_indexer,
+            poi_client.list_keys(),
+        )
+        .unwrap();
+
+        assert_eq!(entry.txid, txid);
+        assert_eq!(entry.spending_pubkey, signer.spending_public_key());
+        assert_eq!(entry.utxo_tree_in, 0);
+        assert_eq!(entry.bound_params_hash, op.bound_params_hash);
+        assert_eq!(entry.in_notes.len(), 1);
+        assert_eq!(entry.in_notes[0].value(), 100);
+        assert_eq!(entry.out_commitments, op.commitment_hashes);
+        assert_eq!(entry.out_npks, vec![transfer.note_public_key()]);
+        assert_eq!(entry.out_values, vec![U256::from(100)]);
+        assert_eq!(entry.token, asset.hash());
+        assert!(entry.has_unshield);
+        assert_eq!(entry.list_keys, poi_client.list_keys());
+    }
+}
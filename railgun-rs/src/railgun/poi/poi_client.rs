@@ -12,34 +12,74 @@ use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use thiserror::Error;
 use tracing::info;
 
-use crate::railgun::{
-    merkle_tree::{MerkleProof, MerkleRoot, MerkleTreeVerifier},
-    note::{IncludedNote, utxo::UtxoNote},
-    poi::{
-        poi_note::PoiNote,
-        types::{
-            BlindedCommitment, BlindedCommitmentData, ChainParams, GetMerkleProofsParams,
-            GetPoisPerListParams, ListKey, NodeStatusAllNetworks, PoisPerListMap,
-            SubmitTransactProofParams, TransactProofData, TxidVersion,
-            ValidatePoiMerklerootsParams, ValidateTxidMerklerootParams, ValidatedRailgunTxidStatus,
+use crate::{
+    chain_config::ChainConfig,
+    railgun::{
+        merkle_tree::{MerkleProof, MerkleRoot, MerkleTreeVerifier},
+        note::{IncludedNote, utxo::UtxoNote},
+        poi::{
+            poi_note::PoiNote,
+            types::{
+                BlindedCommitment, BlindedCommitmentData, ChainParams, GetMerkleProofsParams,
+                GetPoisPerListParams, ListKey, NodeStatusAllNetworks, PoisPerListMap,
+                SubmitTransactProofParams, TransactProofData, TxidVersion,
+                ValidatePoiMerklerootsParams, ValidateTxidMerklerootParams,
+                ValidatedRailgunTxidStatus,
+            },
         },
     },
 };
 
+/// Maximum number of blinded commitments sent in a single `ppoi_merkle_proofs`
+/// request. Aggregators reject requests above their limit.
+const MAX_MERKLE_PROOFS_PER_REQUEST: usize = 100;
+
 #[derive(Clone)]
 pub struct PoiClient {
     inner: Arc<PoiClientInner>,
 }
 
 pub struct PoiClientInner {
-    http: Client,
-    url: String,
-    next_id: AtomicU64,
+    transport: Box<dyn JsonRpcTransport>,
 
     chain: ChainId,
     status: NodeStatusAllNetworks,
 }
 
+/// Transport for JSON-RPC calls to a POI node.
+///
+/// Defaults to `ReqwestTransport`. Implement this to route requests through a
+/// different HTTP client or to script responses in tests.
+#[cfg(not(feature = "wasm"))]
+#[async_trait::async_trait]
+pub trait JsonRpcTransport: Send + Sync {
+    /// Sends a JSON-RPC request and returns its result.
+    async fn call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, PoiClientError>;
+}
+
+/// Transport for JSON-RPC calls to a POI node (WASM version).
+#[cfg(feature = "wasm")]
+#[async_trait::async_trait(?Send)]
+pub trait JsonRpcTransport {
+    /// Sends a JSON-RPC request and returns its result.
+    async fn call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, PoiClientError>;
+}
+
+/// JSON-RPC transport over HTTP using reqwest.
+pub struct ReqwestTransport {
+    http: Client,
+    url: String,
+    next_id: AtomicU64,
+}
+
 #[derive(Debug, Error)]
 pub enum PoiClientError {
     #[error("HTTP error: {0}")]
@@ -52,12 +92,14 @@ pub enum PoiClientError {
     UnexpectedResponse(String),
     #[error("Invalid POI Merkle root for list key {0:?}: {1}")]
     InvalidPoiMerkleRoot(ListKey, MerkleRoot),
+    #[error("No POI endpoint configured for chain ID {0}")]
+    NoEndpoint(ChainId),
 }
 
 #[derive(Debug, Serialize)]
-struct JsonRpcRequest<P: Serialize> {
+struct JsonRpcRequest<'a, P: Serialize> {
     jsonrpc: &'static str,
-    method: &'static str,
+    method: &'a str,
     id: u64,
     params: P,
 }
@@ -81,14 +123,25 @@ pub struct JsonRpcError {
 
 impl PoiClient {
     pub async fn new(url: impl Into<String>, chain: ChainId) -> Result<Self, PoiClientError> {
-        let next_id = AtomicU64::new(1);
-        let http = Client::new();
-        let url = url.into();
+        Self::with_transport(ReqwestTransport::new(url), chain).await
+    }
 
+    /// Creates a client for the chain's configured POI endpoint.
+    pub async fn for_chain(chain: ChainConfig) -> Result<Self, PoiClientError> {
+        let url = chain
+            .poi_endpoint
+            .ok_or(PoiClientError::NoEndpoint(chain.id))?;
+        Self::new(url, chain.id).await
+    }
+
+    /// Creates a client that sends requests through the given transport.
+    pub async fn with_transport(
+        transport: impl JsonRpcTransport + 'static,
+        chain: ChainId,
+    ) -> Result<Self, PoiClientError> {
+        let transport: Box<dyn JsonRpcTransport> = Box::new(transport);
         let status: NodeStatusAllNetworks = call(
-            &next_id,
-            &http,
-            &url,
+            transport.as_ref(),
             "ppoi_node_status",
             serde_json::json!({}),
         )
@@ -97,9 +150,7 @@ impl PoiClient {
 
         Ok(Self {
             inner: Arc::new(PoiClientInner {
-                http,
-                url,
-                next_id,
+                transport,
                 chain,
                 status,
             }),
@@ -168,6 +219,10 @@ impl PoiClient {
 
     /// Fetches the POI merkle proofs for the given blinded commitments and
     /// list keys.
+    ///
+    /// Blinded commitments are requested in batches of at most
+    /// `MAX_MERKLE_PROOFS_PER_REQUEST`. Proofs are returned in the same order
+    /// as the blinded commitments.
     pub async fn merkle_proofs(
         &self,
         blinded_commitments: Vec<BlindedCommitment>,
@@ -175,16 +230,21 @@ impl PoiClient {
     ) -> Result<HashMap<ListKey, Vec<MerkleProof>>, PoiClientError> {
         let mut proofs = HashMap::new();
         for list_key in list_keys.iter() {
-            let list_key_proofs: Vec<MerkleProof> = self
-                .call(
-                    "ppoi_merkle_proofs",
-                    GetMerkleProofsParams {
-                        chain: self.chain(),
-                        list_key: list_key.clone(),
-                        blinded_commitments: blinded_commitments.clone(),
-                    },
-                )
-                .await?;
+            let list_key_proofs: Vec<MerkleProof> = fetch_chunked(
+                &blinded_commitments,
+                MAX_MERKLE_PROOFS_PER_REQUEST,
+                |chunk| {
+                    self.call(
+                        "ppoi_merkle_proofs",
+                        GetMerkleProofsParams {
+                            chain: self.chain(),
+                            list_key: list_key.clone(),
+                            blinded_commitments: chunk,
+                        },
+                    )
+                },
+            )
+            .await?;
 
             proofs.insert(list_key.clone(), list_key_proofs);
         }
@@ -274,48 +334,102 @@ impl PoiClient {
         method: &'static str,
         params: P,
     ) -> Result<R, PoiClientError> {
-        call(
-            &self.inner.next_id,
-            &self.inner.http,
-            &self.inner.url,
-            method,
-            params,
-        )
-        .await
+        call(self.inner.transport.as_ref(), method, params).await
     }
 }
 
+/// Serializes the params, sends them through the transport, and deserializes
+/// the result.
 async fn call<P: Serialize, R: DeserializeOwned>(
-    next_id: &AtomicU64,
-    http: &Client,
-    url: &str,
+    transport: &dyn JsonRpcTransport,
     method: &'static str,
     params: P,
 ) -> Result<R, PoiClientError> {
-    let id = next_id.fetch_add(1, Ordering::Relaxed);
-    let req = JsonRpcRequest {
-        jsonrpc: "2.0",
-        method,
-        id,
-        params,
-    };
-
-    info!("Calling RPC method: {}", method);
-    info!("Request: {}", serde_json::to_string(&req).unwrap());
-
-    let resp: JsonRpcResponse<R> = http
-        .post(url)
-        .header("connection", "close")
-        .json(&req)
-        .send()
-        .await?
-        .json()
-        .await?;
+    let params = serde_json::to_value(params)
+        .map_err(|e| PoiClientError::UnexpectedResponse(e.to_string()))?;
+    let result = transport.call(method, params).await?;
+    serde_json::from_value(result).map_err(|e| PoiClientError::UnexpectedResponse(e.to_string()))
+}
+
+impl ReqwestTransport {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_client(Client::new(), url)
+    }
+
+    pub fn with_client(http: Client, url: impl Into<String>) -> Self {
+        Self {
+            http,
+            url: url.into(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "wasm"), async_trait::async_trait)]
+#[cfg_attr(feature = "wasm", async_trait::async_trait(?Send))]
+impl JsonRpcTransport for ReqwestTransport {
+    async fn call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, PoiClientError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let req = JsonRpcRequest {
+            jsonrpc: "2.0",
+            method,
+            id,
+            params,
+        };
+
+        info!("Calling RPC method: {}", method);
+        info!("Request: {}", serde_json::to_string(&req).unwrap());
+
+        let resp: JsonRpcResponse<serde_json::Value> = self
+            .http
+            .post(&self.url)
+            .header("connection", "close")
+            .json(&req)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(err) = resp.error {
+            return Err(PoiClientError::Rpc(err));
+        }
+        resp.result.ok_or(PoiClientError::NullResult)
+    }
+}
 
-    if let Some(err) = resp.error {
-        return Err(PoiClientError::Rpc(err));
+/// Splits `items` into chunks of at most `limit`, fetches each chunk in order,
+/// and concatenates the results.
+///
+/// Results are matched to items by position, so each chunk's response must
+/// contain exactly one result per item.
+async fn fetch_chunked<T, R, F, Fut>(
+    items: &[T],
+    limit: usize,
+    mut fetch: F,
+) -> Result<Vec<R>, PoiClientError>
+where
+    T: Clone,
+    F: FnMut(Vec<T>) -> Fut,
+    Fut: Future<Output = Result<Vec<R>, PoiClientError>>,
+{
+    let mut results = Vec::with_capacity(items.len());
+    for chunk in items.chunks(limit) {
+        let chunk_results = fetch(chunk.to_vec()).await?;
+        if chunk_results.len() != chunk.len() {
+            return Err(PoiClientError::UnexpectedResponse(format!(
+                "expected {} results, got {}",
+                chunk.len(),
+                chunk_results.len()
+            )));
+        }
+        results.extend(chunk_results);
     }
-    resp.result.ok_or(PoiClientError::NullResult)
+
+    Ok(results)
 }
 
 #[cfg_attr(not(feature = "wasm"), async_trait::async_trait)]
@@ -333,6 +447,36 @@ impl MerkleTreeVerifier for PoiClient {
     }
 }
 
+/// A POI node tracking a single list key, that has validated txids up to
+/// `validated_txid_index` and accepts any txid root. Every other request fails.
+#[cfg(test)]
+pub struct TestPoiNode {
+    pub validated_txid_index: u64,
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl JsonRpcTransport for TestPoiNode {
+    async fn call(
+        &self,
+        method: &str,
+        _params: serde_json::Value,
+    ) -> Result<serde_json::Value, PoiClientError> {
+        match method {
+            "ppoi_node_status" => Ok(serde_json::json!({
+                "listKeys": ["efc6ddb59c098a13fb2b618fdae94c1c3a807abc8fb1837c93620c9143ee9e88"],
+                "forNetwork": {},
+            })),
+            "ppoi_validated_txid" => Ok(serde_json::json!({
+                "validatedTxidIndex": self.validated_txid_index,
+                "validatedMerkleroot": format!("{:064x}", 0),
+            })),
+            "ppoi_validate_txid_merkleroot" => Ok(serde_json::json!(true)),
+            _ => Err(PoiClientError::NullResult),
+        }
+    }
+}
+
 impl std::fmt::Display for JsonRpcError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "RPC error {}: {}", self.code, self.message)
@@ -340,3 +484,102 @@ impl std::fmt::Display for JsonRpcError {
 }
 
 impl std::error::Error for JsonRpcError {}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use ruint::aliases::U256;
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    /// Transport that returns scripted responses in order, checking each
+    /// request's method.
+    struct MockTransport {
+        responses: Mutex<VecDeque<(&'static str, serde_json::Value)>>,
+    }
+
+    impl MockTransport {
+        fn new(responses: Vec<(&'static str, serde_json::Value)>) -> Self {
+            let mut all = vec![(
+                "ppoi_node_status",
+                serde_json::json!({ "listKeys": [], "forNetwork": {} }),
+            )];
+            all.extend(responses);
+            Self {
+                responses: Mutex::new(all.into()),
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "wasm"), async_trait::async_trait)]
+    #[cfg_attr(feature = "wasm", async_trait::async_trait(?Send))]
+    impl JsonRpcTransport for MockTransport {
+        async fn call(
+            &self,
+            method: &str,
+            _params: serde_json::Value,
+        ) -> Result<serde_json::Value, PoiClientError> {
+            let (expected, response) = self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("unexpected request");
+            assert_eq!(method, expected);
+            Ok(response)
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_mock_transport() {
+        let transport = MockTransport::new(vec![
+            ("ppoi_validate_txid_merkleroot", serde_json::json!(true)),
+            ("ppoi_validated_txid", serde_json::json!("not a status")),
+        ]);
+        let client = PoiClient::with_transport(transport, 1).await.unwrap();
+
+        let valid = client
+            .validate_txid_merkleroot(0, 10, U256::ZERO.into())
+            .await
+            .unwrap();
+        assert!(valid);
+
+        let status = client.validated_txid().await;
+        assert!(matches!(status, Err(PoiClientError::UnexpectedResponse(_))));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_fetch_chunked() {
+        let items: Vec<u32> = (0..250).collect();
+        let calls = Mutex::new(Vec::new());
+
+        let results = fetch_chunked(&items, MAX_MERKLE_PROOFS_PER_REQUEST, |chunk| {
+            calls.lock().unwrap().push(chunk.len());
+            async move { Ok(chunk.iter().map(|i| i * 2).collect()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec![100, 100, 50]);
+        let expected: Vec<u32> = items.iter().map(|i| i * 2).collect();
+        assert_eq!(results, expected);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_fetch_chunked_length_mismatch() {
+        let items: Vec<u32> = (0..10).collect();
+
+        let result = fetch_chunked(&items, 4, |mut chunk| {
+            chunk.pop();
+            async move { Ok::<_, PoiClientError>(chunk) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(PoiClientError::UnexpectedResponse(_))));
+    }
+}
@@ -1,7 +1,8 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 use ruint::aliases::U256;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::{
     circuit::proof::Proof,
@@ -9,9 +10,20 @@ use crate::{
     railgun::merkle_tree::{MerkleRoot, TxidLeafHash},
 };
 
+/// Identifier of a POI list: 32 bytes, stored as lowercase hex without a `0x`
+/// prefix.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
 pub struct ListKey(String);
 
+#[derive(Debug, Error)]
+pub enum ListKeyError {
+    #[error("List key must be 64 hex characters, got {0}")]
+    InvalidLength(usize),
+    #[error("List key contains non-hex characters: {0}")]
+    InvalidHex(String),
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Eq, Hash)]
 pub struct BlindedCommitment(U256);
 
@@ -117,6 +129,7 @@ pub struct GetPoisPerListParams {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BlindedCommitmentData {
+    #[serde(rename = "type")]
     pub commitment_type: BlindedCommitmentType,
     pub blinded_commitment: BlindedCommitment,
 }
@@ -220,21 +233,41 @@ impl Display for BlindedCommitment {
     }
 }
 
-impl From<String> for ListKey {
-    fn from(value: String) -> Self {
-        ListKey(value)
+impl ListKey {
+    /// Creates a list key without validation.
+    #[cfg(test)]
+    pub(crate) fn new_unchecked(key: &str) -> Self {
+        ListKey(key.to_string())
     }
 }
 
-impl From<ListKey> for String {
-    fn from(value: ListKey) -> Self {
-        value.0
+impl FromStr for ListKey {
+    type Err = ListKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        if hex.len() != 64 {
+            return Err(ListKeyError::InvalidLength(hex.len()));
+        }
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ListKeyError::InvalidHex(s.to_string()));
+        }
+
+        Ok(ListKey(hex.to_ascii_lowercase()))
+    }
+}
+
+impl TryFrom<String> for ListKey {
+    type Error = ListKeyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
     }
 }
 
-impl From<&str> for ListKey {
-    fn from(value: &str) -> Self {
-        ListKey(value.to_string())
+impl From<ListKey> for String {
+    fn from(value: ListKey) -> Self {
+        value.0
     }
 }
 
@@ -259,3 +292,36 @@ impl Serialize for BlindedCommitment {
         serializer.serialize_str(&hex_string)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "efc6ddb59c098a13fb2b618fdae94c1c3a807abc8fb1837c93620c9143ee9e88";
+
+    #[test]
+    fn test_list_key_from_str() {
+        let key: ListKey = KEY.parse().unwrap();
+        let prefixed: ListKey = format!("0x{}", KEY.to_uppercase()).parse().unwrap();
+        assert_eq!(key, prefixed);
+        assert_eq!(String::from(key), KEY);
+
+        assert!(matches!(
+            "abcd".parse::<ListKey>(),
+            Err(ListKeyError::InvalidLength(4))
+        ));
+        assert!(matches!(
+            "z".repeat(64).parse::<ListKey>(),
+            Err(ListKeyError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_list_key_deserialize() {
+        let key: ListKey = serde_json::from_str(&format!("\"{KEY}\"")).unwrap();
+        assert_eq!(key, KEY.parse().unwrap());
+
+        let invalid = serde_json::from_str::<ListKey>("\"test_list_key\"");
+        assert!(invalid.is_err());
+    }
+}
@@ -19,6 +19,14 @@ use crate::{
         transaction::{ShieldBuilder, TransactionBuilder},
     },
 };
+#[cfg(not(feature = "wasm"))]
+use crate::{
+    circuit::native::Groth16Prover,
+    railgun::{
+        indexer::syncer::{ChainedSyncer, RpcSyncer, SubsquidSyncer},
+        merkle_tree::SmartWalletUtxoVerifier,
+    },
+};
 
 /// Provides access to Railgun interactions
 pub struct RailgunProvider {
@@ -38,10 +46,25 @@ pub struct RailgunProviderState {
 pub enum RailgunProviderError {
     #[error("Unsupported chain ID: {0}")]
     UnsupportedChainId(ChainId),
+    #[error("State is for chain ID {state}, but chain config is for chain ID {chain}")]
+    ChainIdMismatch { state: ChainId, chain: ChainId },
     #[error("Utxo indexer error: {0}")]
     UtxoIndexer(#[from] UtxoIndexerError),
 }
 
+impl RailgunProviderState {
+    /// Retargets this state to a different chain, keeping the synced trees and
+    /// matched events. Useful for loading state synced against a chain into a
+    /// fork of it with a different chain ID.
+    ///
+    /// The new chain must share the original chain's railgun deployment,
+    /// otherwise the synced trees won't match the chain's.
+    pub fn retarget_chain(mut self, new_chain: ChainConfig) -> Self {
+        self.chain_id = new_chain.id;
+        self
+    }
+}
+
 /// General provider functions
 impl RailgunProvider {
     pub fn new(
@@ -59,6 +82,28 @@ impl RailgunProvider {
         }
     }
 
+    /// Creates a provider wired up from the chain config's defaults: syncing
+    /// from the chain's subsquid endpoint with an RPC fallback, verifying roots
+    /// against the smart wallet, and proving with the artifacts in
+    /// `chain.artifacts_path`.
+    #[cfg(not(feature = "wasm"))]
+    pub fn for_chain(chain: ChainConfig, provider: DynProvider) -> Self {
+        let (utxo_syncer, utxo_verifier, prover) = default_components(chain, &provider);
+        Self::new(chain, provider, utxo_syncer, utxo_verifier, prover)
+    }
+
+    /// Restores a provider from state using the chain config's defaults. See
+    /// `RailgunProvider::for_chain`.
+    #[cfg(not(feature = "wasm"))]
+    pub fn for_chain_from_state(
+        state: RailgunProviderState,
+        chain: ChainConfig,
+        provider: DynProvider,
+    ) -> Result<Self, RailgunProviderError> {
+        let (utxo_syncer, utxo_verifier, prover) = default_components(chain, &provider);
+        Self::from_state_with_chain(state, chain, provider, utxo_syncer, utxo_verifier, prover)
+    }
+
     pub fn from_state(
         state: RailgunProviderState,
         provider: DynProvider,
@@ -69,6 +114,27 @@ impl RailgunProvider {
         let chain = get_chain_config(state.chain_id)
             .ok_or(RailgunProviderError::UnsupportedChainId(state.chain_id))?;
 
+        Self::from_state_with_chain(state, chain, provider, utxo_syncer, utxo_verifier, prover)
+    }
+
+    /// Restores a provider from state using a custom chain config, for chains
+    /// without a built-in config (e.g. local forks). The state's chain ID must
+    /// match the config's, see `RailgunProviderState::retarget_chain`.
+    pub fn from_state_with_chain(
+        state: RailgunProviderState,
+        chain: ChainConfig,
+        provider: DynProvider,
+        utxo_syncer: Arc<dyn NoteSyncer>,
+        utxo_verifier: Arc<dyn MerkleTreeVerifier>,
+        prover: Arc<dyn TransactProver>,
+    ) -> Result<Self, RailgunProviderError> {
+        if state.chain_id != chain.id {
+            return Err(RailgunProviderError::ChainIdMismatch {
+                state: state.chain_id,
+                chain: chain.id,
+            });
+        }
+
         Ok(Self {
             chain,
             provider,
@@ -130,3 +196,57 @@ impl RailgunProvider {
         Ok(())
     }
 }
+
+#[cfg(not(feature = "wasm"))]
+fn default_components(
+    chain: ChainConfig,
+    provider: &DynProvider,
+) -> (
+    Arc<dyn NoteSyncer>,
+    Arc<dyn MerkleTreeVerifier>,
+    Arc<dyn TransactProver>,
+) {
+    let mut syncers: Vec<Box<dyn NoteSyncer>> = Vec::new();
+    if let Some(endpoint) = chain.subsquid_endpoint {
+        syncers.push(Box::new(SubsquidSyncer::new(endpoint)));
+    }
+    syncers.push(Box::new(RpcSyncer::new(provider.clone(), chain)));
+
+    let utxo_syncer: Arc<dyn NoteSyncer> = Arc::new(ChainedSyncer::new(syncers));
+    let utxo_verifier: Arc<dyn MerkleTreeVerifier> = Arc::new(SmartWalletUtxoVerifier::new(
+        chain.railgun_smart_wallet,
+        provider.clone(),
+    ));
+    let prover: Arc<dyn TransactProver> = Arc::new(Groth16Prover::new_native(chain.artifacts_path));
+
+    (utxo_syncer, utxo_verifier, prover)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::chain_config::MAINNET_CONFIG;
+
+    #[test]
+    fn test_retarget_chain() {
+        let state = RailgunProviderState {
+            chain_id: MAINNET_CONFIG.id,
+            indexer: UtxoIndexerState {
+                utxo_trees: BTreeMap::new(),
+                synced_block: 20_000_000,
+                matched_events: vec![],
+            },
+        };
+
+        let fork = ChainConfig {
+            id: 31337,
+            ..MAINNET_CONFIG
+        };
+        let state = state.retarget_chain(fork);
+
+        assert_eq!(state.chain_id, 31337);
+        assert_eq!(state.indexer.synced_block, 20_000_000);
+    }
+}
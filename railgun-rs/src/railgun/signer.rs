@@ -1,18 +1,27 @@
 use std::{fmt::Debug, sync::Arc};
 
 use ruint::aliases::U256;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    crypto::keys::{SpendingKey, SpendingSignature, ViewingKey},
+    crypto::keys::{
+        MasterPublicKey, SpendingKey, SpendingPublicKey, SpendingSignature, ViewingKey,
+    },
     railgun::address::{ChainId, RailgunAddress},
 };
 
 pub trait Signer: SpendingKeyProvider + ViewingKeyProvider {
     fn sign(&self, inputs: U256) -> SpendingSignature;
     fn address(&self) -> RailgunAddress;
+
+    /// Whether this signer can authorize spends. Watch-only signers can
+    /// decrypt and track notes, but building a transaction from them fails.
+    fn can_spend(&self) -> bool {
+        true
+    }
 }
 pub trait SpendingKeyProvider {
-    fn spending_key(&self) -> SpendingKey;
+    fn spending_public_key(&self) -> SpendingPublicKey;
 }
 
 pub trait ViewingKeyProvider {
@@ -43,11 +52,21 @@ impl PrivateKeySigner {
     pub fn new_evm(spending_key: SpendingKey, viewing_key: ViewingKey, chain_id: u64) -> Arc<Self> {
         Self::new(spending_key, viewing_key, ChainId::EVM(chain_id))
     }
+
+    /// Exports the keys needed to view this account's notes, without the
+    /// spending key.
+    pub fn export_watch_only(&self) -> WatchOnlyAccount {
+        WatchOnlyAccount::new(
+            self.spending_key.public_key(),
+            self.viewing_key,
+            self.chain_id,
+        )
+    }
 }
 
 impl SpendingKeyProvider for PrivateKeySigner {
-    fn spending_key(&self) -> SpendingKey {
-        self.spending_key
+    fn spending_public_key(&self) -> SpendingPublicKey {
+        self.spending_key.public_key()
     }
 }
 
@@ -66,3 +85,94 @@ impl Signer for PrivateKeySigner {
         RailgunAddress::from_private_keys(self.spending_key, self.viewing_key, self.chain_id)
     }
 }
+
+/// Watch-only view of an account, for sharing with third parties (e.g. an
+/// accountant) that should see its shielded activity without being able to
+/// spend.
+///
+/// Holds the viewing key and spending public key, which is enough to decrypt
+/// and track the account's notes. Register it with
+/// `UtxoIndexer::register_watch_only`.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchOnlyAccount {
+    spending_pubkey: SpendingPublicKey,
+    viewing_key: ViewingKey,
+    chain_id: ChainId,
+}
+
+impl WatchOnlyAccount {
+    pub fn new(
+        spending_pubkey: SpendingPublicKey,
+        viewing_key: ViewingKey,
+        chain_id: ChainId,
+    ) -> Self {
+        Self {
+            spending_pubkey,
+            viewing_key,
+            chain_id,
+        }
+    }
+}
+
+impl SpendingKeyProvider for WatchOnlyAccount {
+    fn spending_public_key(&self) -> SpendingPublicKey {
+        self.spending_pubkey
+    }
+}
+
+impl ViewingKeyProvider for WatchOnlyAccount {
+    fn viewing_key(&self) -> ViewingKey {
+        self.viewing_key
+    }
+}
+
+impl Signer for WatchOnlyAccount {
+    /// Panics, since watch-only accounts have no spending key. Transaction
+    /// building checks `can_spend` first and fails before reaching this.
+    fn sign(&self, _inputs: U256) -> SpendingSignature {
+        panic!("Watch-only account {} can't sign", self.address());
+    }
+
+    fn address(&self) -> RailgunAddress {
+        let master_key =
+            MasterPublicKey::new(self.spending_pubkey, self.viewing_key.nullifying_key());
+        RailgunAddress::new(master_key, self.viewing_key.public_key(), self.chain_id)
+    }
+
+    fn can_spend(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+pub fn test_signer() -> Arc<PrivateKeySigner> {
+    use crate::crypto::keys::ByteKey;
+
+    PrivateKeySigner::new_evm(
+        SpendingKey::from_bytes([1u8; 32]),
+        ViewingKey::from_bytes([2u8; 32]),
+        1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::keys::{ByteKey, HexKey};
+
+    #[test]
+    fn test_export_watch_only() {
+        let signer = test_signer();
+        let watch_only = signer.export_watch_only();
+
+        assert_eq!(watch_only.address(), signer.address());
+        assert!(signer.can_spend());
+        assert!(!watch_only.can_spend());
+
+        let json = serde_json::to_string(&watch_only).unwrap();
+        assert!(!json.contains(&SpendingKey::from_bytes([1u8; 32]).to_hex()));
+
+        let decoded: WatchOnlyAccount = serde_json::from_str(&json).unwrap();
+        assert!(decoded == watch_only);
+    }
+}
@@ -1,4 +1,5 @@
 mod gas_estimator;
+mod poi_estimator;
 mod poi_proved_transaction;
 mod proved_transaction;
 mod shield_builder;
@@ -6,10 +7,14 @@ mod transaction_builder;
 mod tx_data;
 
 pub use gas_estimator::GasEstimator;
+pub use poi_estimator::PoiProofEstimator;
 pub use poi_proved_transaction::{
-    PoiProvedOperation, PoiProvedOperationError, PoiProvedTransaction,
+    PoiProvedOperation, PoiProvedOperationError, PoiProvedTransaction, PoiProvedTransactionError,
 };
 pub use proved_transaction::{ProvedOperation, ProvedTransaction};
 pub use shield_builder::ShieldBuilder;
-pub use transaction_builder::{BuildError, TransactionBuilder};
+pub use transaction_builder::{
+    BroadcastChoice, BroadcastPolicy, BuildError, NoteSelectionStrategy, PoiPolicy,
+    TransactionBuilder,
+};
 pub use tx_data::TxData;
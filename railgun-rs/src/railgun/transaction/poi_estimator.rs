@@ -0,0 +1,104 @@
+use web_time::Duration;
+
+use crate::{
+    circuit::inputs::poi_inputs::circuit_size,
+    railgun::{
+        note::{Note, operation::Operation},
+        poi::ListKey,
+        transaction::PoiProvedTransaction,
+    },
+};
+
+/// Estimates how long POI proving will take, from a calibration of the average
+/// proof time for each POI circuit size on the current device.
+///
+/// Proof times vary greatly between native and wasm provers and across devices,
+/// so wallets should calibrate by timing a real proof of each size.
+#[derive(Debug, Clone, Copy)]
+pub struct PoiProofEstimator {
+    /// Average time to prove the small (3x3) POI circuit.
+    pub small_circuit: Duration,
+    /// Average time to prove the large (13x13) POI circuit.
+    pub large_circuit: Duration,
+}
+
+impl PoiProofEstimator {
+    pub fn new(small_circuit: Duration, large_circuit: Duration) -> Self {
+        Self {
+            small_circuit,
+            large_circuit,
+        }
+    }
+
+    /// Estimates the total POI proving time for a planned set of operations,
+    /// with one proof per operation per list key.
+    ///
+    /// ```ignore
+    /// let ops = proved.proved_operations.iter().map(|p| &p.operation);
+    /// let eta = estimator.estimate(ops, &list_keys);
+    /// ```
+    pub fn estimate<'a, N: Note + 'a>(
+        &self,
+        operations: impl IntoIterator<Item = &'a Operation<N>>,
+        list_keys: &[ListKey],
+    ) -> Duration {
+        operations
+            .into_iter()
+            .map(|o| self.proof_time(o) * list_keys.len() as u32)
+            .sum()
+    }
+
+    /// Estimates the remaining POI proving time for a transaction, skipping
+    /// proofs that have already been generated.
+    pub fn estimate_remaining(
+        &self,
+        transaction: &PoiProvedTransaction,
+        list_keys: &[ListKey],
+    ) -> Duration {
+        transaction
+            .operations
+            .iter()
+            .map(|o| self.proof_time(&o.operation) * o.poi_proof_count(list_keys) as u32)
+            .sum()
+    }
+
+    fn proof_time<N: Note>(&self, operation: &Operation<N>) -> Duration {
+        match circuit_size(operation.in_notes.len(), operation.out_notes().len()) {
+            3 => self.small_circuit,
+            _ => self.large_circuit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+    use crate::{
+        caip::AssetId,
+        railgun::{
+            note::utxo::{UtxoNote, test_note},
+            signer::test_signer,
+        },
+    };
+
+    #[test]
+    fn test_estimate() {
+        let estimator = PoiProofEstimator::new(Duration::from_secs(2), Duration::from_secs(10));
+        let asset = AssetId::Erc20(address!("0x1234567890123456789012345678901234567890"));
+        let note = test_note();
+        let signer = test_signer();
+
+        let small = Operation::<UtxoNote>::new_empty(0, signer.clone(), asset);
+        let mut large = Operation::<UtxoNote>::new_empty(0, signer, asset);
+        large.in_notes = vec![note; 4];
+        let list_keys: Vec<ListKey> = vec![
+            "a".repeat(64).parse().unwrap(),
+            "b".repeat(64).parse().unwrap(),
+        ];
+
+        let estimate = estimator.estimate([&small, &large], &list_keys);
+        assert_eq!(estimate, Duration::from_secs(24));
+    }
+}
@@ -9,14 +9,21 @@ use thiserror::Error;
 use crate::{
     abis,
     circuit::{
-        inputs::{PoiCircuitInputs, PoiCircuitInputsError, TransactCircuitInputs},
+        inputs::{
+            PoiCircuitInputs, PoiCircuitInputsError, TransactCircuitInputs,
+            poi_inputs::circuit_size,
+        },
         prover::PoiProver,
     },
+    crypto::railgun_txid::Txid,
     railgun::{
         broadcaster::broadcaster::Fee,
-        merkle_tree::{TxidLeafHash, UtxoMerkleTree},
+        merkle_tree::{TxidLeafHash, UtxoMerkleTree, UtxoTreeIndex},
         note::operation::Operation,
-        poi::{ListKey, PoiNote, PreTransactionPoi},
+        poi::{
+            BlindedCommitment, BlindedCommitmentData, BlindedCommitmentType, ListKey, PoiClient,
+            PoiClientError, PoiNote, PoisPerListMap, PreTransactionPoi,
+        },
         transaction::tx_data::TxData,
     },
 };
@@ -41,9 +48,11 @@ pub struct PoiProvedOperation {
     pub transaction: abis::railgun::Transaction,
     /// POI proofs keyed by list key.
     pub pois: HashMap<ListKey, PreTransactionPoi>,
-    /// The txid for this operation. Computed on first `add_pois` call.
-    pub txid: Option<crate::crypto::railgun_txid::Txid>,
-    /// The leaf hash for this operation. Computed on first `add_pois` call.
+    /// The txid for this operation. Computed on first `add_pois` call, even
+    /// with no list keys.
+    pub txid: Option<Txid>,
+    /// The leaf hash for this operation. Computed on first `add_pois` call,
+    /// even with no list keys.
     pub txid_leaf_hash: Option<TxidLeafHash>,
 }
 
@@ -57,7 +66,139 @@ pub enum PoiProvedOperationError {
     Prover(Box<dyn std::error::Error>),
 }
 
+#[derive(Debug, Error)]
+pub enum PoiProvedTransactionError {
+    #[error("Operation {0} is missing its txid leaf hash")]
+    MissingTxidLeaf(usize),
+    #[error("Operation {0} is missing a POI for list key {1}")]
+    MissingPoi(usize, ListKey),
+    #[error(
+        "Operation {index} POI for list key {list_key} has {actual} blinded commitments, expected {expected}"
+    )]
+    InvalidBlindedCommitments {
+        index: usize,
+        list_key: ListKey,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl PoiProvedTransaction {
+    /// Returns the number of POI proofs still needed across all operations to
+    /// cover the given list keys.
+    pub fn poi_proof_count(&self, list_keys: &[ListKey]) -> usize {
+        self.operations
+            .iter()
+            .map(|o| o.poi_proof_count(list_keys))
+            .sum()
+    }
+
+    /// Performs a cheap structural validation of the POI proofs.
+    ///
+    /// Checks that every operation has a txid leaf hash and a POI for each of
+    /// the required list keys, with the number of blinded commitments expected
+    /// by the POI circuit. Does not validate merkleroots against the POI node.
+    pub fn validate(&self, list_keys: &[ListKey]) -> Result<(), PoiProvedTransactionError> {
+        for (index, operation) in self.operations.iter().enumerate() {
+            if operation.txid_leaf_hash.is_none() {
+                return Err(PoiProvedTransactionError::MissingTxidLeaf(index));
+            }
+
+            let expected = circuit_size(
+                operation.operation.in_notes.len(),
+                operation.operation.out_notes().len(),
+            );
+            for list_key in list_keys {
+                let poi = operation.pois.get(list_key).ok_or_else(|| {
+                    PoiProvedTransactionError::MissingPoi(index, list_key.clone())
+                })?;
+
+                let actual = poi.blinded_commitments_out.len();
+                if actual != expected {
+                    return Err(PoiProvedTransactionError::InvalidBlindedCommitments {
+                        index,
+                        list_key: list_key.clone(),
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the blinded commitments of every operation's outputs. These are
+    /// the keys the POI node tracks the outputs' POI status under.
+    pub fn blinded_commitments_out(&self) -> Vec<BlindedCommitment> {
+        self.operations
+            .iter()
+            .flat_map(|o| o.blinded_commitments_out())
+            .collect()
+    }
+
+    /// Returns the txid leaf hashes of all operations that have POI proofs.
+    pub fn txid_leaf_hashes(&self) -> Vec<TxidLeafHash> {
+        self.operations
+            .iter()
+            .filter_map(|o| o.txid_leaf_hash)
+            .collect()
+    }
+
+    /// Fetches the POI status of each output on every list key tracked by the
+    /// POI node. Outputs become spendable once they're `Valid` on the required
+    /// lists.
+    pub async fn poi_status(
+        &self,
+        poi_client: &PoiClient,
+    ) -> Result<PoisPerListMap, PoiClientError> {
+        let blinded_commitment_datas = self
+            .operations
+            .iter()
+            .flat_map(|o| o.blinded_commitment_datas_out())
+            .collect();
+
+        poi_client
+            .pois(poi_client.list_keys(), blinded_commitment_datas)
+            .await
+    }
+}
+
 impl PoiProvedOperation {
+    /// Returns the blinded commitments of this operation's outputs, excluding
+    /// the zero padding the POI circuit adds.
+    ///
+    /// Blinded commitments don't depend on the list key, so they're taken from
+    /// any of the operation's POIs. Empty if no POIs have been added.
+    pub fn blinded_commitments_out(&self) -> Vec<BlindedCommitment> {
+        let Some(poi) = self.pois.values().next() else {
+            return Vec::new();
+        };
+
+        poi.blinded_commitments_out
+            .iter()
+            .filter(|c| !c.is_zero())
+            .map(|c| (*c).into())
+            .collect()
+    }
+
+    /// Returns the blinded commitments of this operation's outputs, tagged
+    /// with the commitment type the POI node tracks them under.
+    pub fn blinded_commitment_datas_out(&self) -> Vec<BlindedCommitmentData> {
+        tag_blinded_commitments(
+            self.blinded_commitments_out(),
+            self.operation.unshield_note().is_some(),
+        )
+    }
+
+    /// Returns the number of POI proofs still needed to cover the given list keys.
+    pub fn poi_proof_count(&self, list_keys: &[ListKey]) -> usize {
+        list_keys
+            .iter()
+            .filter(|list_key| !self.pois.contains_key(*list_key))
+            .count()
+    }
+
     /// Add POI proofs to this operation for the provided list keys.
     pub async fn add_pois(
         &mut self,
@@ -65,6 +206,22 @@ impl PoiProvedOperation {
         list_keys: &[ListKey],
         utxo_trees: &BTreeMap<u32, UtxoMerkleTree>,
     ) -> Result<(), PoiProvedOperationError> {
+        //? The txid only depends on the transact circuit's public inputs, so
+        //? it's computed up front rather than from a list key's POI inputs.
+        if self.txid_leaf_hash.is_none() {
+            let txid = Txid::new(
+                &self.circuit_inputs.nullifiers,
+                &self.circuit_inputs.commitments_out,
+                self.circuit_inputs.bound_params_hash,
+            );
+            self.txid = Some(txid);
+            self.txid_leaf_hash = Some(TxidLeafHash::new(
+                txid,
+                self.operation.utxo_tree_number,
+                UtxoTreeIndex::PreInclusion,
+            ));
+        }
+
         let utxo_merkle_tree = utxo_trees.get(&self.operation.utxo_tree_number).ok_or(
             PoiProvedOperationError::MissingTree(self.operation.utxo_tree_number),
         )?;
@@ -95,7 +252,7 @@ impl PoiProvedOperation {
                 .collect();
 
             let inputs = PoiCircuitInputs::from_inputs(
-                self.operation.from.spending_key().public_key(),
+                self.operation.from.spending_public_key(),
                 self.operation.from.viewing_key().nullifying_key(),
                 utxo_merkle_tree,
                 self.operation.utxo_tree_number,
@@ -109,12 +266,6 @@ impl PoiProvedOperation {
                 list_key.clone(),
             )?;
 
-            // Store txid_leaf_hash and txid (same for all list keys)
-            if self.txid_leaf_hash.is_none() {
-                self.txid = Some(inputs.txid);
-                self.txid_leaf_hash = Some(inputs.txid_leaf_hash);
-            }
-
             let (proof, public_inputs) = prover
                 .prove_poi(&inputs)
                 .await
@@ -145,3 +296,56 @@ impl Display for PoiProvedOperation {
         )
     }
 }
+
+/// Tags an operation's output blinded commitments with their commitment type.
+/// An operation's unshield, if any, is always its last output.
+fn tag_blinded_commitments(
+    blinded_commitments: Vec<BlindedCommitment>,
+    has_unshield: bool,
+) -> Vec<BlindedCommitmentData> {
+    let unshield_index = has_unshield.then(|| blinded_commitments.len().saturating_sub(1));
+    blinded_commitments
+        .into_iter()
+        .enumerate()
+        .map(|(i, blinded_commitment)| BlindedCommitmentData {
+            commitment_type: if Some(i) == unshield_index {
+                BlindedCommitmentType::Unshield
+            } else {
+                BlindedCommitmentType::Transact
+            },
+            blinded_commitment,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_blinded_commitments() {
+        let blinded_commitments: Vec<BlindedCommitment> =
+            (1..=3).map(|c| U256::from(c).into()).collect();
+
+        let types = |has_unshield| {
+            tag_blinded_commitments(blinded_commitments.clone(), has_unshield)
+                .into_iter()
+                .map(|data| data.commitment_type)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(types(false), vec![BlindedCommitmentType::Transact; 3]);
+        assert_eq!(
+            types(true),
+            vec![
+                BlindedCommitmentType::Transact,
+                BlindedCommitmentType::Transact,
+                BlindedCommitmentType::Unshield,
+            ]
+        );
+
+        let tagged = tag_blinded_commitments(blinded_commitments.clone(), true);
+        let order: Vec<_> = tagged.into_iter().map(|d| d.blinded_commitment).collect();
+        assert_eq!(order, blinded_commitments);
+    }
+}
@@ -10,6 +10,7 @@ use crate::{
 };
 
 /// A transaction that has been proven for railgun.
+#[derive(Clone)]
 pub struct ProvedTransaction {
     /// Transaction data to execute this transaction on-chain in railgun.
     pub tx_data: TxData,
@@ -19,6 +20,7 @@ pub struct ProvedTransaction {
 }
 
 /// A single proved operation.
+#[derive(Clone)]
 pub struct ProvedOperation {
     pub operation: Operation<UtxoNote>,
     pub circuit_inputs: TransactCircuitInputs,
@@ -1,10 +1,11 @@
 use alloy::primitives::U256;
 use alloy_sol_types::SolCall;
+use rand::Rng;
 
 use crate::{
-    abis::railgun::{RailgunSmartWallet, ShieldRequest},
+    abis::railgun::{RailgunSmartWallet, ShieldRequest, TokenData},
     caip::AssetId,
-    chain_config::ChainConfig,
+    chain_config::{ChainConfig, FeeError},
     railgun::{
         address::RailgunAddress,
         note::shield::{ShieldError, create_shield_request},
@@ -15,7 +16,7 @@ use crate::{
 /// Basic builder for constructing shield transactions.
 pub struct ShieldBuilder {
     chain: ChainConfig,
-    shields: Vec<(RailgunAddress, AssetId, u128)>,
+    shields: Vec<(RailgunAddress, TokenData, u128)>,
 }
 
 impl ShieldBuilder {
@@ -28,16 +29,38 @@ impl ShieldBuilder {
 
     /// Adds a shield operation to the transaction builder
     pub fn shield(mut self, recipient: RailgunAddress, asset: AssetId, value: u128) -> Self {
-        self.shields.push((recipient, asset, value));
+        self.shields.push((recipient, asset.into(), value));
         self
     }
 
+    /// Adds a shield operation for raw token data. Allows shielding token types
+    /// that aren't modeled by `AssetId`.
+    pub fn shield_raw(mut self, recipient: RailgunAddress, token: TokenData, value: u128) -> Self {
+        self.shields.push((recipient, token, value));
+        self
+    }
+
+    /// Returns each recipient's net shielded value, after the railgun shield
+    /// fee is deducted.
+    pub fn net_values(&self) -> Result<Vec<(RailgunAddress, u128)>, FeeError> {
+        self.shields
+            .iter()
+            .map(|(recipient, _, value)| Ok((*recipient, self.chain.shield_fee(*value)?.0)))
+            .collect()
+    }
+
     /// Builds the shield transaction. Shield txns must be self-broadcast.
     pub fn build(self) -> Result<TxData, ShieldError> {
+        self.build_with_rng(&mut rand::rng())
+    }
+
+    /// Builds the shield transaction, drawing shield keys and note randoms from
+    /// the given rng. A seeded rng produces reproducible commitments.
+    pub fn build_with_rng<R: Rng>(self, rng: &mut R) -> Result<TxData, ShieldError> {
         let shields = self
             .shields
             .into_iter()
-            .map(|(r, a, v)| create_shield_request(r, a, v, &mut rand::rng()))
+            .map(|(r, t, v)| create_shield_request(r, t, v, rng))
             .collect::<Result<Vec<ShieldRequest>, ShieldError>>()?;
 
         let call = RailgunSmartWallet::shieldCall {
@@ -17,6 +17,7 @@ use std::{
 };
 
 use alloy::primitives::Address;
+use futures::{StreamExt, TryStreamExt, stream};
 use rand::Rng;
 use ruint::aliases::U256;
 use thiserror::Error;
@@ -24,13 +25,12 @@ use tracing::{info, warn};
 
 use crate::{
     abis,
-    caip::AssetId,
+    caip::{AccountId, AccountIdParseError, AssetId},
     chain_config::ChainConfig,
     circuit::{
         inputs::{PoiCircuitInputsError, TransactCircuitInputs, TransactCircuitInputsError},
         prover::{PoiProver, TransactProver},
     },
-    crypto::keys::ViewingPublicKey,
     railgun::{
         address::RailgunAddress,
         broadcaster::broadcaster::Fee,
@@ -44,11 +44,11 @@ use crate::{
             unshield::UnshieldNote,
             utxo::UtxoNote,
         },
-        poi::{ListKey, PoiClient, PoiClientError},
+        poi::{ListKey, PoiClient, PoiClientError, PoiNote},
         signer::Signer,
         transaction::{
             GasEstimator, PoiProvedOperation, PoiProvedOperationError, PoiProvedTransaction,
-            ProvedOperation, ProvedTransaction, TxData,
+            PoiProvedTransactionError, ProvedOperation, ProvedTransaction, TxData,
         },
     },
 };
@@ -57,8 +57,13 @@ use crate::{
 pub struct TransactionBuilder<'a, M = Standard> {
     transfers: Vec<TransferData>,
     unshields: BTreeMap<AssetId, UnshieldData>,
+    consolidations: Vec<ConsolidateData>,
     broadcaster_fee: Option<TransferData>,
-    signers: BTreeMap<ViewingPublicKey, Arc<dyn Signer>>,
+    adapt_contract: Address,
+    adapt_params: Vec<u8>,
+    min_confirmations: u64,
+    note_selection: NoteSelectionStrategy,
+    max_inputs: Option<usize>,
 
     chain: ChainConfig,
     indexer: &'a UtxoIndexer,
@@ -70,7 +75,61 @@ pub struct Standard;
 pub struct WithPoi<'a> {
     poi_client: &'a PoiClient,
     poi_prover: &'a dyn PoiProver,
+    poi_policy: PoiPolicy,
+    list_keys: Option<Vec<ListKey>>,
+}
+
+/// Whether POI proofs are generated when building a transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PoiPolicy {
+    /// POI proofs are generated for all list keys. Any failure fails the build.
+    #[default]
+    Required,
+    /// POI proofs are generated for all list keys. If proving fails, the
+    /// transaction is returned without POI proofs.
+    Optional,
+    /// POI proofs are skipped. Only suitable for self-broadcasting on chains
+    /// that don't enforce POI.
+    Disabled,
 }
+
+/// How input notes are chosen to cover an operation's value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NoteSelectionStrategy {
+    /// Notes are taken in the order they were received.
+    #[default]
+    InOrder,
+    /// Notes without a memo are taken before notes with one, so spending
+    /// doesn't link the transaction to an old memo's context where avoidable.
+    PreferEmptyMemo,
+}
+
+/// How `build_auto` chooses between self-broadcasting and a broadcaster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BroadcastPolicy {
+    /// Compares the estimated self-broadcast gas cost against the broadcaster's
+    /// fee and builds only the cheaper path. `token_per_eth` prices the
+    /// self-broadcast gas in the broadcaster's fee token, in the token's base
+    /// units per ether. Self-broadcasts if no broadcaster is available.
+    Cheapest { token_per_eth: u128 },
+    /// Uses the broadcaster when one is available, so the EOA paying gas isn't
+    /// linked to the transaction. Fails if none is available.
+    MaxPrivacy,
+    /// Always self-broadcasts.
+    SelfOnly,
+    /// Always uses the broadcaster. Fails if none is available.
+    BroadcasterOnly,
+}
+
+/// A transaction built by `build_auto`, tagged with the path it was built for.
+#[derive(Debug)]
+pub enum BroadcastChoice {
+    /// Submit `tx_data` from your own EOA.
+    SelfBroadcast(PoiProvedTransaction),
+    /// Submit through the broadcaster whose fee is attached.
+    Broadcaster(PoiProvedTransaction),
+}
+
 pub struct WithBroadcast<'a> {
     poi_client: &'a PoiClient,
     poi_prover: &'a dyn PoiProver,
@@ -79,6 +138,8 @@ pub struct WithBroadcast<'a> {
     fee: Fee,
 }
 
+/// Transfer and unshield data hold a shared handle to their signer, so key
+/// material stays inside the signer rather than being copied into the builder.
 #[derive(Clone)]
 struct TransferData {
     pub from: Arc<dyn Signer>,
@@ -86,6 +147,14 @@ struct TransferData {
     pub asset: AssetId,
     pub value: u128,
     pub memo: String,
+    pub reveal_sender: bool,
+}
+
+#[derive(Clone)]
+struct ConsolidateData {
+    pub from: Arc<dyn Signer>,
+    pub asset: AssetId,
+    pub max_inputs: usize,
 }
 
 #[derive(Clone)]
@@ -100,6 +169,15 @@ struct UnshieldData {
 pub enum BuildError {
     #[error("Multiple unshield operations are not supported")]
     MultipleUnshields,
+    #[error("Adapt params must be 32 bytes, got {0}")]
+    InvalidAdaptParams(usize),
+    #[error("Insufficient balance for {asset} in {from}: needed {needed}, available {available}")]
+    InsufficientBalance {
+        from: RailgunAddress,
+        asset: AssetId,
+        needed: u128,
+        available: u128,
+    },
     #[error("Encryption error: {0}")]
     Encryption(#[from] EncryptError),
     #[error("Prover error: {0}")]
@@ -120,9 +198,58 @@ pub enum BuildError {
     PoiProvedOperation(#[from] PoiProvedOperationError),
     #[error("Invalid POI merkleroot for list key {0}: {1}")]
     InvalidPoiMerkleroot(ListKey, MerkleRoot),
+    #[error("POI validation error: {0}")]
+    PoiValidation(#[from] PoiProvedTransactionError),
+    #[error("No {asset} notes to consolidate for {from}")]
+    NothingToConsolidate {
+        from: RailgunAddress,
+        asset: AssetId,
+    },
+    #[error(
+        "Covering the value needs {needed} input notes, more than the maximum of {max}. Consolidate notes first"
+    )]
+    TooManyInputsRequired { needed: usize, max: usize },
+    #[error("Max inputs {0} exceeds the largest circuit's {max} inputs", max = MAX_CIRCUIT_INPUTS)]
+    MaxInputsTooLarge(usize),
+    #[error("No broadcaster available")]
+    NoBroadcaster,
+    #[error("Can't spend from watch-only account {0}")]
+    WatchOnly(RailgunAddress),
+    #[error("Failed to build {asset} operation from {from}: {source}")]
+    Operation {
+        from: RailgunAddress,
+        asset: AssetId,
+        source: Box<BuildError>,
+    },
+}
+
+impl BuildError {
+    /// Wraps this error with the sender and asset of the operation it occurred
+    /// in.
+    fn in_operation<N>(self, operation: &Operation<N>) -> Self {
+        BuildError::Operation {
+            from: operation.from.address(),
+            asset: operation.asset,
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the underlying error, without any operation context.
+    pub fn root(&self) -> &BuildError {
+        match self {
+            BuildError::Operation { source, .. } => source.root(),
+            e => e,
+        }
+    }
 }
 
 const FEE_BUFFER: f64 = 1.3;
+/// Gas assumed for a transaction before it's proved and can be estimated.
+const INITIAL_GAS_ESTIMATE: u128 = 1_000_000;
+/// Maximum number of operations proved concurrently when building a transaction.
+const MAX_CONCURRENT_PROOFS: usize = 4;
+/// Input count of the largest railgun transact circuit.
+pub const MAX_CIRCUIT_INPUTS: usize = 13;
 
 impl<'a> TransactionBuilder<'a, Standard> {
     pub fn new(
@@ -133,8 +260,13 @@ impl<'a> TransactionBuilder<'a, Standard> {
         Self {
             transfers: Vec::new(),
             unshields: BTreeMap::new(),
+            consolidations: Vec::new(),
             broadcaster_fee: None,
-            signers: BTreeMap::new(),
+            adapt_contract: Address::ZERO,
+            adapt_params: vec![0u8; 32],
+            min_confirmations: 0,
+            note_selection: NoteSelectionStrategy::default(),
+            max_inputs: None,
             indexer,
             prover,
             chain,
@@ -148,8 +280,13 @@ impl<'a, M> TransactionBuilder<'a, M> {
         TransactionBuilder {
             transfers: self.transfers.clone(),
             unshields: self.unshields.clone(),
+            consolidations: self.consolidations.clone(),
             broadcaster_fee: self.broadcaster_fee.clone(),
-            signers: self.signers.clone(),
+            adapt_contract: self.adapt_contract,
+            adapt_params: self.adapt_params.clone(),
+            min_confirmations: self.min_confirmations,
+            note_selection: self.note_selection,
+            max_inputs: self.max_inputs,
             indexer: self.indexer,
             prover: self.prover,
             chain: self.chain,
@@ -165,14 +302,21 @@ impl<'a, M> TransactionBuilder<'a, M> {
         TransactionBuilder {
             transfers: self.transfers,
             unshields: self.unshields,
+            consolidations: self.consolidations,
             broadcaster_fee: self.broadcaster_fee,
-            signers: self.signers,
+            adapt_contract: self.adapt_contract,
+            adapt_params: self.adapt_params,
+            min_confirmations: self.min_confirmations,
+            note_selection: self.note_selection,
+            max_inputs: self.max_inputs,
             indexer: self.indexer,
             prover: self.prover,
             chain: self.chain,
             mode: WithPoi {
                 poi_client,
                 poi_prover,
+                poi_policy: PoiPolicy::default(),
+                list_keys: None,
             },
         }
     }
@@ -188,8 +332,13 @@ impl<'a, M> TransactionBuilder<'a, M> {
         TransactionBuilder {
             transfers: self.transfers,
             unshields: self.unshields,
+            consolidations: self.consolidations,
             broadcaster_fee: self.broadcaster_fee,
-            signers: self.signers,
+            adapt_contract: self.adapt_contract,
+            adapt_params: self.adapt_params,
+            min_confirmations: self.min_confirmations,
+            note_selection: self.note_selection,
+            max_inputs: self.max_inputs,
             indexer: self.indexer,
             prover: self.prover,
             chain: self.chain,
@@ -203,6 +352,49 @@ impl<'a, M> TransactionBuilder<'a, M> {
         }
     }
 
+    /// Sets the adapt contract and params bound to every operation's proof.
+    ///
+    /// The params must be exactly 32 bytes, otherwise building fails with
+    /// `BuildError::InvalidAdaptParams`.
+    pub fn with_adapt(mut self, contract: Address, params: &[u8]) -> Self {
+        self.adapt_contract = contract;
+        self.adapt_params = params.to_vec();
+        self
+    }
+
+    /// Only spends notes with at least `min_confirmations` confirmations,
+    /// guarding against recently-created notes being invalidated by a reorg.
+    /// Defaults to 0, spending every unspent note.
+    pub fn with_min_confirmations(mut self, min_confirmations: u64) -> Self {
+        self.min_confirmations = min_confirmations;
+        self
+    }
+
+    /// Sets how input notes are selected. Defaults to
+    /// `NoteSelectionStrategy::InOrder`.
+    pub fn with_note_selection(mut self, strategy: NoteSelectionStrategy) -> Self {
+        self.note_selection = strategy;
+        self
+    }
+
+    /// Caps the number of input notes per operation. Larger input counts need
+    /// bigger circuits and more gas, so building fails with
+    /// `BuildError::TooManyInputsRequired` instead of exceeding the cap.
+    ///
+    /// Fails with `BuildError::MaxInputsTooLarge` if `max_inputs` exceeds
+    /// `MAX_CIRCUIT_INPUTS`, since no circuit could prove that many inputs.
+    pub fn with_max_inputs(mut self, max_inputs: usize) -> Result<Self, BuildError> {
+        if max_inputs > MAX_CIRCUIT_INPUTS {
+            return Err(BuildError::MaxInputsTooLarge(max_inputs));
+        }
+
+        self.max_inputs = Some(max_inputs);
+        Ok(self)
+    }
+
+    /// Adds a private transfer. If `reveal_sender` is set, the recipient can
+    /// see the sender's address, e.g. to match a payment to an invoice.
+    /// Otherwise the sender is blinded.
     pub fn transfer(
         mut self,
         from: Arc<dyn Signer>,
@@ -210,21 +402,59 @@ impl<'a, M> TransactionBuilder<'a, M> {
         asset: AssetId,
         value: u128,
         memo: &str,
+        reveal_sender: bool,
     ) -> Self {
-        self.signers
-            .insert(from.viewing_key().public_key(), from.clone());
-
         let transfer_data = TransferData {
             from,
             to,
             asset,
             value,
             memo: memo.to_string(),
+            reveal_sender,
         };
         self.transfers.push(transfer_data);
         self
     }
 
+    /// Sends `value` of `asset` to `to`, which may be a railgun address
+    /// (`0zk...`) or an EVM address (`0x...` or CAIP-10 `eip155:<chainId>:0x...`).
+    /// Railgun recipients receive a private transfer, and EVM recipients an
+    /// unshield. Unshields don't carry a memo, so `memo` is ignored for them.
+    /// Transfers are sent with a blinded sender.
+    ///
+    /// Fails if `to` can't be parsed, or is a CAIP-10 account on a different
+    /// chain.
+    pub fn send(
+        self,
+        from: Arc<dyn Signer>,
+        to: &str,
+        asset: AssetId,
+        value: u128,
+        memo: &str,
+    ) -> Result<Self, AccountIdParseError> {
+        let builder = match AccountId::parse_for_chain(to, self.chain.id)? {
+            AccountId::Railgun(to) => self.transfer(from, to, asset, value, memo, false),
+            AccountId::Eip155(to) => self.set_unshield(from, to, asset, value),
+        };
+        Ok(builder)
+    }
+
+    /// Spends up to `max_inputs` of `from`'s smallest `asset` notes back to
+    /// `from` as a single note, reducing the inputs needed by later
+    /// transactions.
+    ///
+    /// Consolidated notes aren't used by the builder's other operations.
+    /// Building fails with `BuildError::MaxInputsTooLarge` if `max_inputs`
+    /// exceeds `MAX_CIRCUIT_INPUTS`.
+    pub fn consolidate(mut self, from: Arc<dyn Signer>, asset: AssetId, max_inputs: usize) -> Self {
+        self.consolidations.push(ConsolidateData {
+            from,
+            asset,
+            max_inputs,
+        });
+        self
+    }
+
     pub fn set_unshield(
         mut self,
         from: Arc<dyn Signer>,
@@ -232,9 +462,6 @@ impl<'a, M> TransactionBuilder<'a, M> {
         asset: AssetId,
         value: u128,
     ) -> Self {
-        self.signers
-            .insert(from.viewing_key().public_key(), from.clone());
-
         let unshield_data = UnshieldData {
             from,
             to,
@@ -258,7 +485,7 @@ impl<'a> TransactionBuilder<'a, Standard> {
     /// The resulting transaction can be self-broadcasted, but does not include
     /// any POI proofs.
     pub async fn build<R: Rng>(self, rng: &mut R) -> Result<TxData, BuildError> {
-        let in_notes = self.indexer.all_unspent();
+        let in_notes = self.spendable_notes();
         let operations = self.build_operations(in_notes, rng)?;
 
         let proved = self
@@ -274,17 +501,62 @@ impl<'a> TransactionBuilder<'a, Standard> {
 
         Ok(proved.tx_data)
     }
+
+    /// Builds and proves a transaction for railgun, split across as many EVM
+    /// transactions as needed to keep each one's estimated gas below `max_gas`.
+    ///
+    /// Useful for large batches that would otherwise exceed the block gas
+    /// limit. The resulting transactions can be self-broadcasted in any order.
+    pub async fn build_packed<R: Rng>(
+        self,
+        max_gas: u128,
+        rng: &mut R,
+    ) -> Result<Vec<TxData>, BuildError> {
+        let in_notes = self.spendable_notes();
+        let operations = self.build_operations(in_notes, rng)?;
+
+        let proved = self
+            .prove_operations(
+                self.prover,
+                &self.indexer.utxo_trees,
+                &operations,
+                self.chain,
+                0,
+                rng,
+            )
+            .await?;
+
+        let transactions = proved
+            .proved_operations
+            .into_iter()
+            .map(|po| po.transaction)
+            .collect();
+        Ok(TxData::pack_transactions(
+            self.chain.railgun_smart_wallet,
+            transactions,
+            max_gas,
+        ))
+    }
 }
 
 impl<'a> TransactionBuilder<'a, WithPoi<'a>> {
-    /// Builds and proves a transaction for railgun.
-    ///
-    /// The resulting transaction can be self-broadcasted, but does not include
-    /// any POI proofs.
+    /// Sets the POI policy for this transaction. Defaults to `PoiPolicy::Required`.
+    pub fn with_poi_policy(mut self, policy: PoiPolicy) -> Self {
+        self.mode.poi_policy = policy;
+        self
+    }
+
+    /// Restricts POI proving to the given list keys. By default, proofs are
+    /// generated for every list key reported by the POI node.
+    pub fn with_list_keys(mut self, list_keys: Vec<ListKey>) -> Self {
+        self.mode.list_keys = Some(list_keys);
+        self
+    }
 
-    /// Builds and proves a transaction for railgun with POI proofs.
+    /// Builds and proves a transaction for railgun with POI proofs, according
+    /// to the builder's `PoiPolicy`.
     pub async fn build<R: Rng>(&self, rng: &mut R) -> Result<PoiProvedTransaction, BuildError> {
-        let in_notes = self.indexer.all_unspent();
+        let in_notes = self.spendable_notes();
         let operations = self.build_operations(in_notes, rng)?;
 
         let proved = self
@@ -298,16 +570,100 @@ impl<'a> TransactionBuilder<'a, WithPoi<'a>> {
             )
             .await?;
 
-        let list_keys = self.mode.poi_client.list_keys();
-        self.prove_poi(
-            self.mode.poi_prover,
-            &self.mode.poi_client,
-            proved,
-            &self.indexer.utxo_trees,
-            &list_keys,
-            None,
-        )
-        .await
+        let list_keys = match self.mode.poi_policy {
+            PoiPolicy::Required | PoiPolicy::Optional => match &self.mode.list_keys {
+                Some(list_keys) => list_keys.clone(),
+                None => self.mode.poi_client.list_keys(),
+            },
+            //? With no list keys, no POI proofs are fetched or generated
+            PoiPolicy::Disabled => vec![],
+        };
+
+        let result = self
+            .prove_poi(
+                self.mode.poi_prover,
+                &self.mode.poi_client,
+                proved.clone(),
+                &self.indexer.utxo_trees,
+                &list_keys,
+                None,
+            )
+            .await;
+
+        match (result, self.mode.poi_policy) {
+            (Err(e), PoiPolicy::Optional) => {
+                warn!("POI proving failed, continuing without POI: {}", e);
+                self.prove_poi(
+                    self.mode.poi_prover,
+                    &self.mode.poi_client,
+                    proved,
+                    &self.indexer.utxo_trees,
+                    &[],
+                    None,
+                )
+                .await
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Builds the transaction for either self-broadcasting or a broadcaster,
+    /// chosen by `policy`. `fee` is the best broadcaster's offer for the fee
+    /// token, if any broadcaster is available.
+    ///
+    /// Self-broadcasts use the builder's `PoiPolicy`. Broadcasts are built as
+    /// with `with_broadcast`.
+    pub async fn build_auto<R: Rng>(
+        &self,
+        estimator: &'a dyn GasEstimator,
+        fee_payer: Arc<dyn Signer>,
+        fee: Option<Fee>,
+        policy: BroadcastPolicy,
+        rng: &mut R,
+    ) -> Result<BroadcastChoice, BuildError> {
+        let fee = match (policy, fee) {
+            (BroadcastPolicy::SelfOnly, _) => None,
+            (BroadcastPolicy::BroadcasterOnly | BroadcastPolicy::MaxPrivacy, None) => {
+                return Err(BuildError::NoBroadcaster);
+            }
+            (_, fee) => fee,
+        };
+        let Some(fee) = fee else {
+            return Ok(BroadcastChoice::SelfBroadcast(self.build(rng).await?));
+        };
+
+        if let BroadcastPolicy::Cheapest { token_per_eth } = policy {
+            //? Gas can only be estimated for a proved transaction, so both
+            //? paths are priced at the same up-front estimate before proving.
+            let gas_price_wei = estimator
+                .gas_price_wei()
+                .await
+                .map_err(BuildError::Estimator)?;
+            let self_cost = gas_cost_in_token(INITIAL_GAS_ESTIMATE, gas_price_wei, token_per_eth);
+            let broadcaster_cost =
+                calculate_fee(INITIAL_GAS_ESTIMATE, gas_price_wei, fee.per_unit_gas);
+            info!(
+                "Self-broadcast cost: {}, broadcaster cost: {}",
+                self_cost, broadcaster_cost
+            );
+
+            if self_cost <= broadcaster_cost {
+                return Ok(BroadcastChoice::SelfBroadcast(self.build(rng).await?));
+            }
+        }
+
+        let broadcast = self
+            .standard()
+            .with_broadcast(
+                self.mode.poi_client,
+                self.mode.poi_prover,
+                estimator,
+                fee_payer,
+                fee,
+            )
+            .build(rng)
+            .await?;
+        Ok(BroadcastChoice::Broadcaster(broadcast))
     }
 }
 
@@ -317,7 +673,7 @@ impl<'a> TransactionBuilder<'a, WithBroadcast<'a>> {
     /// Calculates the broadcaster fee iteratively, proves the transaction,
     /// and generates POI proofs.
     pub async fn build<R: Rng>(&self, rng: &mut R) -> Result<PoiProvedTransaction, BuildError> {
-        let in_notes = self.indexer.all_unspent();
+        let in_notes = self.spendable_notes();
 
         let proved = calculate_fee_to_convergence(
             self.standard(),
@@ -352,18 +708,32 @@ impl<'a, M> TransactionBuilder<'a, M> {
         asset: AssetId,
         value: u128,
     ) {
-        self.signers
-            .insert(from.viewing_key().public_key(), from.clone());
         let fee_data = TransferData {
             from,
             to,
             asset,
             value,
             memo: "fee".to_string(),
+            reveal_sender: false,
         };
         self.broadcaster_fee = Some(fee_data);
     }
 
+    /// Returns the unspent notes eligible to be spent by this builder.
+    fn spendable_notes(&self) -> Vec<UtxoNote> {
+        self.indexer.all_unspent_confirmed(self.min_confirmations)
+    }
+
+    /// Returns the validated adapt contract and params.
+    fn adapt(&self) -> Result<(Address, [u8; 32]), BuildError> {
+        let params: [u8; 32] = self
+            .adapt_params
+            .as_slice()
+            .try_into()
+            .map_err(|_| BuildError::InvalidAdaptParams(self.adapt_params.len()))?;
+        Ok((self.adapt_contract, params))
+    }
+
     /// Proves the operations and returns a proved transaction that can be
     /// executed in railgun on-chain.
     async fn prove_operations<R: Rng>(
@@ -375,14 +745,15 @@ impl<'a, M> TransactionBuilder<'a, M> {
         min_gas_price: u128,
         rng: &mut R,
     ) -> Result<ProvedTransaction, BuildError> {
+        let (adapt_contract, adapt_params) = self.adapt()?;
         let tx_results = create_transactions(
             prover,
             utxo_trees,
             operations,
             chain,
             min_gas_price,
-            Address::ZERO,
-            &[0u8; 32],
+            adapt_contract,
+            &adapt_params,
             rng,
         )
         .await?;
@@ -419,9 +790,34 @@ impl<'a, M> TransactionBuilder<'a, M> {
     /// Creates change notes when input value exceeds output value.
     fn build_operations<R: Rng>(
         &self,
-        in_notes: Vec<UtxoNote>,
+        mut in_notes: Vec<UtxoNote>,
         rng: &mut R,
     ) -> Result<Vec<Operation<UtxoNote>>, BuildError> {
+        check_can_spend(
+            self.transfers
+                .iter()
+                .map(|t| &t.from)
+                .chain(self.unshields.values().map(|u| &u.from))
+                .chain(self.broadcaster_fee.iter().map(|f| &f.from))
+                .chain(self.consolidations.iter().map(|c| &c.from)),
+        )?;
+
+        //? Consolidations get their own operations, and their notes are
+        //? excluded from selection for the other operations.
+        let mut consolidations = Vec::new();
+        for consolidation in &self.consolidations {
+            let mut operation =
+                Operation::new_empty(0, consolidation.from.clone(), consolidation.asset);
+            operation.in_notes = select_consolidation_notes(
+                consolidation.from.address(),
+                consolidation.asset,
+                consolidation.max_inputs,
+                &in_notes,
+            )?;
+            in_notes.retain(|n| !operation.in_notes.contains(n));
+            consolidations.push(operation);
+        }
+
         //? Collect all output notes into draft operations, grouped by (from_address, asset_id).
         let mut draft_operations: HashMap<(RailgunAddress, AssetId), Operation<UtxoNote>> =
             HashMap::new();
@@ -434,14 +830,17 @@ impl<'a, M> TransactionBuilder<'a, M> {
                     transfer.asset,
                 ))
                 .out_notes
-                .push(TransferNote::new(
-                    transfer.from.viewing_key(),
-                    transfer.to,
-                    transfer.asset,
-                    transfer.value,
-                    rng.random(),
-                    &transfer.memo,
-                ));
+                .push(
+                    TransferNote::new(
+                        transfer.from.viewing_key(),
+                        transfer.to,
+                        transfer.asset,
+                        transfer.value,
+                        rng.random(),
+                        &transfer.memo,
+                    )
+                    .with_revealed_sender(transfer.reveal_sender),
+                );
         }
 
         for unshield in self.unshields.values() {
@@ -474,27 +873,31 @@ impl<'a, M> TransactionBuilder<'a, M> {
         }
 
         //? Collect input notes to satisfy each operation's output value.
-        draft_operations.values_mut().for_each(|o| {
-            o.in_notes = select_in_notes(o.from.address(), o.asset, o.out_value(), in_notes.clone())
-        });
+        for o in draft_operations.values_mut() {
+            o.in_notes = select_in_notes(
+                o.from.address(),
+                o.asset,
+                o.out_value(),
+                in_notes.clone(),
+                self.note_selection,
+                self.max_inputs,
+            )?;
+        }
 
-        //? Split operations by tree number and add change notes if necessary.
-        let operations: Vec<_> = draft_operations
+        //? Split operations by tree number, then sort before adding change
+        //? notes so the rng is consumed in a deterministic order.
+        let mut operations: Vec<_> = draft_operations
             .into_values()
+            .chain(consolidations)
             .flat_map(|o| split_trees(o))
             .collect();
-        let mut operations: Vec<_> = operations
+        sort_operations(&mut operations);
+
+        let operations: Vec<_> = operations
             .into_iter()
             .map(|o| add_change_note(o, rng))
             .collect();
 
-        //? Sort the operations to bring the fee note to the front if it exists
-        operations.sort_by(|a, b| {
-            let a_fee = a.fee_note().is_some();
-            let b_fee = b.fee_note().is_some();
-            b_fee.cmp(&a_fee) // fee note first
-        });
-
         Ok(operations)
     }
 
@@ -513,8 +916,18 @@ impl<'a, M> TransactionBuilder<'a, M> {
         let mut poi_operations = Vec::new();
         for operation in proved_operations {
             let op = operation.operation;
-            let in_notes = op.in_notes;
-            let poi_in_notes = poi_client.note_to_poi_note(in_notes, list_keys).await?;
+            //? With no list keys there are no merkle proofs to fetch
+            let poi_in_notes = if list_keys.is_empty() {
+                op.in_notes
+                    .iter()
+                    .map(|n| PoiNote::new(n.clone(), HashMap::new()))
+                    .collect()
+            } else {
+                poi_client
+                    .note_to_poi_note(op.in_notes.clone(), list_keys)
+                    .await
+                    .map_err(|e| BuildError::from(e).in_operation(&op))?
+            };
 
             //? Need to create a new operation since the generic can't be
             //? trivially cast.
@@ -538,13 +951,24 @@ impl<'a, M> TransactionBuilder<'a, M> {
 
         // Attach POI proofs to each operation
         for poi_op in poi_operations.iter_mut() {
-            poi_op.add_pois(poi_prover, list_keys, utxo_trees).await?;
+            poi_op
+                .add_pois(poi_prover, list_keys, utxo_trees)
+                .await
+                .map_err(|e| BuildError::from(e).in_operation(&poi_op.operation))?;
         }
 
+        let transaction = PoiProvedTransaction {
+            tx_data: proved.tx_data,
+            operations: poi_operations,
+            min_gas_price: proved.min_gas_price,
+            fee,
+        };
+        transaction.validate(list_keys)?;
+
         // Validate all POI merkle roots
         //? Should always pass, but sanity check to ensure proofs are valid before broadcasting
         #[cfg(debug_assertions)]
-        for poi_op in poi_operations.iter() {
+        for poi_op in transaction.operations.iter() {
             for (list_key, poi) in poi_op.pois.iter() {
                 for merkleroot in &poi.poi_merkleroots {
                     let valid = poi_client
@@ -560,26 +984,31 @@ impl<'a, M> TransactionBuilder<'a, M> {
             }
         }
 
-        Ok(PoiProvedTransaction {
-            tx_data: proved.tx_data,
-            operations: poi_operations,
-            min_gas_price: proved.min_gas_price,
-            fee,
-        })
+        Ok(transaction)
     }
 }
 
 /// Selects input notes for an operation.
+///
+/// If the strategy's selection exceeds `max_inputs`, falls back to the largest
+/// notes, which cover the value with the fewest inputs.
 fn select_in_notes<N: IncludedNote + Clone>(
     from: RailgunAddress,
     asset: AssetId,
     value: u128,
-    in_notes: Vec<N>,
-) -> Vec<N> {
+    mut in_notes: Vec<N>,
+    strategy: NoteSelectionStrategy,
+    max_inputs: Option<usize>,
+) -> Result<Vec<N>, BuildError> {
+    if strategy == NoteSelectionStrategy::PreferEmptyMemo {
+        //? Stable, so notes keep their received order within each group.
+        in_notes.sort_by_key(|n| !n.memo().is_empty());
+    }
+
     //? Naive implementation: just takes notes until we have enough value.
     let mut selected = Vec::new();
     let mut total = 0;
-    for note in in_notes {
+    for note in in_notes.iter() {
         if note.viewing_pubkey() == from.viewing_pubkey() && note.asset() == asset {
             selected.push(note.clone());
             total += note.value();
@@ -589,7 +1018,94 @@ fn select_in_notes<N: IncludedNote + Clone>(
         }
     }
 
-    selected
+    if total < value {
+        return Err(BuildError::InsufficientBalance {
+            from,
+            asset,
+            needed: value,
+            available: total,
+        });
+    }
+
+    let Some(max) = max_inputs else {
+        return Ok(selected);
+    };
+    if selected.len() <= max {
+        return Ok(selected);
+    }
+
+    let mut largest: Vec<N> = in_notes
+        .into_iter()
+        .filter(|n| n.viewing_pubkey() == from.viewing_pubkey() && n.asset() == asset)
+        .collect();
+    largest.sort_by_key(|n| std::cmp::Reverse(n.value()));
+
+    let mut total = 0;
+    let needed = largest
+        .iter()
+        .position(|n| {
+            total += n.value();
+            total >= value
+        })
+        .map_or(largest.len(), |i| i + 1);
+    if needed > max {
+        return Err(BuildError::TooManyInputsRequired { needed, max });
+    }
+
+    largest.truncate(needed);
+    Ok(largest)
+}
+
+/// Selects up to `max_inputs` of the smallest notes for a consolidation. Notes
+/// are taken from the tree holding the most eligible notes, since an operation
+/// can only spend notes from one tree.
+fn select_consolidation_notes<N: IncludedNote + Clone>(
+    from: RailgunAddress,
+    asset: AssetId,
+    max_inputs: usize,
+    in_notes: &[N],
+) -> Result<Vec<N>, BuildError> {
+    if max_inputs > MAX_CIRCUIT_INPUTS {
+        return Err(BuildError::MaxInputsTooLarge(max_inputs));
+    }
+
+    let eligible: Vec<&N> = in_notes
+        .iter()
+        .filter(|n| n.viewing_pubkey() == from.viewing_pubkey() && n.asset() == asset)
+        .collect();
+
+    let mut counts: BTreeMap<u32, usize> = BTreeMap::new();
+    for note in &eligible {
+        *counts.entry(note.tree_number()).or_insert(0) += 1;
+    }
+    //? Ties go to the lowest tree number, since `max_by_key` keeps the last max
+    let Some((tree_number, _)) = counts.into_iter().rev().max_by_key(|(_, count)| *count) else {
+        return Err(BuildError::NothingToConsolidate { from, asset });
+    };
+
+    let mut selected: Vec<N> = eligible
+        .into_iter()
+        .filter(|n| n.tree_number() == tree_number)
+        .cloned()
+        .collect();
+    selected.sort_by_key(|n| n.value());
+    selected.truncate(max_inputs);
+
+    if selected.is_empty() {
+        return Err(BuildError::NothingToConsolidate { from, asset });
+    }
+
+    Ok(selected)
+}
+
+/// Fails if any of the signers is watch-only.
+fn check_can_spend<'s>(
+    signers: impl IntoIterator<Item = &'s Arc<dyn Signer>>,
+) -> Result<(), BuildError> {
+    match signers.into_iter().find(|s| !s.can_spend()) {
+        Some(signer) => Err(BuildError::WatchOnly(signer.address())),
+        None => Ok(()),
+    }
 }
 
 /// Splits an operation into multiple operations by tree number if the input notes
@@ -618,6 +1134,19 @@ fn split_trees<N: IncludedNote>(operation: Operation<N>) -> Vec<Operation<N>> {
     }]
 }
 
+/// Sorts operations into a stable order: the operation with the fee note first,
+/// then by tree number, asset, and sender address.
+fn sort_operations<N>(operations: &mut [Operation<N>]) {
+    operations.sort_by_key(|o| {
+        (
+            o.fee_note.is_none(),
+            o.utxo_tree_number,
+            o.asset,
+            o.from.address(),
+        )
+    });
+}
+
 /// Adds a change note to the operation if required. The change note sends any
 /// excess consumed value back to the sender's address.
 fn add_change_note<R: Rng, N: IncludedNote + Clone>(
@@ -666,7 +1195,8 @@ async fn calculate_fee_to_convergence<R: Rng>(
         .map_err(BuildError::Estimator)?;
 
     let mut fee_builder = builder;
-    let mut last_fee: u128 = calculate_fee(1000000, gas_price_wei, fee.per_unit_gas);
+    let (adapt_contract, adapt_params) = fee_builder.adapt()?;
+    let mut last_fee: u128 = calculate_fee(INITIAL_GAS_ESTIMATE, gas_price_wei, fee.per_unit_gas);
     fee_builder.set_broadcaster_fee(
         fee_payer.clone(),
         fee.recipient.clone(),
@@ -685,8 +1215,8 @@ async fn calculate_fee_to_convergence<R: Rng>(
             &operations,
             chain,
             0,
-            Address::ZERO,
-            &[0u8; 32],
+            adapt_contract,
+            &adapt_params,
             rng,
         )
         .await?;
@@ -739,6 +1269,11 @@ async fn calculate_fee_to_convergence<R: Rng>(
 }
 
 /// Creates a list of railgun transactions for a list of operations.
+///
+/// Operations are proved concurrently, up to `MAX_CONCURRENT_PROOFS` at a
+/// time. Provers that offload work to other threads (e.g. `Groth16Prover`,
+/// which proves on tokio's blocking pool) prove them in parallel.
+/// Transactions are returned in the same order as the operations.
 async fn create_transactions<R: Rng>(
     prover: &dyn TransactProver,
     utxo_trees: &BTreeMap<u32, UtxoMerkleTree>,
@@ -749,17 +1284,20 @@ async fn create_transactions<R: Rng>(
     adapt_input: &[u8; 32],
     rng: &mut R,
 ) -> Result<Vec<(TransactCircuitInputs, abis::railgun::Transaction)>, BuildError> {
-    let mut transactions = Vec::new();
+    //? Prepare inputs serially, since this draws from the rng and reads merkle
+    //? proofs from the trees. Only proving runs concurrently.
+    let mut prepared = Vec::new();
     for operation in operations {
-        operation.verify()?;
+        operation
+            .verify()
+            .map_err(|e| BuildError::from(e).in_operation(operation))?;
 
         let tree_number = operation.utxo_tree_number();
         let tree = utxo_trees
             .get(&tree_number)
-            .ok_or(BuildError::MissingTree(tree_number))?;
+            .ok_or_else(|| BuildError::MissingTree(tree_number).in_operation(operation))?;
 
-        let tx = create_transaction(
-            prover,
+        let (inputs, bound_params) = prepare_transaction(
             tree,
             operation,
             chain,
@@ -768,17 +1306,23 @@ async fn create_transactions<R: Rng>(
             adapt_input,
             rng,
         )
-        .await?;
-
-        transactions.push(tx);
+        .map_err(|e| e.in_operation(operation))?;
+        prepared.push((operation, inputs, bound_params));
     }
 
-    Ok(transactions)
+    stream::iter(prepared)
+        .map(|(operation, inputs, bound_params)| async move {
+            prove_transaction(prover, operation, inputs, bound_params)
+                .await
+                .map_err(|e| e.in_operation(operation))
+        })
+        .buffered(MAX_CONCURRENT_PROOFS)
+        .try_collect()
+        .await
 }
 
-/// Creates a railgun transaction for a single operation.
-async fn create_transaction<R: Rng>(
-    prover: &dyn TransactProver,
+/// Prepares the circuit inputs and bound params for a single operation.
+fn prepare_transaction<R: Rng>(
     utxo_tree: &UtxoMerkleTree,
     operation: &Operation<UtxoNote>,
     chain: ChainConfig,
@@ -786,7 +1330,7 @@ async fn create_transaction<R: Rng>(
     adapt_contract: Address,
     adapt_input: &[u8; 32],
     rng: &mut R,
-) -> Result<(TransactCircuitInputs, abis::railgun::Transaction), BuildError> {
+) -> Result<(TransactCircuitInputs, abis::railgun::BoundParams), BuildError> {
     let notes_in = operation.in_notes();
     let notes_out = operation.out_notes();
 
@@ -815,6 +1359,16 @@ async fn create_transaction<R: Rng>(
     let inputs =
         TransactCircuitInputs::from_inputs(utxo_tree, bound_params.hash(), notes_in, &notes_out)?;
 
+    Ok((inputs, bound_params))
+}
+
+/// Proves a prepared operation and creates its railgun transaction.
+async fn prove_transaction(
+    prover: &dyn TransactProver,
+    operation: &Operation<UtxoNote>,
+    inputs: TransactCircuitInputs,
+    bound_params: abis::railgun::BoundParams,
+) -> Result<(TransactCircuitInputs, abis::railgun::Transaction), BuildError> {
     info!("Proving transaction");
     let (proof, _) = prover
         .prove_transact(&inputs)
@@ -846,3 +1400,672 @@ fn calculate_fee(gas_cost: u128, gas_price_wei: u128, fee_rate: u128) -> u128 {
     let raw = (gas_cost * gas_price_wei * fee_rate) / 10_u128.pow(18);
     ((raw as f64) * FEE_BUFFER).ceil() as u128
 }
+
+/// Prices `gas` at `gas_price_wei` in a token worth `token_per_eth` base units
+/// per ether.
+fn gas_cost_in_token(gas: u128, gas_price_wei: u128, token_per_eth: u128) -> u128 {
+    let cost = U256::from(gas) * U256::from(gas_price_wei) * U256::from(token_per_eth)
+        / U256::from(10u128.pow(18));
+    cost.saturating_to()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+    use tracing_test::traced_test;
+
+    use rand_chacha::{ChaChaRng, rand_core::SeedableRng};
+
+    use super::*;
+    use crate::{
+        chain_config::MAINNET_CONFIG,
+        circuit::{
+            inputs::PoiCircuitInputs,
+            native::Groth16Prover,
+            proof::{G1Affine, G2Affine, Proof},
+            prover::PublicInputs,
+        },
+        crypto::keys::{ByteKey, SpendingKey, ViewingKey},
+        railgun::{
+            indexer::test_indexer,
+            note::{
+                Note,
+                utxo::{UtxoNote, UtxoType, test_note},
+            },
+            poi::TestPoiNode,
+            signer::{PrivateKeySigner, test_signer},
+        },
+    };
+
+    #[test]
+    #[traced_test]
+    fn test_sort_operations_deterministic() {
+        let signer_a = test_signer();
+        let signer_b = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([3u8; 32]),
+            ViewingKey::from_bytes([4u8; 32]),
+            1,
+        );
+        let asset_a = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let asset_b = AssetId::Erc20(address!("0x2222222222222222222222222222222222222222"));
+
+        let operations = || {
+            let mut fee_op = Operation::<UtxoNote>::new_empty(1, signer_b.clone(), asset_b);
+            fee_op.fee_note = Some(TransferNote::new(
+                ViewingKey::from_bytes([4u8; 32]),
+                signer_a.address(),
+                asset_b,
+                1,
+                [0u8; 16],
+                "fee",
+            ));
+
+            vec![
+                Operation::new_empty(1, signer_a.clone(), asset_b),
+                Operation::new_empty(0, signer_b.clone(), asset_a),
+                fee_op,
+                Operation::new_empty(0, signer_a.clone(), asset_a),
+                Operation::new_empty(1, signer_a.clone(), asset_a),
+            ]
+        };
+        let key = |o: &Operation<UtxoNote>| {
+            (
+                o.fee_note.is_some(),
+                o.utxo_tree_number,
+                o.asset,
+                o.from.address(),
+            )
+        };
+
+        let mut forward = operations();
+        let mut reversed = operations();
+        reversed.reverse();
+        sort_operations(&mut forward);
+        sort_operations(&mut reversed);
+
+        let forward: Vec<_> = forward.iter().map(key).collect();
+        let reversed: Vec<_> = reversed.iter().map(key).collect();
+        assert_eq!(forward, reversed);
+
+        // Fee operation first, then ordered by tree number
+        assert!(forward[0].0);
+        let trees: Vec<_> = forward[1..].iter().map(|k| k.1).collect();
+        assert_eq!(trees, vec![0, 0, 1, 1]);
+    }
+
+    /// Each operation's unshield must be its final commitment, even when the
+    /// change note is added after the unshield.
+    #[test]
+    #[traced_test]
+    fn test_multi_asset_unshield_ordering() {
+        let signer = test_signer();
+        let in_note = test_note();
+        let asset_a = in_note.asset();
+        let asset_b = AssetId::Erc20(address!("0x2222222222222222222222222222222222222222"));
+        let recipient = address!("0x3333333333333333333333333333333333333333");
+        let mut rng = ChaChaRng::seed_from_u64(0);
+
+        let mut op_a = Operation::<UtxoNote>::new_empty(1, signer.clone(), asset_a);
+        op_a.in_notes.push(in_note);
+        op_a.unshield_note = Some(UnshieldNote::new(recipient, asset_a, 40));
+        let op_a = add_change_note(op_a, &mut rng);
+        assert_eq!(op_a.out_notes.len(), 1);
+
+        let mut op_b = Operation::<UtxoNote>::new_empty(0, signer.clone(), asset_b);
+        op_b.unshield_note = Some(UnshieldNote::new(recipient, asset_b, 10));
+        op_b.out_notes.push(TransferNote::new(
+            ViewingKey::from_bytes([2u8; 32]),
+            signer.address(),
+            asset_b,
+            5,
+            [0u8; 16],
+            "change",
+        ));
+
+        let mut operations = vec![op_a, op_b];
+        sort_operations(&mut operations);
+
+        for operation in &operations {
+            let unshield = operation.unshield_note().unwrap();
+            let notes_out = operation.out_notes();
+            assert_eq!(notes_out.len(), 2);
+            assert_eq!(notes_out.last().unwrap().hash(), unshield.hash());
+        }
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_select_in_notes_insufficient_balance() {
+        let signer = test_signer();
+        let in_note = test_note();
+        let asset = in_note.asset();
+
+        let selected = select_in_notes(
+            signer.address(),
+            asset,
+            100,
+            vec![in_note.clone()],
+            NoteSelectionStrategy::InOrder,
+            None,
+        );
+        assert_eq!(selected.unwrap().len(), 1);
+
+        let err = select_in_notes(
+            signer.address(),
+            asset,
+            150,
+            vec![in_note],
+            NoteSelectionStrategy::InOrder,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            BuildError::InsufficientBalance {
+                needed: 150,
+                available: 100,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_select_in_notes_prefer_empty_memo() {
+        let signer = test_signer();
+        let memo_note = test_note();
+        let empty_note: UtxoNote = UtxoNote::new(
+            1,
+            1,
+            signer.clone(),
+            memo_note.asset(),
+            100,
+            [4u8; 16],
+            "",
+            UtxoType::Transact,
+        );
+        let in_notes = vec![memo_note.clone(), empty_note.clone()];
+
+        let selected = select_in_notes(
+            signer.address(),
+            memo_note.asset(),
+            100,
+            in_notes.clone(),
+            NoteSelectionStrategy::InOrder,
+            None,
+        )
+        .unwrap();
+        assert_eq!(selected, vec![memo_note]);
+
+        let selected = select_in_notes(
+            signer.address(),
+            empty_note.asset(),
+            100,
+            in_notes,
+            NoteSelectionStrategy::PreferEmptyMemo,
+            None,
+        )
+        .unwrap();
+        assert_eq!(selected, vec![empty_note]);
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_select_in_notes_max_inputs() {
+        let signer = test_signer();
+        let asset = test_note().asset();
+        let in_notes: Vec<UtxoNote> = [10, 10, 100]
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| {
+                UtxoNote::new(
+                    1,
+                    i as u32,
+                    signer.clone(),
+                    asset,
+                    value,
+                    [i as u8; 16],
+                    "",
+                    UtxoType::Transact,
+                )
+            })
+            .collect();
+
+        //? In order would take all three notes, so the largest are used instead
+        let selected = select_in_notes(
+            signer.address(),
+            asset,
+            110,
+            in_notes.clone(),
+            NoteSelectionStrategy::InOrder,
+            Some(2),
+        )
+        .unwrap();
+        assert_eq!(selected, vec![in_notes[2].clone(), in_notes[0].clone()]);
+
+        let err = select_in_notes(
+            signer.address(),
+            asset,
+            110,
+            in_notes,
+            NoteSelectionStrategy::InOrder,
+            Some(1),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            BuildError::TooManyInputsRequired { needed: 2, max: 1 }
+        ));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_select_consolidation_notes() {
+        let signer = test_signer();
+        let asset = test_note().asset();
+        let note = |tree_number: u32, leaf_index: u32, value: u128| {
+            UtxoNote::new(
+                tree_number,
+                leaf_index,
+                signer.clone(),
+                asset,
+                value,
+                [leaf_index as u8; 16],
+                "",
+                UtxoType::Transact,
+            )
+        };
+        let in_notes = vec![
+            note(1, 0, 50),
+            note(1, 1, 10),
+            note(1, 2, 30),
+            note(2, 0, 5),
+        ];
+
+        //? Tree 1 holds the most notes, so the tree 2 note is skipped
+        let selected = select_consolidation_notes(signer.address(), asset, 2, &in_notes).unwrap();
+        assert_eq!(selected, vec![in_notes[1].clone(), in_notes[2].clone()]);
+
+        let other = AssetId::Erc20(address!("0x2222222222222222222222222222222222222222"));
+        let err = select_consolidation_notes(signer.address(), other, 2, &in_notes).unwrap_err();
+        assert!(matches!(err, BuildError::NothingToConsolidate { .. }));
+
+        let err = select_consolidation_notes(signer.address(), asset, 50, &in_notes).unwrap_err();
+        assert!(matches!(err, BuildError::MaxInputsTooLarge(50)));
+
+        //? The change note carries the full consolidated value back to the sender
+        let mut operation = Operation::new_empty(1, signer.clone(), asset);
+        operation.in_notes = selected;
+        let operation = add_change_note(operation, &mut ChaChaRng::seed_from_u64(0));
+        assert_eq!(operation.out_notes.len(), 1);
+        assert_eq!(operation.out_notes[0].to, signer.address());
+        assert_eq!(operation.out_notes[0].value, 40);
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_transfer_reveal_sender() {
+        let signer = test_signer();
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let indexer = test_indexer(signer.clone(), asset, &[100]);
+
+        let builder = TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .transfer(signer.clone(), signer.address(), asset, 10, "", true)
+            .transfer(signer.clone(), signer.address(), asset, 20, "", false);
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let operations = builder
+            .build_operations(builder.spendable_notes(), &mut rng)
+            .unwrap();
+
+        let out: Vec<_> = operations[0]
+            .out_notes
+            .iter()
+            .map(|note| (note.value, note.reveal_sender))
+            .collect();
+        assert!(out.contains(&(10, true)));
+        assert!(out.contains(&(20, false)));
+        //? The change note keeps the sender blinded
+        assert!(out.contains(&(70, false)));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_with_adapt() {
+        let signer = test_signer();
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let indexer = test_indexer(signer.clone(), asset, &[100]);
+        let adapt_contract = address!("0x2222222222222222222222222222222222222222");
+
+        let builder = TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .transfer(signer.clone(), signer.address(), asset, 40, "", false)
+            .with_adapt(adapt_contract, &[7u8; 32]);
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let operations = builder
+            .build_operations(builder.spendable_notes(), &mut rng)
+            .unwrap();
+        let proved = builder
+            .prove_operations(
+                &MockTransactProver,
+                &indexer.utxo_trees,
+                &operations,
+                MAINNET_CONFIG,
+                0,
+                &mut rng,
+            )
+            .await
+            .unwrap();
+
+        //? Every operation's proof is bound to the adapt contract and params
+        for proved_operation in proved.proved_operations {
+            let bound_params = proved_operation.transaction.boundParams;
+            assert_eq!(bound_params.adaptContract, adapt_contract);
+            assert_eq!(bound_params.adaptParams.0, [7u8; 32]);
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_with_adapt_invalid_params() {
+        let signer = test_signer();
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let indexer = test_indexer(signer.clone(), asset, &[100]);
+
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let err = TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .transfer(signer.clone(), signer.address(), asset, 40, "", false)
+            .with_adapt(Address::ZERO, &[7u8; 31])
+            .build(&mut rng)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BuildError::InvalidAdaptParams(31)));
+    }
+
+    #[test]
+    fn test_with_max_inputs() {
+        let signer = test_signer();
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let indexer = test_indexer(signer.clone(), asset, &[100]);
+
+        let builder = TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .with_max_inputs(MAX_CIRCUIT_INPUTS)
+            .unwrap();
+        assert_eq!(builder.max_inputs, Some(MAX_CIRCUIT_INPUTS));
+
+        let err = TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .with_max_inputs(MAX_CIRCUIT_INPUTS + 1)
+            .err()
+            .unwrap();
+        assert!(matches!(err, BuildError::MaxInputsTooLarge(14)));
+
+        //? Consolidations are checked when building, before any proving
+        let builder = TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .consolidate(signer.clone(), asset, 50);
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        assert!(matches!(
+            builder.build_operations(builder.spendable_notes(), &mut rng),
+            Err(BuildError::MaxInputsTooLarge(50))
+        ));
+    }
+
+    #[test]
+    fn test_gas_cost_in_token() {
+        // 100k gas at 20 gwei is 0.002 ETH, or 6 USDC at 3000 USDC/ETH
+        assert_eq!(
+            gas_cost_in_token(100_000, 20_000_000_000, 3_000_000_000),
+            6_000_000
+        );
+        // 18-decimal tokens would overflow u128 without widening
+        assert_eq!(
+            gas_cost_in_token(10_000_000, 1_000_000_000_000, 3_000 * 10u128.pow(18)),
+            30_000 * 10u128.pow(18)
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_check_can_spend() {
+        let signer: Arc<dyn Signer> = test_signer();
+        let other = PrivateKeySigner::new_evm(
+            SpendingKey::from_bytes([5u8; 32]),
+            ViewingKey::from_bytes([6u8; 32]),
+            1,
+        );
+        let watch_only: Arc<dyn Signer> = Arc::new(other.export_watch_only());
+
+        assert!(check_can_spend([&signer]).is_ok());
+        assert!(matches!(
+            check_can_spend([&signer, &watch_only]),
+            Err(BuildError::WatchOnly(address)) if address == other.address()
+        ));
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_build_operations_watch_only() {
+        let signer = test_signer();
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let indexer = test_indexer(signer.clone(), asset, &[100]);
+        let watch_only: Arc<dyn Signer> = Arc::new(signer.export_watch_only());
+
+        //? Fails before any notes are signed, since signing would panic
+        let builder = TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .transfer(watch_only, signer.address(), asset, 10, "", false);
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        assert!(matches!(
+            builder.build_operations(builder.spendable_notes(), &mut rng),
+            Err(BuildError::WatchOnly(address)) if address == signer.address()
+        ));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_operation_error_context() {
+        let signer = test_signer();
+        let in_note = test_note();
+        let mut operation =
+            Operation::new_empty(in_note.tree_number(), signer.clone(), in_note.asset());
+        operation.in_notes.push(in_note.clone());
+
+        let prover = Groth16Prover::new_native("./does-not-exist");
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let err = create_transactions(
+            &prover,
+            &BTreeMap::new(),
+            &[operation],
+            crate::chain_config::MAINNET_CONFIG,
+            0,
+            Address::ZERO,
+            &[0u8; 32],
+            &mut rng,
+        )
+        .await
+        .unwrap_err();
+
+        let BuildError::Operation { from, asset, .. } = &err else {
+            panic!("expected operation context, got {err}");
+        };
+        assert_eq!(*from, signer.address());
+        assert_eq!(*asset, in_note.asset());
+        assert!(matches!(err.root(), BuildError::MissingTree(1)));
+    }
+
+    struct MockTransactProver;
+
+    #[async_trait::async_trait]
+    impl TransactProver for MockTransactProver {
+        async fn prove_transact(
+            &self,
+            _inputs: &TransactCircuitInputs,
+        ) -> Result<(Proof, PublicInputs), Box<dyn std::error::Error>> {
+            let point = || G1Affine {
+                x: U256::ZERO,
+                y: U256::ZERO,
+            };
+            let proof = Proof {
+                a: point(),
+                b: G2Affine {
+                    x: [U256::ZERO; 2],
+                    y: [U256::ZERO; 2],
+                },
+                c: point(),
+            };
+            Ok((proof, vec![]))
+        }
+    }
+
+    struct UnavailablePoiProver;
+
+    #[async_trait::async_trait]
+    impl PoiProver for UnavailablePoiProver {
+        async fn prove_poi(
+            &self,
+            _inputs: &PoiCircuitInputs,
+        ) -> Result<(Proof, PublicInputs), Box<dyn std::error::Error>> {
+            Err("POI prover unavailable".into())
+        }
+    }
+
+    async fn build_with_policy(policy: PoiPolicy) -> Result<PoiProvedTransaction, BuildError> {
+        let signer = test_signer();
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let indexer = test_indexer(signer.clone(), asset, &[100]);
+        let poi_client = PoiClient::with_transport(
+            TestPoiNode {
+                validated_txid_index: 0,
+            },
+            1,
+        )
+        .await
+        .unwrap();
+
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .transfer(signer.clone(), signer.address(), asset, 40, "", false)
+            .with_poi(&poi_client, &UnavailablePoiProver)
+            .with_poi_policy(policy)
+            .build(&mut rng)
+            .await
+    }
+
+    /// Prices gas but can't estimate it, so building a broadcast fails.
+    struct PriceOnlyEstimator;
+
+    #[async_trait::async_trait]
+    impl GasEstimator for PriceOnlyEstimator {
+        async fn estimate_gas(
+            &self,
+            _tx_data: &TxData,
+        ) -> Result<u128, Box<dyn std::error::Error>> {
+            Err("gas estimation unavailable".into())
+        }
+
+        async fn gas_price_wei(&self) -> Result<u128, Box<dyn std::error::Error>> {
+            Ok(1_000_000_000)
+        }
+    }
+
+    async fn build_auto_with(
+        fee: Option<Fee>,
+        policy: BroadcastPolicy,
+    ) -> Result<BroadcastChoice, BuildError> {
+        let signer = test_signer();
+        let asset = AssetId::Erc20(address!("0x1111111111111111111111111111111111111111"));
+        let indexer = test_indexer(signer.clone(), asset, &[100]);
+        let poi_client = PoiClient::with_transport(
+            TestPoiNode {
+                validated_txid_index: 0,
+            },
+            1,
+        )
+        .await
+        .unwrap();
+
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        TransactionBuilder::new(&indexer, &MockTransactProver, MAINNET_CONFIG)
+            .transfer(signer.clone(), signer.address(), asset, 40, "", false)
+            .with_poi(&poi_client, &UnavailablePoiProver)
+            .with_poi_policy(PoiPolicy::Disabled)
+            .build_auto(&PriceOnlyEstimator, signer.clone(), fee, policy, &mut rng)
+            .await
+    }
+
+    fn test_fee(per_unit_gas: u128) -> Fee {
+        let signer = test_signer();
+        Fee {
+            token: address!("0x1111111111111111111111111111111111111111"),
+            per_unit_gas,
+            recipient: signer.address(),
+            expiration: 0,
+            fees_id: String::new(),
+            available_wallets: 1,
+            relay_adapt: Address::ZERO,
+            reliability: 100,
+            list_keys: vec![],
+        }
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_build_auto_max_privacy_requires_broadcaster() {
+        let err = build_auto_with(None, BroadcastPolicy::MaxPrivacy)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BuildError::NoBroadcaster));
+
+        let choice = build_auto_with(None, BroadcastPolicy::SelfOnly)
+            .await
+            .unwrap();
+        assert!(matches!(choice, BroadcastChoice::SelfBroadcast(_)));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_build_auto_cheapest_builds_one_path() {
+        //? The broadcaster charges twice the token's price of gas, so only the
+        //? self-broadcast is built. Building the broadcast would need a gas
+        //? estimate, which `PriceOnlyEstimator` refuses.
+        let choice = build_auto_with(
+            Some(test_fee(2 * 10u128.pow(18))),
+            BroadcastPolicy::Cheapest {
+                token_per_eth: 10u128.pow(18),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(choice, BroadcastChoice::SelfBroadcast(_)));
+
+        let err = build_auto_with(
+            Some(test_fee(10u128.pow(17))),
+            BroadcastPolicy::Cheapest {
+                token_per_eth: 10u128.pow(18),
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err.root(), BuildError::Estimator(_)));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_build_poi_disabled() {
+        let tx = build_with_policy(PoiPolicy::Disabled).await.unwrap();
+
+        assert_eq!(tx.operations.len(), 1);
+        assert!(tx.operations[0].pois.is_empty());
+        assert!(tx.operations[0].txid_leaf_hash.is_some());
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_build_poi_optional_fallback() {
+        let tx = build_with_policy(PoiPolicy::Optional).await.unwrap();
+
+        assert_eq!(tx.operations.len(), 1);
+        assert!(tx.operations[0].pois.is_empty());
+        assert!(tx.operations[0].txid_leaf_hash.is_some());
+
+        //? The same failure fails the build when POIs are required
+        let err = build_with_policy(PoiPolicy::Required).await.unwrap_err();
+        assert!(matches!(err.root(), BuildError::PoiClient(_)));
+    }
+}
@@ -6,6 +6,15 @@ use alloy_sol_types::SolCall;
 
 use crate::abis::railgun::{RailgunSmartWallet, Transaction};
 
+/// Estimated gas for the EVM transaction itself and the transact call overhead.
+const BASE_GAS: u128 = 60_000;
+/// Estimated gas per railgun transaction, dominated by proof verification.
+const TRANSACTION_GAS: u128 = 250_000;
+/// Estimated gas per nullifier spent.
+const NULLIFIER_GAS: u128 = 25_000;
+/// Estimated gas per commitment inserted into the merkle tree.
+const COMMITMENT_GAS: u128 = 40_000;
+
 #[derive(Debug, Clone)]
 pub struct TxData {
     pub to: Address,
@@ -30,6 +39,60 @@ impl TxData {
             value: U256::ZERO,
         }
     }
+
+    /// Packs railgun transactions into as few EVM transactions as possible,
+    /// keeping each EVM transaction's estimated gas at or below `max_gas`.
+    ///
+    /// Transaction order is preserved. A single railgun transaction whose
+    /// estimate exceeds `max_gas` is placed in its own EVM transaction.
+    pub fn pack_transactions(
+        to: Address,
+        transactions: Vec<Transaction>,
+        max_gas: u128,
+    ) -> Vec<Self> {
+        pack_by_gas(transactions, BASE_GAS, max_gas, estimate_transaction_gas)
+            .into_iter()
+            .map(|batch| TxData::from_transactions(to, batch))
+            .collect()
+    }
+}
+
+/// Estimates the gas cost of a single railgun transaction within a transact
+/// call. This is a conservative heuristic, not a substitute for an RPC estimate.
+pub fn estimate_transaction_gas(transaction: &Transaction) -> u128 {
+    TRANSACTION_GAS
+        + NULLIFIER_GAS * transaction.nullifiers.len() as u128
+        + COMMITMENT_GAS * transaction.commitments.len() as u128
+}
+
+/// Greedily groups items in order such that each group's total gas, including
+/// `base_gas`, stays within `max_gas`.
+fn pack_by_gas<T>(
+    items: Vec<T>,
+    base_gas: u128,
+    max_gas: u128,
+    gas_of: impl Fn(&T) -> u128,
+) -> Vec<Vec<T>> {
+    let mut batches: Vec<Vec<T>> = Vec::new();
+    let mut current: Vec<T> = Vec::new();
+    let mut current_gas = base_gas;
+
+    for item in items {
+        let gas = gas_of(&item);
+        if !current.is_empty() && current_gas + gas > max_gas {
+            batches.push(std::mem::take(&mut current));
+            current_gas = base_gas;
+        }
+
+        current_gas += gas;
+        current.push(item);
+    }
+
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    batches
 }
 
 impl From<TxData> for TransactionRequest {
@@ -40,3 +103,26 @@ impl From<TxData> for TransactionRequest {
             .value(tx_data.value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_by_gas() {
+        let items = vec![40u128, 30, 20, 50, 10, 200];
+        let batches = pack_by_gas(items, 10, 100, |g| *g);
+
+        assert_eq!(
+            batches,
+            vec![vec![40, 30, 20], vec![50, 10], vec![200]],
+            "Batches should be filled in order and oversized items isolated"
+        );
+    }
+
+    #[test]
+    fn test_pack_by_gas_empty() {
+        let batches = pack_by_gas(Vec::<u128>::new(), 10, 100, |g| *g);
+        assert!(batches.is_empty());
+    }
+}
@@ -96,6 +96,21 @@ impl JsChainConfig {
     pub fn poi_endpoint(&self) -> Option<String> {
         self.inner.poi_endpoint.map(|s| s.to_string())
     }
+
+    #[wasm_bindgen(getter, js_name = "artifactsPath")]
+    pub fn artifacts_path(&self) -> String {
+        self.inner.artifacts_path.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = "shieldFeeBps")]
+    pub fn shield_fee_bps(&self) -> u16 {
+        self.inner.shield_fee_bps
+    }
+
+    #[wasm_bindgen(getter, js_name = "unshieldFeeBps")]
+    pub fn unshield_fee_bps(&self) -> u16 {
+        self.inner.unshield_fee_bps
+    }
 }
 
 /// Get chain config by chain ID. Returns undefined if chain is not supported.
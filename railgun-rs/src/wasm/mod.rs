@@ -4,6 +4,7 @@ mod indexer;
 mod poi_client;
 mod prover;
 mod provider;
+mod railgun;
 mod transaction;
 
 pub use bindings::{
@@ -12,6 +13,7 @@ pub use bindings::{
 pub use broadcaster::JsBroadcasterManager;
 pub use indexer::{JsIndexer, JsSyncer};
 pub use prover::{JsProofResponse, JsProver};
+pub use railgun::JsRailgun;
 pub use transaction::{JsShieldBuilder, JsTransactionBuilder, JsTxData};
 use wasm_bindgen::prelude::wasm_bindgen;
 
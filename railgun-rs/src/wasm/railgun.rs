@@ -0,0 +1,227 @@
+use alloy::primitives::Address;
+use wasm_bindgen::{JsError, prelude::wasm_bindgen};
+
+use crate::{
+    caip::AssetId,
+    railgun::{
+        address::RailgunAddress,
+        transaction::{ShieldBuilder, TransactionBuilder},
+    },
+    wasm::{
+        JsBroadcasterManager, JsProver, JsRailgunAccount, JsTxData, indexer::JsIndexer,
+        poi_client::JsPoiClient, provider::JsProvider,
+    },
+};
+
+/// Single entry point for shielding, transferring, unshielding, and
+/// broadcasting. Orchestrates the indexer, prover, POI client, and
+/// broadcasters so they don't need to be wired together by hand.
+///
+/// @example
+/// ```typescript
+/// const railgun = new JsRailgun(indexer, prover, poiClient, provider, broadcasters);
+/// railgun.addAccount(account);
+/// const shieldTx = railgun.shield(account.address, wasm.erc20_asset("0x..."), "100");
+/// const transferTx = await railgun.transfer(account, "0zk...", wasm.erc20_asset("0x..."), "10", "");
+/// const txHash = await railgun.broadcast(account, "0zk...", wasm.erc20_asset("0x..."), "10", "", "0x...");
+/// ```
+#[wasm_bindgen]
+pub struct JsRailgun {
+    indexer: JsIndexer,
+    prover: JsProver,
+    poi_client: JsPoiClient,
+    provider: JsProvider,
+    broadcasters: JsBroadcasterManager,
+}
+
+#[wasm_bindgen]
+impl JsRailgun {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        indexer: JsIndexer,
+        prover: JsProver,
+        poi_client: JsPoiClient,
+        provider: JsProvider,
+        broadcasters: JsBroadcasterManager,
+    ) -> JsRailgun {
+        JsRailgun {
+            indexer,
+            prover,
+            poi_client,
+            provider,
+            broadcasters,
+        }
+    }
+
+    /// Tracks an account's notes and balances as the indexer syncs.
+    #[wasm_bindgen(js_name = "addAccount")]
+    pub fn add_account(&mut self, account: &JsRailgunAccount) {
+        self.indexer.add_account(account);
+    }
+
+    /// Syncs the indexer to the latest block.
+    pub async fn sync(&mut self) -> Result<(), JsError> {
+        self.indexer.sync().await
+    }
+
+    /// Builds a shield transaction. Shields must be self-broadcast.
+    ///
+    /// - `recipient`: Railgun address (0zk...)
+    /// - `asset`: Asset ID (e.g., "erc20:0x...")
+    /// - `amount`: Amount as decimal string
+    pub fn shield(&self, recipient: &str, asset: &str, amount: &str) -> Result<JsTxData, JsError> {
+        let recipient = parse_railgun_address(recipient)?;
+        let asset = parse_asset(asset)?;
+        let amount = parse_amount(amount)?;
+
+        let tx_data = ShieldBuilder::new(self.indexer.chain())
+            .shield(recipient, asset, amount)
+            .build()
+            .map_err(|e| JsError::new(&format!("Failed to build shield: {}", e)))?;
+
+        Ok(JsTxData { inner: tx_data })
+    }
+
+    /// Syncs, then builds a self-broadcast private transfer.
+    ///
+    /// - `to`: Railgun address (0zk...)
+    /// - `asset`: Asset ID (e.g., "erc20:0x...")
+    /// - `amount`: Amount as decimal string
+    /// - `memo`: Optional memo string
+    pub async fn transfer(
+        &mut self,
+        from: &JsRailgunAccount,
+        to: &str,
+        asset: &str,
+        amount: &str,
+        memo: &str,
+    ) -> Result<JsTxData, JsError> {
+        let to = parse_railgun_address(to)?;
+        let asset = parse_asset(asset)?;
+        let amount = parse_amount(amount)?;
+
+        self.sync().await?;
+        let tx_data = self
+            .transact()
+            .transfer(from.inner().clone(), to, asset, amount, memo, false)
+            .build(&mut rand::rng())
+            .await
+            .map_err(|e| JsError::new(&format!("Failed to build transfer: {}", e)))?;
+
+        Ok(JsTxData { inner: tx_data })
+    }
+
+    /// Syncs, then builds a self-broadcast unshield.
+    ///
+    /// - `to`: Ethereum address (0x...)
+    /// - `asset`: Asset ID (e.g., "erc20:0x...")
+    /// - `amount`: Amount as decimal string
+    pub async fn unshield(
+        &mut self,
+        from: &JsRailgunAccount,
+        to: &str,
+        asset: &str,
+        amount: &str,
+    ) -> Result<JsTxData, JsError> {
+        let to = parse_address(to)?;
+        let asset = parse_asset(asset)?;
+        let amount = parse_amount(amount)?;
+
+        self.sync().await?;
+        let tx_data = self
+            .transact()
+            .set_unshield(from.inner().clone(), to, asset, amount)
+            .build(&mut rand::rng())
+            .await
+            .map_err(|e| JsError::new(&format!("Failed to build unshield: {}", e)))?;
+
+        Ok(JsTxData { inner: tx_data })
+    }
+
+    /// Syncs, then privately transfers through the cheapest available
+    /// broadcaster, paying its fee from `from` in `fee_token`. Returns the
+    /// transaction hash.
+    ///
+    /// - `to`: Railgun address (0zk...)
+    /// - `asset`: Asset ID (e.g., "erc20:0x...")
+    /// - `amount`: Amount as decimal string
+    /// - `memo`: Optional memo string
+    /// - `fee_token`: ERC20 token address (0x...) to pay the broadcaster fee in
+    pub async fn broadcast(
+        &mut self,
+        from: &JsRailgunAccount,
+        to: &str,
+        asset: &str,
+        amount: &str,
+        memo: &str,
+        fee_token: &str,
+    ) -> Result<String, JsError> {
+        let to = parse_railgun_address(to)?;
+        let asset = parse_asset(asset)?;
+        let amount = parse_amount(amount)?;
+        let fee_token = parse_address(fee_token)?;
+
+        let now = js_sys::Date::now() as u64;
+        let broadcaster = self
+            .broadcasters
+            .inner()
+            .best_broadcaster_for_token(fee_token, now)
+            .await
+            .ok_or_else(|| {
+                JsError::new(&format!("No broadcaster available for token {}", fee_token))
+            })?;
+
+        self.sync().await?;
+        let mut rng = rand::rng();
+        let tx = self
+            .transact()
+            .transfer(from.inner().clone(), to, asset, amount, memo, false)
+            .with_broadcast(
+                self.poi_client.inner(),
+                &self.prover,
+                self.provider.inner(),
+                from.inner().clone(),
+                broadcaster.fee.clone(),
+            )
+            .build(&mut rng)
+            .await
+            .map_err(|e| JsError::new(&format!("Failed to build transaction: {}", e)))?;
+
+        let tx_hash = broadcaster
+            .broadcast(&tx, &mut rng)
+            .await
+            .map_err(|e| JsError::new(&format!("Broadcast error: {}", e)))?;
+
+        Ok(tx_hash.to_string())
+    }
+}
+
+impl JsRailgun {
+    fn transact(&self) -> TransactionBuilder<'_> {
+        TransactionBuilder::new(self.indexer.inner(), &self.prover, self.indexer.chain())
+    }
+}
+
+fn parse_railgun_address(address: &str) -> Result<RailgunAddress, JsError> {
+    address
+        .parse()
+        .map_err(|e| JsError::new(&format!("Invalid recipient address: {}", e)))
+}
+
+fn parse_address(address: &str) -> Result<Address, JsError> {
+    address
+        .parse()
+        .map_err(|e| JsError::new(&format!("Invalid address: {}", e)))
+}
+
+fn parse_asset(asset: &str) -> Result<AssetId, JsError> {
+    asset
+        .parse()
+        .map_err(|e| JsError::new(&format!("Invalid asset ID: {}", e)))
+}
+
+fn parse_amount(amount: &str) -> Result<u128, JsError> {
+    amount
+        .parse()
+        .map_err(|e| JsError::new(&format!("Invalid amount: {}", e)))
+}
@@ -22,7 +22,7 @@ use crate::{
 /// Transaction data output for EVM submission
 #[wasm_bindgen]
 pub struct JsTxData {
-    inner: TxData,
+    pub(crate) inner: TxData,
 }
 
 #[wasm_bindgen]
@@ -106,7 +106,7 @@ impl JsShieldBuilder {
         let shields: Result<Vec<ShieldRequest>, _> = self
             .shields
             .iter()
-            .map(|(r, a, v)| create_shield_request(*r, *a, *v, &mut rng))
+            .map(|(r, a, v)| create_shield_request(*r, (*a).into(), *v, &mut rng))
             .collect();
 
         let shields = shields
@@ -132,7 +132,7 @@ impl JsShieldBuilder {
 /// @example
 /// ```typescript
 /// const builder = new JsTransactionBuilder(account);
-/// builder.transfer("0zk...", wasm.erc20_asset("0x..."), "100", "Optional memo");
+/// builder.transfer("0zk...", wasm.erc20_asset("0x..."), "100", "Optional memo", false);
 /// builder.unshield("0x...", wasm.erc20_asset("0x..."), "50");
 /// const txData = await builder.build(indexer, prover);
 /// ```
@@ -156,6 +156,8 @@ impl JsTransactionBuilder {
     /// - `asset`: Asset ID (e.g., "erc20:0x...")
     /// - `amount`: Amount as decimal string
     /// - `memo`: Optional memo string
+    /// - `reveal_sender`: Let the recipient see the sender's address, e.g. to
+    ///   match a payment to an invoice. Pass `false` to blind the sender.
     pub fn transfer(
         &mut self,
         from: &JsRailgunAccount,
@@ -163,6 +165,7 @@ impl JsTransactionBuilder {
         asset: &str,
         amount: &str,
         memo: &str,
+        reveal_sender: bool,
     ) -> Result<(), JsError> {
         let to: RailgunAddress = to
             .parse()
@@ -176,9 +179,14 @@ impl JsTransactionBuilder {
             .parse()
             .map_err(|e| JsError::new(&format!("Invalid amount: {}", e)))?;
 
-        self.inner
-            .borrow_mut()
-            .transfer(from.inner().clone(), to, asset, amount, memo);
+        self.inner.borrow_mut().transfer(
+            from.inner().clone(),
+            to,
+            asset,
+            amount,
+            memo,
+            reveal_sender,
+        );
 
         Ok(())
     }
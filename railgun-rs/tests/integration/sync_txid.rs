@@ -1,11 +1,6 @@
-use std::sync::Arc;
-
 use railgun_rs::{
     chain_config::{ChainConfig, MAINNET_CONFIG},
-    railgun::{
-        indexer::{TxidIndexer, syncer::SubsquidSyncer},
-        poi::PoiClient,
-    },
+    railgun::indexer::TxidIndexer,
 };
 use tracing::info;
 use tracing_subscriber::EnvFilter;
@@ -23,19 +18,11 @@ async fn test_sync_txid() {
         .try_init()
         .ok();
 
-    info!("Setting up POI client");
-    let poi_client = PoiClient::new(CHAIN.poi_endpoint.unwrap(), CHAIN.id)
-        .await
-        .unwrap();
-
     info!("Setting up indexer");
-    let endpoint = CHAIN
-        .subsquid_endpoint
-        .expect("Subsquid endpoint must be set");
-
-    let subsquid_syncer = Arc::new(SubsquidSyncer::new(endpoint));
-    let mut indexer = TxidIndexer::new(subsquid_syncer, poi_client);
+    let mut indexer = TxidIndexer::for_chain(CHAIN).await.unwrap();
 
     info!("Syncing indexer");
     indexer.sync_to(FORK_BLOCK).await.unwrap();
+    assert_eq!(indexer.synced_block(), FORK_BLOCK);
+    assert!(indexer.txid_root(0).is_some());
 }
@@ -55,3 +55,17 @@ async fn test_sync_utxo() {
     let state = bitcode::serialize(&indexer.state()).unwrap();
     std::fs::write("./tests/fixtures/indexer_state.bincode", state).unwrap();
 }
+
+#[tokio::test]
+#[ignore]
+async fn test_probe_subsquid() {
+    let endpoint = CHAIN
+        .subsquid_endpoint
+        .expect("Subsquid endpoint must be set");
+
+    let latest_block = SubsquidSyncer::new(endpoint).probe().await.unwrap();
+    assert!(latest_block > FORK_BLOCK);
+
+    let invalid = SubsquidSyncer::new_probed("http://127.0.0.1:1/graphql").await;
+    assert!(invalid.is_err());
+}
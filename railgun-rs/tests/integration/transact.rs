@@ -108,6 +108,7 @@ async fn test_transact() {
             USDC,
             5_000,
             "test transfer",
+            false,
         )
         .build(&mut rand::rng())
         .await